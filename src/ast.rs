@@ -1,3 +1,7 @@
+use crate::{diagnostic::Diagnostic, eval::Number, lexer, symbol::Symbol};
+use num_bigint::{BigInt, BigUint};
+use num_traits::ToPrimitive;
+
 mod parse;
 
 type TS<'a, 'src> = &'a [crate::lexer::Token<'src>];
@@ -6,7 +10,9 @@ struct EpicWrapper(TS<'static, 'static>);
 
 impl EpicWrapper {
     fn epicify<'a, 'src>(ts: TS<'a, 'src>) -> EpicWrapper {
-        EpicWrapper(unsafe { std::slice::from_raw_parts(ts.as_ptr(), ts.len()) })
+        EpicWrapper(unsafe {
+            std::slice::from_raw_parts(ts.as_ptr().cast::<crate::lexer::Token<'static>>(), ts.len())
+        })
     }
 
     fn lmao<'a, 'src>(&'a self) -> TS<'a, 'src> {
@@ -41,42 +47,791 @@ impl Error {
     }
 }
 
+pub fn parse<'src>(tokens: &lexer::Tokens<'src>) -> anyhow::Result<Ast<'src>> {
+    let (_, ast) = parse::ast(tokens.as_slice())?;
+    Ok(ast)
+}
+
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub struct Ast<'src> {
-    expressions: Vec<Expression<'src>>,
+    pub statements: Vec<Statement<'src>>,
+    /// Source of each statement, in the same order, to point diagnostics about a part without
+    /// source of its own at, like the `{}` of `-{}`
+    pub sources: Vec<&'src str>,
+}
+
+/// One line, or a part of a line seperated by `;`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub enum Statement<'src> {
+    Definition(Definition<'src>),
+    FunctionDefinition(FunctionDefinition<'src>),
+    /// `x^2 - 4 = 0`, or any other relation
+    Equation(Relation<'src>),
+    Expression(Expression<'src>),
+    Derivation(Derivation<'src>),
+}
+
+impl<'src> Statement<'src> {
+    /// The name a definition gives a meaning to, like the `g` of `let g = 9.81`
+    pub(crate) fn defined_name(&self) -> Option<&'src str> {
+        match self {
+            Statement::Definition(definition) if definition.index.is_none() => {
+                Some(definition.identifier.text)
+            }
+            Statement::FunctionDefinition(function_definition) => {
+                Some(function_definition.identifier.text)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Steps of working out, like
+/// ```text
+/// x^2 - 4 = 0
+///   => (x-2)(x+2) = 0  "factor"
+///   => x = ±2
+/// ```
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Derivation<'src> {
+    pub steps: Vec<Step<'src>>,
+}
+
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Step<'src> {
+    pub kind: StepKind,
+    pub expression: Expression<'src>,
+    /// Text of the `"..."` after the step, without the quotes
+    pub justification: Option<&'src str>,
+    /// Source of the whole step, or [`None`] when it was worked out rather than written
+    pub text: Option<&'src str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StepKind {
+    /// First step
+    Start,
+    /// `=> x = 2`, follows from the step before
+    Implies,
+    /// `= x^2 + 2x + 1`, relates the right hand side of the step before to this one
+    Continues(RelationOperator),
+}
+
+/// `let g = 9.81`, or `let v_1 = 3`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Definition<'src> {
+    pub identifier: Identifier<'src>,
+    /// `1` in `let v_1 = 3`
+    pub index: Option<Expression<'src>>,
+    pub value: Expression<'src>,
+}
+
+impl<'src> Definition<'src> {
+    /// What's defined, as a variable or a subscript
+    pub fn name(&self) -> Expression<'src> {
+        let variable = Expression::Variable(Variable {
+            identifier: self.identifier.clone(),
+        });
+        match &self.index {
+            Some(index) => Expression::Subscript(Subscript {
+                base: Box::new(variable),
+                index: Box::new(index.clone()),
+            }),
+            None => variable,
+        }
+    }
+}
+
+/// `f(x) = x^2 + 1` or `let f(x) = x^2 + 1`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct FunctionDefinition<'src> {
+    pub identifier: Identifier<'src>,
+    pub parameters: Vec<Identifier<'src>>,
+    pub body: Expression<'src>,
 }
 
 /// Thing that gives a value
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub enum Expression<'src> {
-    Literal(Literal),
+    Literal(Literal<'src>),
+    /// A number worked out rather than written, like the result of simplifying
+    Number(Number),
+    Quantity(Quantity<'src>),
     Variable(Variable<'src>),
     FunctionCall(FunctionCall<'src>),
+    Subscript(Subscript<'src>),
+    Unary(Unary<'src>),
+    Binary(Binary<'src>),
+    Relation(Relation<'src>),
+    BigOperator(BigOperator<'src>),
+    Integral(Integral<'src>),
+    Limit(Limit<'src>),
+    Derivative(Derivative<'src>),
+    Vector(Vector<'src>),
+    Matrix(Matrix<'src>),
+    Piecewise(Piecewise<'src>),
+    Set(Set<'src>),
+    SetBuilder(SetBuilder<'src>),
+    Interval(Interval<'src>),
+    NumberSet(NumberSet),
+    Delimited(Delimited<'src>),
+    Group(Group<'src>),
+    List(List<'src>),
+}
+
+impl Expression<'_> {
+    /// How tightly the expression binds, for deciding where brackets go
+    pub fn precedence(&self) -> Precedence {
+        match self {
+            Expression::List(_) => Precedence::List,
+            Expression::Relation(_) => Precedence::Relation,
+            Expression::Binary(binary) => binary.operator.precedence(),
+            Expression::Unary(_) => Precedence::Prefix,
+            // They take everything to their right, like a product with the body
+            Expression::BigOperator(_) | Expression::Integral(_) | Expression::Limit(_) => {
+                Precedence::Product
+            }
+            Expression::Derivative(derivative) => derivative.precedence(),
+            Expression::Number(number) if number.is_negative() => Precedence::Prefix,
+            // Written as a fraction
+            Expression::Number(number) if number.to_integer().is_none() && number.is_exact() => {
+                Precedence::Product
+            }
+            Expression::Number(_) => Precedence::Atom,
+            // A number times its unit
+            Expression::Quantity(_) => Precedence::Product,
+            Expression::Literal(_)
+            | Expression::Variable(_)
+            | Expression::FunctionCall(_)
+            | Expression::Subscript(_)
+            | Expression::Vector(_)
+            | Expression::Matrix(_)
+            | Expression::Piecewise(_)
+            | Expression::Set(_)
+            | Expression::SetBuilder(_)
+            | Expression::Interval(_)
+            | Expression::NumberSet(_)
+            | Expression::Delimited(_)
+            | Expression::Group(_) => Precedence::Atom,
+        }
+    }
+
+    /// Without the brackets the user wrote around it, for places that group by layout
+    /// like a fraction or exponent
+    pub fn ungrouped(&self) -> &Expression<'_> {
+        match self {
+            Expression::Group(group) => group.inner.ungrouped(),
+            expression => expression,
+        }
+    }
+}
+
+impl<'src> Expression<'src> {
+    /// The expression with everything `f` gives [`Some`] for replaced, from the outside in. `f`
+    /// sees every part that isn't replaced, so it can also be used to look through them.
+    pub(crate) fn rewrite(
+        &self,
+        f: &mut dyn FnMut(&Expression<'src>) -> Option<Expression<'src>>,
+    ) -> Expression<'src> {
+        if let Some(rewritten) = f(self) {
+            return rewritten;
+        }
+        let mut rewrite = |expression: &Expression<'src>| Box::new(expression.rewrite(f));
+        match self {
+            Expression::Literal(_)
+            | Expression::Number(_)
+            | Expression::Variable(_)
+            | Expression::NumberSet(_) => self.clone(),
+            Expression::Quantity(quantity) => Expression::Quantity(Quantity {
+                value: rewrite(&quantity.value),
+                unit: quantity.unit.clone(),
+            }),
+            Expression::FunctionCall(function_call) => Expression::FunctionCall(FunctionCall {
+                identifier: function_call.identifier.clone(),
+                inputs: function_call
+                    .inputs
+                    .iter()
+                    .map(|input| *rewrite(input))
+                    .collect(),
+            }),
+            Expression::Subscript(subscript) => Expression::Subscript(Subscript {
+                base: rewrite(&subscript.base),
+                index: rewrite(&subscript.index),
+            }),
+            Expression::Unary(unary) => Expression::Unary(Unary {
+                operator: unary.operator,
+                operand: rewrite(&unary.operand),
+            }),
+            Expression::Binary(binary) => Expression::Binary(Binary {
+                operator: binary.operator,
+                left: rewrite(&binary.left),
+                right: rewrite(&binary.right),
+            }),
+            Expression::Relation(relation) => Expression::Relation(Relation {
+                first: rewrite(&relation.first),
+                rest: relation
+                    .rest
+                    .iter()
+                    .map(|(operator, right)| (*operator, *rewrite(right)))
+                    .collect(),
+            }),
+            Expression::BigOperator(big_operator) => Expression::BigOperator(BigOperator {
+                from: rewrite(&big_operator.from),
+                to: rewrite(&big_operator.to),
+                body: rewrite(&big_operator.body),
+                ..big_operator.clone()
+            }),
+            Expression::Integral(integral) => Expression::Integral(Integral {
+                integrand: rewrite(&integral.integrand),
+                variable: integral.variable.clone(),
+                bounds: integral
+                    .bounds
+                    .as_ref()
+                    .map(|(lower, upper)| (rewrite(lower), rewrite(upper))),
+            }),
+            Expression::Limit(limit) => Expression::Limit(Limit {
+                variable: limit.variable.clone(),
+                approaching: rewrite(&limit.approaching),
+                body: rewrite(&limit.body),
+            }),
+            Expression::Derivative(derivative) => Expression::Derivative(match derivative {
+                Derivative::Leibniz { variable, body } => Derivative::Leibniz {
+                    variable: variable.clone(),
+                    body: rewrite(body),
+                },
+                Derivative::Prime {
+                    function,
+                    order,
+                    inputs,
+                } => Derivative::Prime {
+                    function: function.clone(),
+                    order: *order,
+                    inputs: inputs
+                        .as_ref()
+                        .map(|inputs| inputs.iter().map(|input| *rewrite(input)).collect()),
+                },
+                Derivative::Partial { body, variables } => Derivative::Partial {
+                    body: rewrite(body),
+                    variables: variables.clone(),
+                },
+            }),
+            Expression::Vector(vector) => Expression::Vector(Vector {
+                elements: vector.elements.iter().map(|e| *rewrite(e)).collect(),
+            }),
+            Expression::Matrix(matrix) => Expression::Matrix(Matrix {
+                rows: matrix
+                    .rows
+                    .iter()
+                    .map(|row| row.iter().map(|e| *rewrite(e)).collect())
+                    .collect(),
+            }),
+            Expression::Piecewise(piecewise) => Expression::Piecewise(Piecewise {
+                arms: piecewise
+                    .arms
+                    .iter()
+                    .map(|arm| Arm {
+                        value: *rewrite(&arm.value),
+                        condition: *rewrite(&arm.condition),
+                    })
+                    .collect(),
+                otherwise: piecewise.otherwise.as_deref().map(&mut rewrite),
+            }),
+            Expression::Set(set) => Expression::Set(Set {
+                elements: set.elements.iter().map(|e| *rewrite(e)).collect(),
+            }),
+            Expression::SetBuilder(set_builder) => Expression::SetBuilder(SetBuilder {
+                variable: set_builder.variable.clone(),
+                domain: set_builder.domain.as_deref().map(&mut rewrite),
+                condition: rewrite(&set_builder.condition),
+            }),
+            Expression::Interval(interval) => Expression::Interval(Interval {
+                lower: rewrite(&interval.lower),
+                upper: rewrite(&interval.upper),
+                ..interval.clone()
+            }),
+            Expression::Delimited(delimited) => Expression::Delimited(Delimited {
+                kind: delimited.kind,
+                inner: rewrite(&delimited.inner),
+            }),
+            Expression::List(list) => Expression::List(List {
+                elements: list.elements.iter().map(|e| *rewrite(e)).collect(),
+            }),
+            Expression::Group(group) => Expression::Group(Group {
+                inner: rewrite(&group.inner),
+            }),
+        }
+    }
 }
 
+/// Lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Precedence {
+    List,
+    Relation,
+    Sum,
+    Product,
+    Prefix,
+    Power,
+    Atom,
+}
+
+/// Number exactly as it was written
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub enum Literal {
-    Whole(Whole),
-    Float(Float),
+pub enum Literal<'src> {
+    Whole(Whole<'src>),
+    Float(Float<'src>),
+    Scientific(Scientific<'src>),
 }
 
+impl<'src> Literal<'src> {
+    /// Source text of the literal
+    pub fn text(&self) -> &'src str {
+        match self {
+            Literal::Whole(whole) => whole.text,
+            Literal::Float(float) => float.text,
+            Literal::Scientific(scientific) => scientific.text,
+        }
+    }
+
+    /// Nearest `f64`, or a diagnostic if the literal is too big for one
+    pub fn to_f64(&self) -> Result<f64, Diagnostic<'src>> {
+        let value = match self {
+            Literal::Whole(whole) => whole.value.to_f64().unwrap_or(f64::INFINITY),
+            _ => digits_text(self.text()).parse().unwrap_or(f64::INFINITY),
+        };
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(Diagnostic::error(
+                self.text(),
+                "number is too large for a 64-bit float",
+            ))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Whole<'src> {
+    pub text: &'src str,
+    pub value: BigUint,
+    pub radix: Radix,
+}
+
+impl<'src> Whole<'src> {
+    /// Digits without the radix prefix, as written
+    pub fn digits(&self) -> &'src str {
+        match self.radix {
+            Radix::Decimal => self.text,
+            _ => &self.text[2..],
+        }
+    }
+
+    pub fn to_u64(&self) -> Result<u64, Diagnostic<'src>> {
+        self.value
+            .to_u64()
+            .ok_or_else(|| Diagnostic::error(self.text, "number does not fit in 64 bits"))
+    }
+}
+
+/// `23.13` or `.5`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Float<'src> {
+    pub text: &'src str,
+    pub value: Decimal,
+}
+
+/// `significand * 10^exponent`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Scientific<'src> {
+    pub text: &'src str,
+    pub significand: Decimal,
+    pub exponent: BigInt,
+}
+
+impl<'src> Scientific<'src> {
+    /// Significand and exponent, as written
+    pub fn parts(&self) -> (&'src str, &'src str) {
+        self.text.split_once(['e', 'E']).unwrap()
+    }
+}
+
+/// Exact decimal number `digits * 10^-scale`
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub struct Whole(u64);
+pub struct Decimal {
+    pub digits: BigUint,
+    pub scale: usize,
+}
+
+impl Decimal {
+    /// Parses `23.13`, `.5` or `1_000`
+    pub fn from_text(text: &str) -> Option<Decimal> {
+        let text = digits_text(text);
+        let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let digits = format!("{whole}{fraction}");
+        Some(Decimal {
+            digits: digits.parse().ok()?,
+            scale: fraction.len(),
+        })
+    }
+}
+
+/// Removes digit seperators
+pub(crate) fn digits_text(fragment: &str) -> String {
+    fragment.chars().filter(|c| *c != '_').collect()
+}
+
+/// Base the whole number was written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Radix {
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+}
+
+impl Radix {
+    pub fn base(&self) -> u32 {
+        match self {
+            Radix::Binary => 2,
+            Radix::Octal => 8,
+            Radix::Decimal => 10,
+            Radix::Hexadecimal => 16,
+        }
+    }
+}
+
+/// `9.81 m/s^2`, a number with the unit it's measured in
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Quantity<'src> {
+    pub value: Box<Expression<'src>>,
+    pub unit: Unit<'src>,
+}
+
+/// `m/s^2` or `kg m`, as the units it multiplies together. See [`crate::units`] for what each
+/// symbol means.
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub struct Float(f64);
+pub struct Unit<'src> {
+    pub factors: Vec<UnitFactor<'src>>,
+}
+
+/// `s^-2`, which is how the `/s^2` of `m/s^2` is kept
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct UnitFactor<'src> {
+    /// `km`, with any prefix
+    pub symbol: &'src str,
+    pub exponent: i32,
+}
 
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub struct Variable<'src> {
-    identifier: Identifier<'src>,
+    pub identifier: Identifier<'src>,
 }
 
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub struct FunctionCall<'src> {
-    identifier: Identifier<'src>,
-    inputs: Vec<Expression<'src>>,
+    pub identifier: Identifier<'src>,
+    pub inputs: Vec<Expression<'src>>,
+}
+
+/// `x_1` or `a_(n+1)`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Subscript<'src> {
+    pub base: Box<Expression<'src>>,
+    pub index: Box<Expression<'src>>,
+}
+
+/// `-x`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Unary<'src> {
+    pub operator: UnaryOperator,
+    pub operand: Box<Expression<'src>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UnaryOperator {
+    Negate,
+    /// `±x`
+    PlusMinus,
+}
+
+/// `x + y`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Binary<'src> {
+    pub operator: BinaryOperator,
+    pub left: Box<Expression<'src>>,
+    pub right: Box<Expression<'src>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    /// `a ± b`
+    PlusMinus,
+    Multiply,
+    /// `2x`, written without an operator
+    ImplicitMultiply,
+    Divide,
+    Power,
+    /// `A union B`
+    Union,
+    /// `A intersect B`
+    Intersection,
+    /// `A \ B`
+    Difference,
+}
+
+impl BinaryOperator {
+    pub fn precedence(&self) -> Precedence {
+        match self {
+            BinaryOperator::Add
+            | BinaryOperator::Subtract
+            | BinaryOperator::PlusMinus
+            | BinaryOperator::Union
+            | BinaryOperator::Difference => Precedence::Sum,
+            BinaryOperator::Multiply
+            | BinaryOperator::ImplicitMultiply
+            | BinaryOperator::Divide
+            | BinaryOperator::Intersection => Precedence::Product,
+            BinaryOperator::Power => Precedence::Power,
+        }
+    }
+}
+
+/// `0 < x <= 1`. Chains compare each neighbouring pair.
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Relation<'src> {
+    pub first: Box<Expression<'src>>,
+    pub rest: Vec<(RelationOperator, Expression<'src>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RelationOperator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Approximately,
+    /// `x in R`
+    In,
+    /// `A subset B`, which may also be equal
+    Subset,
+}
+
+/// `sum(i, 1, n, i^2)` or `prod(i, 1, n, i)`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct BigOperator<'src> {
+    pub kind: BigOperatorKind,
+    pub index: Identifier<'src>,
+    pub from: Box<Expression<'src>>,
+    pub to: Box<Expression<'src>>,
+    pub body: Box<Expression<'src>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BigOperatorKind {
+    Sum,
+    Product,
+}
+
+/// `int(f(x), x, 0, 1)`, or `int(f(x), x)` without bounds
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Integral<'src> {
+    pub integrand: Box<Expression<'src>>,
+    pub variable: Identifier<'src>,
+    /// Lower and upper bound
+    pub bounds: Option<(Box<Expression<'src>>, Box<Expression<'src>>)>,
+}
+
+/// `lim(x, 0, sin(x)/x)`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Limit<'src> {
+    pub variable: Identifier<'src>,
+    pub approaching: Box<Expression<'src>>,
+    pub body: Box<Expression<'src>>,
+}
+
+/// `d/dx(x^2)`, `f''(x)` or `pd(f, x, y)`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub enum Derivative<'src> {
+    /// `d/dx(x^2)`
+    Leibniz {
+        variable: Identifier<'src>,
+        body: Box<Expression<'src>>,
+    },
+    /// `f''(x)`, with `order` primes. Just `f'` has no inputs.
+    Prime {
+        function: Identifier<'src>,
+        order: usize,
+        inputs: Option<Vec<Expression<'src>>>,
+    },
+    /// `pd(f, x, y)`, partially by each variable in turn
+    Partial {
+        body: Box<Expression<'src>>,
+        variables: Vec<Identifier<'src>>,
+    },
+}
+
+impl<'src> Derivative<'src> {
+    /// Primes are atoms. The others take everything to their right, like a product.
+    pub fn precedence(&self) -> Precedence {
+        match self {
+            Derivative::Prime { .. } => Precedence::Atom,
+            _ => Precedence::Product,
+        }
+    }
+
+    /// How many times it differentiates
+    pub fn order(&self) -> usize {
+        match self {
+            Derivative::Leibniz { .. } => 1,
+            Derivative::Prime { order, .. } => *order,
+            Derivative::Partial { variables, .. } => variables.len(),
+        }
+    }
+
+    /// Variables of a partial derivative with neighbouring repeats counted, so `x, x, y`
+    /// gives `(x, 2), (y, 1)`
+    pub fn grouped_variables(&self) -> Vec<(&Identifier<'src>, usize)> {
+        let Derivative::Partial { variables, .. } = self else {
+            return vec![];
+        };
+        let mut grouped: Vec<(&Identifier, usize)> = vec![];
+        for variable in variables {
+            match grouped.last_mut() {
+                Some((last, count)) if *last == variable => *count += 1,
+                _ => grouped.push((variable, 1)),
+            }
+        }
+        grouped
+    }
+}
+
+/// `[1, 2, 3]`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Vector<'src> {
+    pub elements: Vec<Expression<'src>>,
+}
+
+/// `[[1, 2], [3, 4]]`. Every row has the same length.
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Matrix<'src> {
+    pub rows: Vec<Vec<Expression<'src>>>,
+}
+
+/// `piecewise(x^2 if x < 0, x otherwise)`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Piecewise<'src> {
+    /// Tried in order, the first arm whose condition holds gives the value
+    pub arms: Vec<Arm<'src>>,
+    /// Value when no condition holds
+    pub otherwise: Option<Box<Expression<'src>>>,
+}
+
+/// `x^2 if x < 0`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Arm<'src> {
+    pub value: Expression<'src>,
+    pub condition: Expression<'src>,
+}
+
+/// `2, 3`, like the solutions of an equation, or in brackets a point like `(1, 2)`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct List<'src> {
+    pub elements: Vec<Expression<'src>>,
+}
+
+/// `(x + 1)`, brackets the user wrote
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Group<'src> {
+    pub inner: Box<Expression<'src>>,
+}
+
+/// `|x|`, `||v||`, `floor(x)` or `ceil(x)`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Delimited<'src> {
+    pub kind: DelimiterKind,
+    pub inner: Box<Expression<'src>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DelimiterKind {
+    /// `|x|` or `abs(x)`
+    Abs,
+    /// `||v||` or `norm(v)`
+    Norm,
+    Floor,
+    Ceil,
+}
+
+/// `{1, 2, 3}`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Set<'src> {
+    pub elements: Vec<Expression<'src>>,
+}
+
+/// `{x in R | x > 0}`
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct SetBuilder<'src> {
+    pub variable: Identifier<'src>,
+    pub domain: Option<Box<Expression<'src>>>,
+    pub condition: Box<Expression<'src>>,
+}
+
+/// `[0, 1)` or `(a, b]`. A square bracket includes its end.
+#[derive(Debug, PartialEq, PartialOrd, Clone)]
+pub struct Interval<'src> {
+    pub lower: Box<Expression<'src>>,
+    pub upper: Box<Expression<'src>>,
+    pub lower_closed: bool,
+    pub upper_closed: bool,
+}
+
+/// `R` where a set is expected, like after `in`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NumberSet {
+    Natural,
+    Integer,
+    Rational,
+    Real,
+    Complex,
+}
+
+impl NumberSet {
+    /// `R` or `ℝ`
+    pub fn recognize(text: &str) -> Option<NumberSet> {
+        match text {
+            "N" | "ℕ" => Some(NumberSet::Natural),
+            "Z" | "ℤ" => Some(NumberSet::Integer),
+            "Q" | "ℚ" => Some(NumberSet::Rational),
+            "R" | "ℝ" => Some(NumberSet::Real),
+            "C" | "ℂ" => Some(NumberSet::Complex),
+            _ => None,
+        }
+    }
+
+    /// Letter for it, like `R`
+    pub fn letter(&self) -> char {
+        match self {
+            NumberSet::Natural => 'N',
+            NumberSet::Integer => 'Z',
+            NumberSet::Rational => 'Q',
+            NumberSet::Real => 'R',
+            NumberSet::Complex => 'C',
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub struct Identifier<'src> {
-    text: &'src str,
+    pub text: &'src str,
+}
+
+impl Identifier<'_> {
+    /// Named symbol like `alpha` or `θ` this identifier stands for
+    pub fn symbol(&self) -> Option<Symbol> {
+        Symbol::recognize(self.text)
+    }
 }
@@ -1,6 +1,6 @@
 use super::{Literal, *};
 use crate::lexer;
-use anyhow::{anyhow, bail, Context};
+use anyhow::{anyhow, Context};
 use nom_reinvented::*;
 
 type TS<'a, 'src> = &'a [lexer::Token<'src>];
@@ -10,12 +10,215 @@ mod nom_reinvented;
 mod test;
 
 pub fn ast<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Ast<'src>> {
-    todo!()
+    let mut statements = vec![];
+    let mut sources = vec![];
+    let (mut input, _) = statement_seperators(input)?;
+    // Once `d` is defined, `d/dt(x)` is `d` divided by `dt(x)` rather than a derivative
+    let mut defines_d = false;
+    while !input.is_empty() {
+        let (rest, mut statement) = statement(input)?;
+        let statement_tokens = &input[..input.len() - rest.len()];
+        if defines_d {
+            statement = reread_statement(statement, statement_tokens);
+        }
+        defines_d |= statement.defined_name() == Some("d");
+        statements.push(statement);
+        sources.push(text(input, rest));
+        let (rest, seperators) = statement_seperators(rest)?;
+        if seperators.is_empty() && !rest.is_empty() {
+            return Err(anyhow!(Error::new(rest)))
+                .with_context(|| "statement expecting new line or `;` after it");
+        }
+        input = rest;
+    }
+    Ok((
+        input,
+        Ast {
+            statements,
+            sources,
+        },
+    ))
+}
+
+/// New lines and `;`
+fn statement_seperators<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>> {
+    let count = input
+        .iter()
+        .take_while(|token| {
+            matches!(
+                token.kind,
+                lexer::TokenKind::WhiteSpace(lexer::WhiteSpace::NewLine)
+                    | lexer::TokenKind::Operator(lexer::Operator::Semicolon)
+            )
+        })
+        .count();
+    take(count)(input)
+}
+
+fn statement<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Statement<'src>> {
+    if let Ok(o) = let_statement(input) {
+        return Ok(o);
+    }
+    let (input, mut derivation) = derivation(input)?;
+    if derivation.steps.len() > 1 || derivation.steps[0].justification.is_some() {
+        return Ok((input, Statement::Derivation(derivation)));
+    }
+    let statement = match derivation.steps.remove(0).expression {
+        Expression::Relation(relation) => match function_definition(relation) {
+            Ok(function_definition) => Statement::FunctionDefinition(function_definition),
+            Err(relation) => Statement::Equation(relation),
+        },
+        expression => Statement::Expression(expression),
+    };
+    Ok((input, statement))
+}
+
+/// First step, then any steps starting with `=>` or a relation, which may be on the following lines
+fn derivation<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Derivation<'src>> {
+    let start = input;
+    let (input, expression) = expression(input)?;
+    let (mut input, justification) = justification(input);
+    let mut steps = vec![Step {
+        kind: StepKind::Start,
+        expression,
+        justification,
+        text: Some(text(start, input)),
+    }];
+    while let Ok((rest, step)) = next_step(input) {
+        steps.push(step);
+        input = rest;
+    }
+    Ok((input, Derivation { steps }))
+}
+
+fn next_step<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Step<'src>> {
+    let new_line = lexer::TokenKind::WhiteSpace(lexer::WhiteSpace::NewLine);
+    let implies = lexer::TokenKind::Operator(lexer::Operator::RightThickArrow);
+    let input = match tag_tokens_kind(&[new_line])(input) {
+        Ok((input, _)) => input,
+        Err(_) => input,
+    };
+    let start = input;
+    let (input, kind) = match tag_tokens_kind(&[implies])(input) {
+        Ok((input, _)) => (input, StepKind::Implies),
+        Err(_) => {
+            let (input, operator) = relation_operator(input)?;
+            (input, StepKind::Continues(operator))
+        }
+    };
+    let (input, expression) = expression(input)?;
+    let (input, justification) = justification(input);
+    Ok((
+        input,
+        Step {
+            kind,
+            expression,
+            justification,
+            text: Some(text(start, input)),
+        },
+    ))
 }
 
-fn tag_tokens_kind(
+/// Source of the tokens parsed from `start` to get to `rest`
+fn text<'src>(start: TS<'_, 'src>, rest: TS<'_, 'src>) -> &'src str {
+    let parsed = &start[..start.len() - rest.len()];
+    match (parsed.first(), parsed.last()) {
+        (Some(first), Some(last)) => first.text_to(last),
+        _ => "",
+    }
+}
+
+/// Optional `"reason"` after a step
+fn justification<'a, 'src>(input: TS<'a, 'src>) -> (TS<'a, 'src>, Option<&'src str>) {
+    let string = lexer::TokenKind::Literal(lexer::Literal::String);
+    match tag_tokens_kind(&[string])(input) {
+        Ok((input, token)) => {
+            let text = token[0].fragment;
+            (input, Some(&text[1..text.len() - 1]))
+        }
+        Err(_) => (input, None),
+    }
+}
+
+/// `let g = 9.81` or `let f(x) = x^2`
+fn let_statement<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Statement<'src>> {
+    let equal = lexer::TokenKind::Operator(lexer::Operator::Equal);
+    let (input, _) = try_map(identifier, |identifier| {
+        (identifier.text == "let").then_some(())
+    })(input)?;
+    let underscore = lexer::TokenKind::Operator(lexer::Operator::Underscore);
+    if let Ok((input, identifier)) = identifier(input) {
+        let (input, index) = match tag_tokens_kind(&[underscore])(input) {
+            Ok((input, _)) => map(subscript_index, Some)(input)?,
+            Err(_) => (input, None),
+        };
+        if let Ok((input, _)) = tag_tokens_kind(&[equal])(input) {
+            let (input, value) = expression(input)?;
+            return Ok((
+                input,
+                Statement::Definition(Definition {
+                    identifier,
+                    index,
+                    value,
+                }),
+            ));
+        }
+    }
+    let (input, expression) = expression(input)?;
+    match expression {
+        Expression::Relation(relation) => match function_definition(relation) {
+            Ok(function_definition) => {
+                Ok((input, Statement::FunctionDefinition(function_definition)))
+            }
+            Err(_) => Err(anyhow!(Error::new(input)))
+                .with_context(|| "let expecting `name = value` or `name(parameters) = body`"),
+        },
+        _ => Err(anyhow!(Error::new(input)))
+            .with_context(|| "let expecting `name = value` or `name(parameters) = body`"),
+    }
+}
+
+/// `f(x, y) = body`. Gives back the relation when it isn't shaped like one.
+fn function_definition(relation: Relation<'_>) -> Result<FunctionDefinition<'_>, Relation<'_>> {
+    let is_function_definition = match (relation.first.as_ref(), relation.rest.as_slice()) {
+        (Expression::FunctionCall(call), [(RelationOperator::Equal, _)]) => {
+            call.inputs.iter().enumerate().all(|(i, input)| {
+                match input {
+                Expression::Variable(variable) => !call.inputs[..i].iter().any(|earlier| {
+                    matches!(earlier, Expression::Variable(earlier) if earlier == variable)
+                }),
+                _ => false,
+            }
+            })
+        }
+        _ => false,
+    };
+    if !is_function_definition {
+        return Err(relation);
+    }
+    let Relation { first, mut rest } = relation;
+    let (_, body) = rest.remove(0);
+    let Expression::FunctionCall(call) = *first else {
+        unreachable!()
+    };
+    let parameters = call
+        .inputs
+        .into_iter()
+        .map(|input| match input {
+            Expression::Variable(variable) => variable.identifier,
+            _ => unreachable!(),
+        })
+        .collect();
+    Ok(FunctionDefinition {
+        identifier: call.identifier,
+        parameters,
+        body,
+    })
+}
+
+fn tag_tokens_kind<'a, 'src: 'a>(
     kind: &[lexer::TokenKind],
-) -> impl for<'a, 'src> Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>> {
+) -> impl Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>> {
     let kind = kind.to_vec();
     move |input| {
         let (input, tokens) = take(kind.len())(input)?;
@@ -29,42 +232,421 @@ fn tag_tokens_kind(
 }
 
 fn expression<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    relation(input)
+}
+
+/// `a < b <= c`, or just `a`
+fn relation<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let (mut input, first) = sum(input)?;
+    let mut rest = vec![];
+    while let Ok((after_operator, operator)) = relation_operator(input) {
+        let Some((after_right, right)) = right_operand(input, sum(after_operator))? else {
+            break;
+        };
+        let right = match operator {
+            RelationOperator::In | RelationOperator::Subset => as_set(right),
+            _ => right,
+        };
+        rest.push((operator, right));
+        input = after_right;
+    }
+    if rest.is_empty() {
+        return Ok((input, first));
+    }
+    // Both sides of `subset` are sets, later ones are already handled as right hand sides
+    let first = match rest[0].0 {
+        RelationOperator::Subset => as_set(first),
+        _ => first,
+    };
+    Ok((
+        input,
+        Expression::Relation(Relation {
+            first: Box::new(first),
+            rest,
+        }),
+    ))
+}
+
+fn relation_operator<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, RelationOperator> {
+    use lexer::{Operator, TokenKind};
+    let called = is_called(input);
+    try_map(take(1), move |token| match token[0].kind {
+        TokenKind::Operator(Operator::Equal) => Some(RelationOperator::Equal),
+        TokenKind::Operator(Operator::BangEqual) => Some(RelationOperator::NotEqual),
+        TokenKind::Operator(Operator::LeftAngleBracket) => Some(RelationOperator::Less),
+        TokenKind::Operator(Operator::LeftAngleBracketEqual) => Some(RelationOperator::LessEqual),
+        TokenKind::Operator(Operator::RightAngleBracket) => Some(RelationOperator::Greater),
+        TokenKind::Operator(Operator::RightAngleBracketEqual) => {
+            Some(RelationOperator::GreaterEqual)
+        }
+        TokenKind::Operator(Operator::TildeEqual) => Some(RelationOperator::Approximately),
+        TokenKind::Identifier if !called => match token[0].fragment {
+            "in" => Some(RelationOperator::In),
+            "subset" => Some(RelationOperator::Subset),
+            _ => None,
+        },
+        _ => None,
+    })(input)
+}
+
+/// `in`, `subset`, `union` and `intersect` are only operators between two operands. Called like
+/// `subset(x)`, or with nothing after them, they're names like any other.
+fn is_called(input: TS<'_, '_>) -> bool {
+    let left_bracket = lexer::TokenKind::Operator(lexer::Operator::LeftRoundBracket);
+    match input {
+        [name, next, ..] => {
+            name.kind == lexer::TokenKind::Identifier
+                && next.kind == left_bracket
+                && touching(name, next)
+        }
+        _ => false,
+    }
+}
+
+/// The operand after the operator at the start of `input`, or [`None`] when the operator is a
+/// word with no operand after it, so it's read as a name instead
+fn right_operand<'a, 'src>(
+    input: TS<'a, 'src>,
+    operand: IResult<TS<'a, 'src>, Expression<'src>>,
+) -> anyhow::Result<Option<(TS<'a, 'src>, Expression<'src>)>> {
+    match operand {
+        Ok(o) => Ok(Some(o)),
+        Err(_) if input[0].kind == lexer::TokenKind::Identifier => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn binary<'src>(
+    operator: BinaryOperator,
+    left: Expression<'src>,
+    right: Expression<'src>,
+) -> Expression<'src> {
+    let (left, right) = match operator {
+        BinaryOperator::Union | BinaryOperator::Intersection | BinaryOperator::Difference => {
+            (as_set(left), as_set(right))
+        }
+        _ => (left, right),
+    };
+    Expression::Binary(Binary {
+        operator,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+/// `a + b - c`
+fn sum<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let (mut input, mut left) = product(input)?;
+    while let Ok((after_operator, operator)) = sum_operator(input) {
+        let Some((after_right, right)) = right_operand(input, product(after_operator))? else {
+            break;
+        };
+        left = binary(operator, left, right);
+        input = after_right;
+    }
+    Ok((input, left))
+}
+
+fn sum_operator<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, BinaryOperator> {
+    use lexer::{Operator, TokenKind};
+    let called = is_called(input);
+    try_map(take(1), move |token| match token[0].kind {
+        TokenKind::Operator(Operator::Plus) => Some(BinaryOperator::Add),
+        TokenKind::Operator(Operator::Minus) => Some(BinaryOperator::Subtract),
+        TokenKind::Operator(Operator::PlusMinus | Operator::PlusSlashMinus) => {
+            Some(BinaryOperator::PlusMinus)
+        }
+        TokenKind::Operator(Operator::BackSlash) => Some(BinaryOperator::Difference),
+        TokenKind::Identifier if token[0].fragment == "union" && !called => {
+            Some(BinaryOperator::Union)
+        }
+        _ => None,
+    })(input)
+}
+
+/// `a * b / c`, or `2x` and `2 x` with nothing between the factors
+fn product<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let (mut rest, mut left) = prefix(input)?;
+    loop {
+        if let Ok((after_operator, operator)) = product_operator(rest) {
+            if let Some((after_right, right)) = right_operand(rest, prefix(after_operator))? {
+                left = binary(operator, left, right);
+                rest = after_right;
+                continue;
+            }
+        }
+        let previous = &input[input.len() - rest.len() - 1];
+        if let Some(next) = rest.first() {
+            // `x in A` is a relation, not `x` times `in`
+            let spaced_word = !touching(previous, next) && is_operator_word(next);
+            if starts_implicit_factor(next) && !spaced_word {
+                let (after_right, right) = power(rest)?;
+                left = binary(BinaryOperator::ImplicitMultiply, left, right);
+                rest = after_right;
+                continue;
+            }
+        }
+        break;
+    }
+    Ok((rest, left))
+}
+
+/// No white space between the two tokens
+fn touching(left: &lexer::Token, right: &lexer::Token) -> bool {
+    left.fragment.as_ptr().wrapping_add(left.fragment.len()) == right.fragment.as_ptr()
+}
+
+/// Words that are operators or part of other syntax, like `in` and the `if` of `piecewise`
+fn is_operator_word(token: &lexer::Token) -> bool {
+    matches!(
+        token.fragment,
+        "in" | "subset" | "union" | "intersect" | "if" | "otherwise"
+    )
+}
+
+fn starts_implicit_factor(token: &lexer::Token) -> bool {
+    matches!(
+        token.kind,
+        lexer::TokenKind::Identifier
+            | lexer::TokenKind::Operator(lexer::Operator::LeftRoundBracket)
+    )
+}
+
+fn product_operator<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, BinaryOperator> {
+    use lexer::{Operator, TokenKind};
+    let called = is_called(input);
+    try_map(take(1), move |token| match token[0].kind {
+        TokenKind::Operator(Operator::Asterisk) => Some(BinaryOperator::Multiply),
+        TokenKind::Operator(Operator::ForwardSlash) => Some(BinaryOperator::Divide),
+        TokenKind::Identifier if token[0].fragment == "intersect" && !called => {
+            Some(BinaryOperator::Intersection)
+        }
+        _ => None,
+    })(input)
+}
+
+/// `-x` or `±x`
+fn prefix<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    use lexer::{Operator, TokenKind};
+    let prefix_operator = try_map(take(1), |token| match token[0].kind {
+        TokenKind::Operator(Operator::Minus) => Some(UnaryOperator::Negate),
+        TokenKind::Operator(Operator::PlusMinus | Operator::PlusSlashMinus) => {
+            Some(UnaryOperator::PlusMinus)
+        }
+        _ => None,
+    });
+    if let Ok((input, operator)) = prefix_operator(input) {
+        let (input, operand) = prefix(input)?;
+        return Ok((
+            input,
+            Expression::Unary(Unary {
+                operator,
+                operand: Box::new(operand),
+            }),
+        ));
+    }
+    power(input)
+}
+
+/// `x^2` or `x**2`, right associative
+fn power<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let caret = lexer::TokenKind::Operator(lexer::Operator::Caret);
+    let double_asterisk = lexer::TokenKind::Operator(lexer::Operator::DoubleAsterisk);
+    let (input, base) = atom(input)?;
+    let power_operator = |input| {
+        tag_tokens_kind(&[caret])(input).or_else(|_| tag_tokens_kind(&[double_asterisk])(input))
+    };
+    if let Ok((input, _)) = power_operator(input) {
+        let (input, exponent) = prefix(input)?;
+        return Ok((input, binary(BinaryOperator::Power, base, exponent)));
+    }
+    Ok((input, base))
+}
+
+fn atom<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
     let literal = map(literal, Expression::Literal);
-    let function_call = map(function_call, Expression::FunctionCall);
-    if let Ok(o) = literal(input) {
+    let function_call = map(function_call, special_form);
+    let subscript = map(subscript, Expression::Subscript);
+    let variable = map(variable, Expression::Variable);
+    let derivative = map(derivative, Expression::Derivative);
+    let left_square_bracket = lexer::TokenKind::Operator(lexer::Operator::LeftSquareBracket);
+    let left_curly_bracket = lexer::TokenKind::Operator(lexer::Operator::LeftCurlyBracket);
+    // Nothing else starts with `[` or `{`, so their errors are the ones worth showing
+    if tag_tokens_kind(&[left_square_bracket])(input).is_ok() {
+        return list_or_interval(input);
+    }
+    if tag_tokens_kind(&[left_curly_bracket])(input).is_ok() {
+        return set(input);
+    }
+    if let Ok(o) = norm(input) {
+        return Ok(o);
+    }
+    if let Ok(o) = abs(input) {
+        return Ok(o);
+    }
+    if let Ok((rest, value)) = literal(input) {
+        // `9.81 m/s^2`, with a space so that `2m` is still `2` times `m`
+        let spaced = rest.first().is_some_and(|next| !touching(&input[0], next));
+        if let (true, Ok((rest, unit))) = (spaced, unit(rest)) {
+            let value = Box::new(value);
+            return Ok((rest, Expression::Quantity(Quantity { value, unit })));
+        }
+        return Ok((rest, value));
+    }
+    if let Ok(o) = derivative(input) {
         return Ok(o);
     }
+    if input
+        .first()
+        .is_some_and(|token| token.fragment == "piecewise")
+    {
+        // `piecewise` is a keyword, not a function to fall back to
+        return piecewise(input);
+    }
     if let Ok(o) = function_call(input) {
         return Ok(o);
     }
-    Err(anyhow!(Error::new(input)))
-        .with_context(|| "expression expecting literal, or functiona call")
+    if let Ok(o) = subscript(input) {
+        return Ok(o);
+    }
+    if let Ok(o) = variable(input) {
+        return Ok(o);
+    }
+    if let Ok(o) = group(input) {
+        return Ok(o);
+    }
+    if let Ok(o) = list_or_interval(input) {
+        return Ok(o);
+    }
+    Err(anyhow!(Error::new(input))).with_context(|| {
+        "expression expecting literal, derivative, function call, subscript, variable, `|`, or bracketed expression"
+    })
 }
 
-fn literal<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Literal> {
+fn literal<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Literal<'src>> {
     let whole = map(whole, Literal::Whole);
     let float = map(float, Literal::Float);
+    let scientific = map(scientific, Literal::Scientific);
     if let Ok(o) = whole(input) {
         return Ok(o);
     }
     if let Ok(o) = float(input) {
         return Ok(o);
     }
+    if let Ok(o) = scientific(input) {
+        return Ok(o);
+    }
 
-    Err(anyhow!(Error::new(input)))
-        .with_context(|| "literal expecting whole number, or floating-point number")
+    Err(anyhow!(Error::new(input))).with_context(|| {
+        "literal expecting whole number, floating-point number, or scientific notation"
+    })
 }
 
-fn whole<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Whole> {
-    let digits_kind = lexer::TokenKind::Literal(lexer::Literal::Digits);
-    let (input, digits) = tag_tokens_kind(&[digits_kind])(input)?;
-    Ok((input, Whole(digits[0].fragment.parse().unwrap())))
+fn whole<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Whole<'src>> {
+    let (rest, token) = take(1)(input)?;
+    let token = token[0];
+    let radix = match token.kind {
+        lexer::TokenKind::Literal(lexer::Literal::Digits) => Radix::Decimal,
+        lexer::TokenKind::Literal(lexer::Literal::Binary) => Radix::Binary,
+        lexer::TokenKind::Literal(lexer::Literal::Octal) => Radix::Octal,
+        lexer::TokenKind::Literal(lexer::Literal::Hexadecimal) => Radix::Hexadecimal,
+        _ => return Err(anyhow!(Error::new(input))).with_context(|| "whole"),
+    };
+    let mut whole = Whole {
+        text: token.fragment,
+        value: BigUint::default(),
+        radix,
+    };
+    let digits = digits_text(whole.digits());
+    match BigUint::parse_bytes(digits.as_bytes(), radix.base()) {
+        Some(value) => whole.value = value,
+        None => return Err(anyhow!(Error::new(input))).with_context(|| "whole"),
+    }
+    Ok((rest, whole))
 }
 
-fn float<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Float> {
+fn float<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Float<'src>> {
     let float_kind = lexer::TokenKind::Literal(lexer::Literal::Float);
-    let (input, float) = tag_tokens_kind(&[float_kind])(input)?;
-    Ok((input, Float(float[0].fragment.parse().unwrap())))
+    try_map(tag_tokens_kind(&[float_kind]), |float| {
+        let text = float[0].fragment;
+        Some(Float {
+            text,
+            value: Decimal::from_text(text)?,
+        })
+    })(input)
+}
+
+fn scientific<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Scientific<'src>> {
+    let scientific_kind = lexer::TokenKind::Literal(lexer::Literal::Scientific);
+    try_map(tag_tokens_kind(&[scientific_kind]), |scientific| {
+        let text = scientific[0].fragment;
+        let (significand, exponent) = text.split_once(['e', 'E'])?;
+        Some(Scientific {
+            text,
+            significand: Decimal::from_text(significand)?,
+            exponent: digits_text(exponent.trim_start_matches('+')).parse().ok()?,
+        })
+    })(input)
+}
+
+/// `m/s^2`, `kg*m` or `kg m`. A `*` or `/` that isn't followed by a unit is left for
+/// [`product`], so `3 kg * 2 m` is two quantities.
+fn unit<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Unit<'src>> {
+    let (mut input, first) = unit_factor(input)?;
+    let mut factors = vec![first];
+    loop {
+        if let Ok((after_operator, operator)) = product_operator(input) {
+            let Ok((after_factor, mut factor)) = unit_factor(after_operator) else {
+                break;
+            };
+            match operator {
+                BinaryOperator::Multiply => {}
+                BinaryOperator::Divide => factor.exponent = -factor.exponent,
+                _ => break,
+            }
+            factors.push(factor);
+            input = after_factor;
+        } else if let Ok((after_factor, factor)) = unit_factor(input) {
+            factors.push(factor);
+            input = after_factor;
+        } else {
+            break;
+        }
+    }
+    Ok((input, Unit { factors }))
+}
+
+/// `s` or `s^-2`, if `s` is a unit
+fn unit_factor<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, UnitFactor<'src>> {
+    use lexer::{Operator, TokenKind};
+    let (input, symbol) = try_map(identifier, |identifier| {
+        crate::units::lookup(identifier.text).map(|_| identifier.text)
+    })(input)?;
+    let exponent = |input| {
+        let (input, _) =
+            tag_tokens_kind(&[TokenKind::Operator(Operator::Caret)])(input).or_else(|_| {
+                tag_tokens_kind(&[TokenKind::Operator(Operator::DoubleAsterisk)])(input)
+            })?;
+        let minus = tag_tokens_kind(&[TokenKind::Operator(Operator::Minus)])(input);
+        let (input, sign) = match minus {
+            Ok((input, _)) => (input, -1),
+            Err(_) => (input, 1),
+        };
+        try_map(whole, move |whole| {
+            let exponent: i32 = whole.value.to_i32()?;
+            Some(sign * exponent)
+        })(input)
+    };
+    match exponent(input) {
+        Ok((input, exponent)) => Ok((input, UnitFactor { symbol, exponent })),
+        Err(_) => Ok((
+            input,
+            UnitFactor {
+                symbol,
+                exponent: 1,
+            },
+        )),
+    }
 }
 
 fn identifier<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Identifier<'src>> {
@@ -78,6 +660,596 @@ fn identifier<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Identifier
     ))
 }
 
+fn variable<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Variable<'src>> {
+    map(identifier, |identifier| Variable { identifier })(input)
+}
+
+fn subscript<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Subscript<'src>> {
+    let underscore = lexer::TokenKind::Operator(lexer::Operator::Underscore);
+    let (input, base) = map(variable, Expression::Variable)(input)?;
+    let (input, _) = tag_tokens_kind(&[underscore])(input)?;
+    let (input, index) = subscript_index(input)?;
+    Ok((
+        input,
+        Subscript {
+            base: Box::new(base),
+            index: Box::new(index),
+        },
+    ))
+}
+
+/// `1`, `n` or `(n+1)`
+fn subscript_index<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let literal = map(literal, Expression::Literal);
+    let variable = map(variable, Expression::Variable);
+    if let Ok(o) = literal(input) {
+        return Ok(o);
+    }
+    if let Ok(o) = variable(input) {
+        return Ok(o);
+    }
+    if let Ok(o) = expression_in_round_bracket(input) {
+        return Ok(o);
+    }
+    Err(anyhow!(Error::new(input)))
+        .with_context(|| "subscript expecting literal, variable, or bracketed expression")
+}
+
+/// `(x + 1)`
+fn group<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    map(expression_in_round_bracket, |inner| {
+        Expression::Group(Group {
+            inner: Box::new(inner),
+        })
+    })(input)
+}
+
+fn expression_in_round_bracket<'a, 'src>(
+    input: TS<'a, 'src>,
+) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let left_bracket = lexer::TokenKind::Operator(lexer::Operator::LeftRoundBracket);
+    let right_bracket = lexer::TokenKind::Operator(lexer::Operator::RightRoundBracket);
+    let (input, _) = tag_tokens_kind(&[left_bracket])(input)?;
+    let (input, expression) = expression(input)?;
+    let (input, _) = tag_tokens_kind(&[right_bracket])(input)?;
+    Ok((input, expression))
+}
+
+/// `[1, 2, 3]`, `[[1, 2], [3, 4]]`, a point like `(a, b)`, or an interval like `[0, 1)`. Where a
+/// set is expected `(a, b)` is an open interval, see [`as_set`].
+fn list_or_interval<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    use lexer::{Operator, TokenKind};
+    let comma = TokenKind::Operator(Operator::Comma);
+    let either = |round, square| {
+        move |input| {
+            tag_tokens_kind(&[TokenKind::Operator(round)])(input)
+                .or_else(|_| tag_tokens_kind(&[TokenKind::Operator(square)])(input))
+        }
+    };
+    let (rest, elements) = surround_seperated_items_allowed_trailing(
+        expression,
+        tag_tokens_kind(&[comma]),
+        either(Operator::LeftRoundBracket, Operator::LeftSquareBracket),
+        either(Operator::RightRoundBracket, Operator::RightSquareBracket),
+    )(input)?;
+    let is_square = |token: &lexer::Token| {
+        matches!(
+            token.kind,
+            TokenKind::Operator(Operator::LeftSquareBracket | Operator::RightSquareBracket)
+        )
+    };
+    let lower_closed = is_square(&input[0]);
+    let upper_closed = is_square(&input[input.len() - rest.len() - 1]);
+    if lower_closed && upper_closed {
+        return vector_or_matrix(input, elements).map(|matrix| (rest, matrix));
+    }
+    if !lower_closed && !upper_closed && elements.len() > 1 {
+        let point = Expression::List(List { elements });
+        let inner = Box::new(point);
+        return Ok((rest, Expression::Group(Group { inner })));
+    }
+    let Ok([lower, upper]) = <[Expression; 2]>::try_from(elements) else {
+        return Err(anyhow!(Error::new(input)))
+            .with_context(|| "interval expecting a lower and an upper end");
+    };
+    Ok((
+        rest,
+        Expression::Interval(Interval {
+            lower: Box::new(lower),
+            upper: Box::new(upper),
+            lower_closed,
+            upper_closed,
+        }),
+    ))
+}
+
+/// Matrix when every element is a list, otherwise a vector
+fn vector_or_matrix<'a, 'src>(
+    input: TS<'a, 'src>,
+    elements: Vec<Expression<'src>>,
+) -> anyhow::Result<Expression<'src>> {
+    let rows = elements
+        .iter()
+        .filter(|element| matches!(element, Expression::Vector(_)));
+    match rows.count() {
+        0 => return Ok(Expression::Vector(Vector { elements })),
+        count if count < elements.len() => {
+            return Err(anyhow!(Error::new(input)))
+                .with_context(|| "matrix expecting every row to be a list");
+        }
+        _ => {}
+    }
+    let rows: Vec<Vec<Expression>> = elements
+        .into_iter()
+        .map(|element| match element {
+            Expression::Vector(vector) => vector.elements,
+            _ => unreachable!(),
+        })
+        .collect();
+    if rows.iter().any(|row| row.len() != rows[0].len()) {
+        return Err(anyhow!(Error::new(input)))
+            .with_context(|| "matrix expecting every row to have the same length");
+    }
+    Ok(Expression::Matrix(Matrix { rows }))
+}
+
+/// `{1, 2, 3}`, `{}` or `{x in R | x > 0}`
+fn set<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let left_bracket = lexer::TokenKind::Operator(lexer::Operator::LeftCurlyBracket);
+    let right_bracket = lexer::TokenKind::Operator(lexer::Operator::RightCurlyBracket);
+    let comma = lexer::TokenKind::Operator(lexer::Operator::Comma);
+    if let Ok((rest, _)) = tag_tokens_kind(&[left_bracket, right_bracket])(input) {
+        return Ok((rest, Expression::Set(Set { elements: vec![] })));
+    }
+    if let Ok(o) = set_builder(input) {
+        return Ok(o);
+    }
+    let (rest, elements) = surround_seperated_items_allowed_trailing(
+        expression,
+        tag_tokens_kind(&[comma]),
+        tag_tokens_kind(&[left_bracket]),
+        tag_tokens_kind(&[right_bracket]),
+    )(input)?;
+    Ok((rest, Expression::Set(Set { elements })))
+}
+
+/// `{x in R | x > 0}` or `{x | x > 0}`
+fn set_builder<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let left_bracket = lexer::TokenKind::Operator(lexer::Operator::LeftCurlyBracket);
+    let right_bracket = lexer::TokenKind::Operator(lexer::Operator::RightCurlyBracket);
+    let pipe = lexer::TokenKind::Operator(lexer::Operator::Pipe);
+    let (input, _) = tag_tokens_kind(&[left_bracket])(input)?;
+    let (input, variable) = identifier(input)?;
+    let (input, domain) =
+        match try_map(identifier, |keyword| (keyword.text == "in").then_some(()))(input) {
+            Ok((input, _)) => {
+                let (input, domain) = sum(input)?;
+                (input, Some(Box::new(as_set(domain))))
+            }
+            Err(_) => (input, None),
+        };
+    let (input, _) = tag_tokens_kind(&[pipe])(input)?;
+    let (input, condition) = expression(input)?;
+    let (input, _) = tag_tokens_kind(&[right_bracket])(input)?;
+    Ok((
+        input,
+        Expression::SetBuilder(SetBuilder {
+            variable,
+            domain,
+            condition: Box::new(condition),
+        }),
+    ))
+}
+
+/// `|x|`. The closing `|` ends the expression inside, as nothing continues an expression with `|`.
+fn abs<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let pipe = lexer::TokenKind::Operator(lexer::Operator::Pipe);
+    let (input, _) = tag_tokens_kind(&[pipe])(input)?;
+    let (input, inner) = expression(input)?;
+    let (input, _) = tag_tokens_kind(&[pipe])(input)?;
+    Ok((input, delimited(DelimiterKind::Abs, inner)))
+}
+
+/// `||v||`, with each pair of `|` touching. Anything else starting with `||` is an `abs` in an `abs`.
+fn norm<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let pipe = lexer::TokenKind::Operator(lexer::Operator::Pipe);
+    let double_pipe = |input: TS<'a, 'src>| {
+        let (rest, pipes) = tag_tokens_kind(&[pipe, pipe])(input)?;
+        if !touching(&pipes[0], &pipes[1]) {
+            return Err(anyhow!(Error::new(input))).with_context(|| "norm expecting `||`");
+        }
+        Ok((rest, pipes))
+    };
+    let (input, _) = double_pipe(input)?;
+    let (input, inner) = expression(input)?;
+    let (input, _) = double_pipe(input)?;
+    Ok((input, delimited(DelimiterKind::Norm, inner)))
+}
+
+fn delimited(kind: DelimiterKind, inner: Expression<'_>) -> Expression<'_> {
+    Expression::Delimited(Delimited {
+        kind,
+        inner: Box::new(inner),
+    })
+}
+
+/// Reads `R` as the real numbers, `[a, b]` as a closed interval and `(a, b)` as an open one, where
+/// a set is expected
+fn as_set(expression: Expression<'_>) -> Expression<'_> {
+    match expression {
+        Expression::Variable(variable) => match NumberSet::recognize(variable.identifier.text) {
+            Some(number_set) => Expression::NumberSet(number_set),
+            None => Expression::Variable(variable),
+        },
+        Expression::Vector(vector) if vector.elements.len() == 2 => {
+            let mut ends = vector.elements.into_iter().map(Box::new);
+            Expression::Interval(Interval {
+                lower: ends.next().unwrap(),
+                upper: ends.next().unwrap(),
+                lower_closed: true,
+                upper_closed: true,
+            })
+        }
+        Expression::Group(Group { inner }) => match *inner {
+            Expression::List(list) if list.elements.len() == 2 => {
+                let mut ends = list.elements.into_iter().map(Box::new);
+                Expression::Interval(Interval {
+                    lower: ends.next().unwrap(),
+                    upper: ends.next().unwrap(),
+                    lower_closed: false,
+                    upper_closed: false,
+                })
+            }
+            inner => Expression::Group(Group {
+                inner: Box::new(inner),
+            }),
+        },
+        expression => expression,
+    }
+}
+
+/// `piecewise(x^2 if x < 0, x otherwise)`
+fn piecewise<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Expression<'src>> {
+    let left_bracket = lexer::TokenKind::Operator(lexer::Operator::LeftRoundBracket);
+    let right_bracket = lexer::TokenKind::Operator(lexer::Operator::RightRoundBracket);
+    let comma = lexer::TokenKind::Operator(lexer::Operator::Comma);
+    let (input, _) = try_map(identifier, |identifier| {
+        (identifier.text == "piecewise").then_some(())
+    })(input)?;
+    let (rest, mut arms) = surround_seperated_items_allowed_trailing(
+        piecewise_arm,
+        tag_tokens_kind(&[comma]),
+        tag_tokens_kind(&[left_bracket]),
+        tag_tokens_kind(&[right_bracket]),
+    )(input)?;
+    let otherwise = match arms.last() {
+        Some((_, None)) => arms.pop().map(|(value, _)| Box::new(value)),
+        _ => None,
+    };
+    let arms = arms
+        .into_iter()
+        .map(|(value, condition)| {
+            Some(Arm {
+                value,
+                condition: condition?,
+            })
+        })
+        .collect::<Option<Vec<_>>>();
+    let Some(arms) = arms else {
+        return Err(anyhow!(Error::new(input)))
+            .with_context(|| "piecewise expecting `otherwise` only on the last arm");
+    };
+    Ok((rest, Expression::Piecewise(Piecewise { arms, otherwise })))
+}
+
+/// `value if condition`, or `value otherwise` without a condition
+fn piecewise_arm<'a, 'src>(
+    input: TS<'a, 'src>,
+) -> IResult<TS<'a, 'src>, (Expression<'src>, Option<Expression<'src>>)> {
+    let (input, value) = expression(input)?;
+    let (input, keyword) = try_map(identifier, |identifier| {
+        matches!(identifier.text, "if" | "otherwise").then_some(identifier.text)
+    })(input)
+    .with_context(|| "piecewise expecting `if` or `otherwise` after the value")?;
+    if keyword == "otherwise" {
+        return Ok((input, (value, None)));
+    }
+    let (input, condition) = expression(input)?;
+    Ok((input, (value, Some(condition))))
+}
+
+/// `d/dx(x^2)` or `f''(x)`
+fn derivative<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Derivative<'src>> {
+    if let Ok(o) = leibniz_derivative(input) {
+        return Ok(o);
+    }
+    prime_derivative(input)
+}
+
+/// Read again as a division by [`ast`] once `d` is defined
+fn leibniz_derivative<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Derivative<'src>> {
+    let forward_slash = lexer::TokenKind::Operator(lexer::Operator::ForwardSlash);
+    let (input, _) = try_map(identifier, |d| (d.text == "d").then_some(()))(input)?;
+    let (input, _) = tag_tokens_kind(&[forward_slash])(input)?;
+    let (input, variable) = try_map(identifier, |dx| {
+        let text = dx.text.strip_prefix('d').filter(|text| !text.is_empty())?;
+        Some(Identifier { text })
+    })(input)?;
+    let (input, body) = expression_in_round_bracket(input)?;
+    Ok((
+        input,
+        Derivative::Leibniz {
+            variable,
+            body: Box::new(body),
+        },
+    ))
+}
+
+/// `statement` as it's read once `d` is defined, see [`reread`]
+fn reread_statement<'src>(statement: Statement<'src>, tokens: TS<'_, 'src>) -> Statement<'src> {
+    let reread = |expression: &Expression<'src>| reread(expression, tokens).0;
+    match statement {
+        Statement::Definition(definition) => Statement::Definition(Definition {
+            index: definition.index.as_ref().map(reread),
+            value: reread(&definition.value),
+            ..definition
+        }),
+        Statement::FunctionDefinition(function_definition) => {
+            Statement::FunctionDefinition(FunctionDefinition {
+                body: reread(&function_definition.body),
+                ..function_definition
+            })
+        }
+        Statement::Equation(relation) => match reread(&Expression::Relation(relation)) {
+            Expression::Relation(relation) => Statement::Equation(relation),
+            _ => unreachable!("rereading keeps the relation"),
+        },
+        Statement::Expression(expression) => Statement::Expression(reread(&expression)),
+        Statement::Derivation(derivation) => Statement::Derivation(Derivation {
+            steps: derivation
+                .steps
+                .into_iter()
+                .map(|step| Step {
+                    expression: reread(&step.expression),
+                    ..step
+                })
+                .collect(),
+        }),
+    }
+}
+
+/// Each `d/dt(x)` parsed as a derivative in `expression`, read again the way it's parsed once
+/// `d` is defined, as `d` divided by `dt(x)`. The division comes back loose when it still has to
+/// be joined up with what's around it that binds tighter than `/`, like the `^2` of
+/// `d/dt(x)^2`. `tokens` are what the expression was parsed from.
+fn reread<'src>(expression: &Expression<'src>, tokens: TS<'_, 'src>) -> (Expression<'src>, bool) {
+    let loose_parts = |expression: Expression<'src>, loose: bool| match expression {
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Divide,
+            left,
+            right,
+        }) if loose => Ok((*left, *right)),
+        expression => Err(expression),
+    };
+    match expression {
+        Expression::Derivative(Derivative::Leibniz { variable, body }) => {
+            let Some((d, dt)) = leibniz_names(variable, tokens) else {
+                let body = Box::new(reread(body, tokens).0);
+                let variable = variable.clone();
+                return (
+                    Expression::Derivative(Derivative::Leibniz { variable, body }),
+                    false,
+                );
+            };
+            let call = Expression::FunctionCall(FunctionCall {
+                identifier: dt,
+                inputs: vec![reread(body, tokens).0],
+            });
+            let d = Expression::Variable(Variable { identifier: d });
+            (binary(BinaryOperator::Divide, d, call), true)
+        }
+        Expression::Unary(unary) => {
+            let (operand, loose) = reread(&unary.operand, tokens);
+            let unary = |operand| {
+                Expression::Unary(Unary {
+                    operator: unary.operator,
+                    operand: Box::new(operand),
+                })
+            };
+            match loose_parts(operand, loose) {
+                // `-d/dt(x)` is `-d` divided by `dt(x)`
+                Ok((d, call)) => (binary(BinaryOperator::Divide, unary(d), call), true),
+                Err(operand) => (unary(operand), false),
+            }
+        }
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Power,
+            left,
+            right,
+        }) => {
+            let (base, base_loose) = reread(left, tokens);
+            let (exponent, exponent_loose) = reread(right, tokens);
+            // `2^d/dt(x)` is `2^d` divided by `dt(x)`
+            let (exponent, after) = match loose_parts(exponent, exponent_loose) {
+                Ok((d, call)) => (d, Some(call)),
+                Err(exponent) => (exponent, None),
+            };
+            // `d/dt(x)^2` is `d` divided by `dt(x)^2`
+            let (power, loose) = match loose_parts(base, base_loose) {
+                Ok((d, call)) => {
+                    let power = binary(BinaryOperator::Power, call, exponent);
+                    (binary(BinaryOperator::Divide, d, power), true)
+                }
+                Err(base) => (binary(BinaryOperator::Power, base, exponent), false),
+            };
+            match after {
+                Some(call) => (binary(BinaryOperator::Divide, power, call), true),
+                None => (power, loose),
+            }
+        }
+        Expression::Binary(Binary {
+            operator,
+            left,
+            right,
+        }) => {
+            let left = reread(left, tokens).0;
+            let (right, loose) = reread(right, tokens);
+            let product = matches!(
+                operator,
+                BinaryOperator::Multiply
+                    | BinaryOperator::ImplicitMultiply
+                    | BinaryOperator::Divide
+                    | BinaryOperator::Intersection
+            );
+            // Products go left to right, so `a/d/dt(x)` is `a/d` divided by `dt(x)`
+            match loose_parts(right, loose && product) {
+                Ok((d, call)) => {
+                    let left = binary(*operator, left, d);
+                    (binary(BinaryOperator::Divide, left, call), false)
+                }
+                Err(right) => (binary(*operator, left, right), false),
+            }
+        }
+        _ => {
+            let rewritten = expression.rewrite(&mut |part| {
+                (!std::ptr::eq(part, expression)).then(|| reread(part, tokens).0)
+            });
+            (rewritten, false)
+        }
+    }
+}
+
+/// Source of the `d` and `dt` of the `d/dt(...)` a derivative with `variable` was parsed from.
+/// [`None`] when it was written some other way, like `diff(x^2, t)`.
+fn leibniz_names<'src>(
+    variable: &Identifier<'src>,
+    tokens: TS<'_, 'src>,
+) -> Option<(Identifier<'src>, Identifier<'src>)> {
+    let forward_slash = lexer::TokenKind::Operator(lexer::Operator::ForwardSlash);
+    let position = tokens.iter().position(|token| {
+        token.fragment.as_ptr().wrapping_add(1) == variable.text.as_ptr()
+            && token.fragment.get(1..) == Some(variable.text)
+            && token.fragment.starts_with('d')
+    })?;
+    match tokens.get(position.checked_sub(2)?..=position)? {
+        [d, slash, dt] if d.fragment == "d" && slash.kind == forward_slash => Some((
+            Identifier { text: d.fragment },
+            Identifier { text: dt.fragment },
+        )),
+        _ => None,
+    }
+}
+
+fn prime_derivative<'a, 'src>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, Derivative<'src>> {
+    let single_quote = lexer::TokenKind::Operator(lexer::Operator::SingleQuote);
+    let (mut input, function) = identifier(input)?;
+    let mut order = 0;
+    while let Ok((rest, _)) = tag_tokens_kind(&[single_quote])(input) {
+        order += 1;
+        input = rest;
+    }
+    if order == 0 {
+        return Err(anyhow!(Error::new(input))).with_context(|| "derivative expecting `'`");
+    }
+    let (input, inputs) = match function_call_inputs(input) {
+        Ok((input, inputs)) => (input, Some(inputs)),
+        Err(_) => (input, None),
+    };
+    Ok((
+        input,
+        Derivative::Prime {
+            function,
+            order,
+            inputs,
+        },
+    ))
+}
+
+/// Turns calls like `sum(i, 1, n, i^2)` into their own node. Other calls stay calls.
+fn special_form(call: FunctionCall<'_>) -> Expression<'_> {
+    if call.identifier.text == "pd" && call.inputs.len() >= 2 {
+        return partial_derivative(call);
+    }
+    let delimiter = match call.identifier.text {
+        "abs" => Some(DelimiterKind::Abs),
+        "norm" => Some(DelimiterKind::Norm),
+        "floor" => Some(DelimiterKind::Floor),
+        "ceil" => Some(DelimiterKind::Ceil),
+        _ => None,
+    };
+    if let (Some(kind), 1) = (delimiter, call.inputs.len()) {
+        let inner = call.inputs.into_iter().next().unwrap();
+        return delimited(kind, inner);
+    }
+    let variable_position = match (call.identifier.text, call.inputs.len()) {
+        ("sum" | "prod", 4) | ("lim", 3) => 0,
+        ("int", 2 | 4) | ("diff", 2) => 1,
+        _ => return Expression::FunctionCall(call),
+    };
+    let Some(variable) = as_identifier(&call.inputs[variable_position]) else {
+        return Expression::FunctionCall(call);
+    };
+    let FunctionCall { identifier, inputs } = call;
+    let mut inputs = inputs.into_iter().map(Box::new);
+    let mut next = || inputs.next().unwrap();
+    match identifier.text {
+        "sum" | "prod" => {
+            next();
+            Expression::BigOperator(BigOperator {
+                kind: match identifier.text {
+                    "sum" => BigOperatorKind::Sum,
+                    _ => BigOperatorKind::Product,
+                },
+                index: variable,
+                from: next(),
+                to: next(),
+                body: next(),
+            })
+        }
+        "diff" => Expression::Derivative(Derivative::Leibniz {
+            body: next(),
+            variable,
+        }),
+        "int" => {
+            let integrand = next();
+            next();
+            let bounds = inputs.next().zip(inputs.next());
+            Expression::Integral(Integral {
+                integrand,
+                variable,
+                bounds,
+            })
+        }
+        _ => {
+            next();
+            Expression::Limit(Limit {
+                variable,
+                approaching: next(),
+                body: next(),
+            })
+        }
+    }
+}
+
+/// `pd(f, x, y)`, if everything after `f` is a variable
+fn partial_derivative(call: FunctionCall<'_>) -> Expression<'_> {
+    let Some(variables) = call.inputs[1..].iter().map(as_identifier).collect() else {
+        return Expression::FunctionCall(call);
+    };
+    let body = call.inputs.into_iter().next().unwrap();
+    Expression::Derivative(Derivative::Partial {
+        body: Box::new(body),
+        variables,
+    })
+}
+
+fn as_identifier<'src>(expression: &Expression<'src>) -> Option<Identifier<'src>> {
+    match expression {
+        Expression::Variable(variable) => Some(variable.identifier.clone()),
+        _ => None,
+    }
+}
+
 fn function_call<'src, 'a>(input: TS<'a, 'src>) -> IResult<TS<'a, 'src>, FunctionCall<'src>> {
     let (input, identifier) = identifier(input)?;
     let (input, function_call_inputs) = function_call_inputs(input)?;
@@ -104,23 +1276,23 @@ fn expressions_in_round_bracket_seperated_by_comma<'a, 'src>(
     let comma = lexer::TokenKind::Operator(lexer::Operator::Comma);
     surround_seperated_items_allowed_trailing(
         expression,
-        tag(&[comma]),
-        tag(&[left_bracket]),
-        tag(&[right_bracket]),
+        tag_tokens_kind(&[comma]),
+        tag_tokens_kind(&[left_bracket]),
+        tag_tokens_kind(&[right_bracket]),
     )(input)
 }
 
-fn surround_seperated_items_allowed_trailing<I, IP, SP, EP, BP>(
+fn surround_seperated_items_allowed_trailing<'a, 'src: 'a, I, IP, SP, EP, BP>(
     item_parser: IP,
     seperator: SP,
     begin: EP,
     end: BP,
-) -> impl for<'a, 'src> Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, Vec<I>>
+) -> impl Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, Vec<I>>
 where
-    IP: for<'a, 'src> Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, I>,
-    SP: for<'a, 'src> Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>>,
-    BP: for<'a, 'src> Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>>,
-    EP: for<'a, 'src> Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>>,
+    IP: Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, I>,
+    SP: Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>>,
+    BP: Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>>,
+    EP: Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>>,
 {
     move |input| {
         let mut vec = vec![];
@@ -1,13 +1,13 @@
 use super::super::TS;
-use anyhow::{anyhow, Context};
+use anyhow::anyhow;
 
 use super::super::Error;
 
 pub type IResult<I, O> = std::result::Result<(I, O), anyhow::Error>;
 
-pub fn take<'a, 'src>(
+pub fn take<'a, 'src: 'a>(
     at: usize,
-) -> impl for<'b, 'bsrc> Fn(TS<'b, 'bsrc>) -> IResult<TS<'b, 'bsrc>, TS<'b, 'bsrc>> {
+) -> impl Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, TS<'a, 'src>> {
     move |input| {
         if at > input.len() {
             Err(anyhow!(Error::new(input)))
@@ -18,29 +18,29 @@ pub fn take<'a, 'src>(
     }
 }
 
-pub fn try_map<'a, 'src, P, PO, M, U>(
+pub fn try_map<'a, 'src: 'a, P, PO, M, U>(
     parser: P,
     mapper: M,
 ) -> impl Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, U>
 where
-    P: for<'b, 'bsrc> Fn(TS<'b, 'bsrc>) -> IResult<TS<'b, 'bsrc>, PO>,
+    P: Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, PO>,
     M: Fn(PO) -> Option<U>,
 {
     move |input| {
         let (i, o) = parser(input)?;
         match mapper(o) {
             Some(o) => Ok((i, o)),
-            None => Err(Error::new(input)),
+            None => Err(anyhow!(Error::new(input))),
         }
     }
 }
 
-pub fn map<'a, 'src, P, PO, M, U>(
+pub fn map<'a, 'src: 'a, P, PO, M, U>(
     parser: P,
     mapper: M,
-) -> impl for<'b, 'bsrc> Fn(TS<'b, 'bsrc>) -> IResult<TS<'b, 'bsrc>, U>
+) -> impl Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, U>
 where
-    P: for<'b, 'bsrc> Fn(TS<'b, 'bsrc>) -> IResult<TS<'b, 'bsrc>, PO>,
+    P: Fn(TS<'a, 'src>) -> IResult<TS<'a, 'src>, PO>,
     M: Fn(PO) -> U,
 {
     move |input| {
@@ -101,17 +101,3 @@ where
 //         }
 //     }
 // }
-
-pub fn tag<'a, 'src>(
-    this: TS<'a, 'src>,
-) -> impl for<'b, 'bsrc> Fn(TS<'b, 'bsrc>) -> IResult<TS<'b, 'bsrc>, TS<'b, 'bsrc>> {
-    let this = this.to_vec();
-    move |input| {
-        let slice = &input[..this.len()];
-        if slice == this {
-            Ok((input, slice))
-        } else {
-            Err(Error::new(input))
-        }
-    }
-}
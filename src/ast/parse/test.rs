@@ -1,39 +1,492 @@
 use crate::{
-    ast::{Expression, Float, FunctionCall, Identifier as AstIdentifier, Literal, Variable, Whole},
-    lexer::{Identifier as LexIdentifier, Token},
+    ast::{
+        Binary, BinaryOperator, Decimal, DelimiterKind, Derivative, Expression, Float,
+        FunctionCall, Identifier as AstIdentifier, Literal, Matrix, NumberSet, Quantity, Radix,
+        RelationOperator, Scientific, Statement, StepKind, Subscript, UnitFactor, Variable, Whole,
+    },
+    lexer::{Token, TokenKind},
 };
 
 fn lex_it(str: &str) -> crate::lexer::Tokens<'_> {
     crate::lexer::parse(str).unwrap()
 }
 
+fn decimal(digits: u64, scale: usize) -> Decimal {
+    Decimal {
+        digits: digits.into(),
+        scale,
+    }
+}
+
 #[test]
 fn expression() {
+    let tokens = lex_it("23.43 otherwise");
+    let (rest, expression) = super::expression(tokens.as_slice()).unwrap();
     assert_eq!(
-        super::expression(lex_it("23.43 aha").as_slice()),
-        Ok((
-            [Token::Identifier(LexIdentifier { str: "aha" })].as_slice(),
-            Expression::Literal(Literal::Float(Float(23.43)))
-        ))
-    )
+        rest,
+        [Token::new("otherwise", TokenKind::Identifier)].as_slice()
+    );
+    assert_eq!(
+        expression,
+        Expression::Literal(Literal::Float(Float {
+            text: "23.43",
+            value: decimal(2343, 2)
+        }))
+    );
 }
 
 #[test]
 fn function_call() {
+    let tokens = lex_it("hello(lmao, 2132, 32.3) nextthing");
+    let (rest, function_call) = super::function_call(tokens.as_slice()).unwrap();
     assert_eq!(
-        super::function_call(lex_it("hello(lmao, 2132, 32.3) nextthing").as_slice()),
-        Ok((
-            [Token::Identifier(LexIdentifier { str: "nextthing" })].as_slice(),
-            FunctionCall {
-                identifier: AstIdentifier { text: "hello" },
-                inputs: vec![
-                    Expression::Variable(Variable {
-                        identifier: AstIdentifier { text: "lmao" }
-                    }),
-                    Expression::Literal(Literal::Whole(Whole(2132))),
-                    Expression::Literal(Literal::Float(Float(32.3)))
-                ]
-            }
-        ))
+        rest,
+        [Token::new("nextthing", TokenKind::Identifier)].as_slice()
+    );
+    assert_eq!(
+        function_call,
+        FunctionCall {
+            identifier: AstIdentifier { text: "hello" },
+            inputs: vec![
+                Expression::Variable(Variable {
+                    identifier: AstIdentifier { text: "lmao" }
+                }),
+                Expression::Literal(Literal::Whole(Whole {
+                    text: "2132",
+                    value: 2132u64.into(),
+                    radix: Radix::Decimal
+                })),
+                Expression::Literal(Literal::Float(Float {
+                    text: "32.3",
+                    value: decimal(323, 1)
+                }))
+            ]
+        }
     )
 }
+
+#[test]
+fn literal() {
+    let literal = |str| super::literal(lex_it(str).as_slice()).unwrap().1;
+    assert_eq!(
+        literal("1_000_000"),
+        Literal::Whole(Whole {
+            text: "1_000_000",
+            value: 1_000_000u64.into(),
+            radix: Radix::Decimal
+        })
+    );
+    assert_eq!(
+        literal("0xFF"),
+        Literal::Whole(Whole {
+            text: "0xFF",
+            value: 255u64.into(),
+            radix: Radix::Hexadecimal
+        })
+    );
+    assert_eq!(
+        literal(".5"),
+        Literal::Float(Float {
+            text: ".5",
+            value: decimal(5, 1)
+        })
+    );
+    assert_eq!(
+        literal("6.022e23"),
+        Literal::Scientific(Scientific {
+            text: "6.022e23",
+            significand: decimal(6022, 3),
+            exponent: 23.into()
+        })
+    );
+    assert_eq!(
+        literal("1e-9"),
+        Literal::Scientific(Scientific {
+            text: "1e-9",
+            significand: decimal(1, 0),
+            exponent: (-9).into()
+        })
+    );
+}
+
+#[test]
+fn big_literal() {
+    let literal = |str| super::literal(lex_it(str).as_slice()).unwrap().1;
+    let big = literal("1234567890123456789012345");
+    assert_eq!(big.text(), "1234567890123456789012345");
+    assert!(big.to_f64().is_ok());
+    let Literal::Whole(whole) = &big else {
+        panic!("{big:?}")
+    };
+    assert!(whole.to_u64().is_err());
+    assert_eq!(whole.value, "1234567890123456789012345".parse().unwrap());
+
+    let huge = literal("1e400");
+    let diagnostic = huge.to_f64().unwrap_err();
+    assert_eq!(diagnostic.fragment, "1e400");
+}
+
+#[test]
+fn subscript() {
+    let variable = |text| {
+        Box::new(Expression::Variable(Variable {
+            identifier: AstIdentifier { text },
+        }))
+    };
+    let tokens = lex_it("a_(n) x_1");
+    let (rest, subscript) = super::subscript(tokens.as_slice()).unwrap();
+    assert_eq!(rest.len(), 3);
+    assert_eq!(
+        subscript,
+        Subscript {
+            base: variable("a"),
+            index: variable("n"),
+        }
+    );
+}
+
+#[test]
+fn relation() {
+    let tokens = lex_it("0 < x <= 1");
+    let (rest, relation) = super::expression(tokens.as_slice()).unwrap();
+    assert!(rest.is_empty());
+    let Expression::Relation(relation) = relation else {
+        panic!("{relation:?}");
+    };
+    let operators: Vec<_> = relation.rest.iter().map(|(o, _)| *o).collect();
+    assert_eq!(
+        operators,
+        [RelationOperator::Less, RelationOperator::LessEqual]
+    );
+}
+
+#[test]
+fn precedence() {
+    let tokens = lex_it("1 + 2x^2 * -3");
+    let (rest, sum) = super::expression(tokens.as_slice()).unwrap();
+    assert!(rest.is_empty());
+    let Expression::Binary(Binary {
+        operator: BinaryOperator::Add,
+        right: product,
+        ..
+    }) = sum
+    else {
+        panic!("{sum:?}");
+    };
+    let Expression::Binary(Binary {
+        operator: BinaryOperator::Multiply,
+        left: implicit,
+        right: negated,
+    }) = *product
+    else {
+        panic!("{product:?}");
+    };
+    assert!(matches!(
+        *implicit,
+        Expression::Binary(Binary {
+            operator: BinaryOperator::ImplicitMultiply,
+            ..
+        })
+    ));
+    assert!(matches!(*negated, Expression::Unary(_)));
+}
+
+#[test]
+fn implicit_multiply() {
+    let expression = |str| {
+        let tokens = lex_it(str);
+        let (rest, expression) = super::expression(tokens.as_slice()).unwrap();
+        assert!(rest.is_empty(), "{str}");
+        expression
+    };
+    for str in ["2 x", "x^2 sin(x)", "a b", "2 (x + 1)"] {
+        let product = expression(str);
+        assert!(
+            matches!(
+                product,
+                Expression::Binary(Binary {
+                    operator: BinaryOperator::ImplicitMultiply,
+                    ..
+                })
+            ),
+            "{str}: {product:?}"
+        );
+    }
+    assert!(matches!(expression("9.81 m/s^2"), Expression::Quantity(_)));
+    assert!(matches!(expression("x in A"), Expression::Relation(_)));
+}
+
+#[test]
+fn statements() {
+    let tokens = lex_it("let g = 9.81\n\nf(x, y) = x^2 + y; x^2 = 4\n2x\nf(x, x) = 1");
+    let (rest, ast) = super::ast(tokens.as_slice()).unwrap();
+    assert!(rest.is_empty());
+    let [Statement::Definition(definition), Statement::FunctionDefinition(function_definition), Statement::Equation(_), Statement::Expression(_), Statement::Equation(_)] =
+        ast.statements.as_slice()
+    else {
+        panic!("{ast:?}");
+    };
+    assert_eq!(definition.identifier.text, "g");
+    assert_eq!(function_definition.identifier.text, "f");
+    assert_eq!(function_definition.parameters.len(), 2);
+
+    assert!(super::ast(lex_it("1 2").as_slice()).is_err());
+}
+
+#[test]
+fn derivation() {
+    let tokens = lex_it("(x+1)^2\n  = x^2 + 2x + 1 \"expand\"\n  => x = 1\ny = 2");
+    let (rest, ast) = super::ast(tokens.as_slice()).unwrap();
+    assert!(rest.is_empty());
+    let [Statement::Derivation(derivation), Statement::Equation(_)] = ast.statements.as_slice()
+    else {
+        panic!("{ast:?}");
+    };
+    let kinds: Vec<_> = derivation.steps.iter().map(|step| step.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            StepKind::Start,
+            StepKind::Continues(RelationOperator::Equal),
+            StepKind::Implies
+        ]
+    );
+    assert_eq!(derivation.steps[1].justification, Some("expand"));
+}
+
+#[test]
+fn derivative() {
+    let expression = |str| super::expression(lex_it(str).as_slice()).unwrap().1;
+    let Expression::Derivative(Derivative::Leibniz { variable, .. }) = expression("d/dx(x^2)")
+    else {
+        panic!();
+    };
+    assert_eq!(variable.text, "x");
+    // Once `d` is defined it's a division
+    let tokens = lex_it("let d = 2\nd/dt(3)\n3/d/dt(x)^2");
+    let (_, ast) = super::ast(tokens.as_slice()).unwrap();
+    assert!(matches!(
+        &ast.statements[1],
+        Statement::Expression(Expression::Binary(Binary {
+            operator: BinaryOperator::Divide,
+            ..
+        }))
+    ));
+    // Joined up with what's around it the same way as any other division
+    let Statement::Expression(Expression::Binary(Binary {
+        operator: BinaryOperator::Divide,
+        left,
+        right,
+    })) = &ast.statements[2]
+    else {
+        panic!("{:?}", ast.statements[2]);
+    };
+    assert!(matches!(
+        (&**left, &**right),
+        (
+            Expression::Binary(Binary {
+                operator: BinaryOperator::Divide,
+                ..
+            }),
+            Expression::Binary(Binary {
+                operator: BinaryOperator::Power,
+                ..
+            })
+        )
+    ));
+    let tokens = lex_it("d/dt(3)");
+    let (_, ast) = super::ast(tokens.as_slice()).unwrap();
+    assert!(matches!(
+        &ast.statements[0],
+        Statement::Expression(Expression::Derivative(_))
+    ));
+    assert!(matches!(
+        expression("f''(x)"),
+        Expression::Derivative(Derivative::Prime {
+            order: 2,
+            inputs: Some(_),
+            ..
+        })
+    ));
+    assert!(matches!(
+        expression("y'"),
+        Expression::Derivative(Derivative::Prime { inputs: None, .. })
+    ));
+    let derivative = expression("pd(f, x, x, y)");
+    let Expression::Derivative(derivative) = derivative else {
+        panic!("{derivative:?}");
+    };
+    assert_eq!(derivative.order(), 3);
+    assert_eq!(derivative.grouped_variables().len(), 2);
+    assert!(matches!(
+        expression("pd(f, 2)"),
+        Expression::FunctionCall(_)
+    ));
+    let Expression::Derivative(Derivative::Leibniz { variable, .. }) = expression("diff(x^2, t)")
+    else {
+        panic!();
+    };
+    assert_eq!(variable.text, "t");
+    assert!(matches!(
+        expression("diff(x^2, 2)"),
+        Expression::FunctionCall(_)
+    ));
+}
+
+#[test]
+fn matrix() {
+    let expression = |str| super::expression(lex_it(str).as_slice()).map(|(_, e)| e);
+    let Ok(Expression::Vector(vector)) = expression("[1, 2, 3]") else {
+        panic!();
+    };
+    assert_eq!(vector.elements.len(), 3);
+    let Ok(Expression::Matrix(Matrix { rows })) = expression("[[1, 2],\n [3, 4]]") else {
+        panic!();
+    };
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().all(|row| row.len() == 2));
+
+    let ragged = expression("[[1, 2], [3]]").unwrap_err();
+    assert!(ragged.to_string().contains("same length"));
+    assert!(expression("[[1, 2], 3]").is_err());
+}
+
+#[test]
+fn piecewise() {
+    let expression = |str| super::expression(lex_it(str).as_slice()).map(|(_, e)| e);
+    let Ok(Expression::Piecewise(piecewise)) =
+        expression("piecewise(x^2 if x < 0, 1 if x = 0, x otherwise)")
+    else {
+        panic!();
+    };
+    assert_eq!(piecewise.arms.len(), 2);
+    assert!(matches!(
+        piecewise.arms[0].condition,
+        Expression::Relation(_)
+    ));
+    assert!(piecewise.otherwise.is_some());
+
+    assert!(expression("piecewise(x otherwise, 1 if x < 0)").is_err());
+    assert!(expression("piecewise(x, 1)").is_err());
+}
+
+#[test]
+fn sets() {
+    let expression = |str| super::expression(lex_it(str).as_slice()).map(|(_, e)| e);
+    let Ok(Expression::Interval(interval)) = expression("[0, 1)") else {
+        panic!();
+    };
+    assert!(interval.lower_closed && !interval.upper_closed);
+    // A point, unless a set is expected
+    let Ok(Expression::Group(point)) = expression("(a, b)") else {
+        panic!();
+    };
+    assert!(matches!(*point.inner, Expression::List(_)));
+    assert!(matches!(expression("[a, b]"), Ok(Expression::Vector(_))));
+    assert!(expression("(1, 2, 3]").is_err());
+
+    let Ok(Expression::SetBuilder(set_builder)) = expression("{x in R | x > 0}") else {
+        panic!();
+    };
+    assert_eq!(
+        set_builder.domain.as_deref(),
+        Some(&Expression::NumberSet(NumberSet::Real))
+    );
+    let Ok(Expression::Relation(relation)) = expression("x in [0, 1]") else {
+        panic!();
+    };
+    assert!(matches!(
+        relation.rest.as_slice(),
+        [(RelationOperator::In, Expression::Interval(_))]
+    ));
+    let Ok(Expression::Binary(union)) = expression("{1, 2} union (0, 1)") else {
+        panic!();
+    };
+    assert_eq!(union.operator, BinaryOperator::Union);
+    assert!(matches!(*union.right, Expression::Interval(_)));
+
+    // Names like any other when they aren't between two operands
+    let tokens = lex_it("let in = 3\nin + 1\nsubset(x) = x\nsubset(2)\nunion");
+    let (rest, ast) = super::ast(tokens.as_slice()).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(ast.statements.len(), 5);
+}
+
+#[test]
+fn delimited() {
+    let kind = |str| match super::expression(lex_it(str).as_slice()) {
+        Ok(([], Expression::Delimited(delimited))) => delimited.kind,
+        other => panic!("{other:?}"),
+    };
+    assert_eq!(kind("|x - 1|"), DelimiterKind::Abs);
+    assert_eq!(kind("||v||"), DelimiterKind::Norm);
+    assert_eq!(kind("||x| - 1|"), DelimiterKind::Abs);
+    assert_eq!(kind("| |x| - 1 |"), DelimiterKind::Abs);
+    assert_eq!(kind("floor(x / 2)"), DelimiterKind::Floor);
+    assert_eq!(kind("ceil(x)"), DelimiterKind::Ceil);
+
+    let tokens = lex_it("{x in R | |x| < 1}");
+    let (rest, set_builder) = super::expression(tokens.as_slice()).unwrap();
+    assert!(rest.is_empty());
+    assert!(matches!(set_builder, Expression::SetBuilder(_)));
+}
+
+#[test]
+fn group() {
+    let tokens = lex_it("(x + 1)^2");
+    let (_, power) = super::expression(tokens.as_slice()).unwrap();
+    let Expression::Binary(Binary { left, .. }) = &power else {
+        panic!("{power:?}");
+    };
+    assert!(matches!(left.as_ref(), Expression::Group(_)));
+    assert!(matches!(left.ungrouped(), Expression::Binary(_)));
+}
+
+#[test]
+fn quantity() {
+    let tokens = lex_it("9.81 m/s^2");
+    let (rest, quantity) = super::expression(tokens.as_slice()).unwrap();
+    assert!(rest.is_empty());
+    let Expression::Quantity(Quantity { value, unit }) = &quantity else {
+        panic!("{quantity:?}");
+    };
+    assert!(matches!(
+        value.as_ref(),
+        Expression::Literal(Literal::Float(_))
+    ));
+    assert_eq!(
+        unit.factors,
+        [
+            UnitFactor {
+                symbol: "m",
+                exponent: 1
+            },
+            UnitFactor {
+                symbol: "s",
+                exponent: -2
+            }
+        ]
+    );
+
+    // A `*` followed by a number starts the next factor, not another unit
+    let tokens = lex_it("3 kg * 2 m/s^2");
+    let (rest, product) = super::expression(tokens.as_slice()).unwrap();
+    assert!(rest.is_empty());
+    let Expression::Binary(Binary { left, right, .. }) = &product else {
+        panic!("{product:?}");
+    };
+    assert!(matches!(left.as_ref(), Expression::Quantity(_)));
+    assert!(matches!(right.as_ref(), Expression::Quantity(_)));
+
+    // Written together it's multiplication, and only known units are units
+    let tokens = lex_it("2m");
+    let (_, product) = super::expression(tokens.as_slice()).unwrap();
+    assert!(matches!(product, Expression::Binary(_)));
+    let tokens = lex_it("2 kg m s^-1");
+    let (rest, quantity) = super::expression(tokens.as_slice()).unwrap();
+    assert!(rest.is_empty());
+    let Expression::Quantity(Quantity { unit, .. }) = &quantity else {
+        panic!("{quantity:?}");
+    };
+    assert_eq!(unit.factors.len(), 3);
+}
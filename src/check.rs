@@ -0,0 +1,186 @@
+//! Checks working out by trying each step at random points, like a student checking their
+//! algebra by putting numbers in. A step that's wrong almost always gives different numbers on
+//! each side somewhere, while a right one never does.
+
+use crate::{
+    ast::*,
+    diagnostic::Diagnostic,
+    eval::{Environment, Value},
+};
+
+/// Points each step is tried at
+const POINTS: usize = 32;
+
+impl<'src> Derivation<'src> {
+    /// Finds the first written step that doesn't hold. Steps continuing with `= ...` must relate
+    /// to the right hand side of the step before, and a first step like `(x+1)^2 = x^2+2x+1` must
+    /// hold on its own. Equations being solved with `=>` aren't checked, since `x^2 = 4` doesn't
+    /// hold everywhere. Nor are steps that can't be evaluated, like ones using an unknown `f(x)`.
+    /// Names defined in `environment`, like by the statements before, keep their meaning.
+    pub fn check(&self, environment: &Environment<'_, 'src>) -> Result<(), Diagnostic<'src>> {
+        let solving = self.steps.iter().any(|step| step.kind == StepKind::Implies);
+        let mut previous: Option<&Expression<'src>> = None;
+        for step in &self.steps {
+            let claim = match (step.kind, previous) {
+                (StepKind::Start, _) if !solving => Some(step.expression.clone()),
+                (StepKind::Continues(operator), Some(previous)) => {
+                    Some(Expression::Relation(Relation {
+                        first: Box::new(previous.clone()),
+                        rest: vec![(operator, step.expression.clone())],
+                    }))
+                }
+                _ => None,
+            };
+            if let (Some(claim), Some(text)) = (claim, step.text) {
+                if let Some(counterexample) = counterexample(&claim, environment) {
+                    let message = match counterexample.as_str() {
+                        "" => "this step doesn't hold".to_string(),
+                        point => format!("this step doesn't hold at {point}"),
+                    };
+                    return Err(Diagnostic::error(text, message));
+                }
+            }
+            previous = Some(match &step.expression {
+                Expression::Relation(relation) => match relation.rest.last() {
+                    Some((_, right)) => right,
+                    None => &relation.first,
+                },
+                expression => expression,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Where a relation is false, like `x = 1.5, y = -0.25`. [`None`] when it's true everywhere it
+/// was tried, or isn't a relation at all. Only names not known in `environment` are tried.
+fn counterexample<'src>(
+    claim: &Expression<'src>,
+    environment: &Environment<'_, 'src>,
+) -> Option<String> {
+    if !matches!(claim, Expression::Relation(_)) {
+        return None;
+    }
+    let variables = variables(claim, environment);
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    for _ in 0..POINTS {
+        let mut environment = environment.scope();
+        let mut point = Vec::new();
+        for variable in &variables {
+            let value = random.value();
+            environment.bind(variable, value);
+            point.push(format!("{variable} = {value}"));
+        }
+        // Points outside the domain, like a negative under `sqrt`, don't say anything
+        if let Ok(Value::Bool(false)) = environment.evaluate(claim) {
+            return Some(point.join(", "));
+        }
+    }
+    None
+}
+
+/// Names of the variables in the expression that need values, in the order they appear
+fn variables<'src>(
+    expression: &Expression<'src>,
+    environment: &Environment<'_, 'src>,
+) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+    expression.rewrite(&mut |expression| {
+        if let Expression::Variable(variable) = expression {
+            let name = variable.identifier.text;
+            // Constants like `pi`, and names defined before, are already known
+            let known = environment.evaluate(expression).is_ok();
+            if !known && !variables.iter().any(|other| other == name) {
+                variables.push(name.to_string());
+            }
+        }
+        None
+    });
+    variables
+}
+
+/// Xorshift, seeded the same every time so the same working gets the same verdict
+struct Random(u64);
+
+impl Random {
+    /// Between -4 and 4, with a quarter step, so values are exact and print short but
+    /// aren't whole numbers that hide mistakes like `x^2 = x` at 0 and 1
+    fn value(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        let quarters = (self.0 % 32) as f64 - 16.0;
+        quarters / 4.0 + 0.125
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ast::*, eval::Environment};
+
+    /// Checks the derivation at the end, knowing the definitions before it
+    fn check(str: &str) -> Result<(), String> {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let [definitions @ .., Statement::Derivation(derivation)] = ast.statements.as_slice()
+        else {
+            panic!("{ast:?}");
+        };
+        let mut environment = Environment::new();
+        for definition in definitions {
+            environment.run(definition).unwrap();
+        }
+        derivation
+            .check(&environment)
+            .map_err(|diagnostic| diagnostic.render(str))
+    }
+
+    #[test]
+    fn holds() {
+        assert_eq!(check("(x+1)^2\n= (x+1)(x+1)\n= x^2 + 2x + 1"), Ok(()));
+        assert_eq!(check("(x+y)^2 = x^2 + 2x*y + y^2 \"expand\""), Ok(()));
+        assert_eq!(check("sqrt(x^2)\n= |x|"), Ok(()));
+        assert_eq!(check("sin(x)^2 + cos(x)^2\n= 1"), Ok(()));
+        assert_eq!(check("x^2 - 4 = 0\n=> x = 2"), Ok(()));
+        assert_eq!(check("f(x) + f(x)\n= 3f(x)"), Ok(()));
+        assert_eq!(check("2 km + 300 m\n= 2300 m"), Ok(()));
+        assert_eq!(check("let a = 2\na x\n= 2x"), Ok(()));
+        assert_eq!(check("f(x) = 3x\nf(x) + f(x)\n= 6x"), Ok(()));
+    }
+
+    #[test]
+    fn mistakes() {
+        assert_eq!(
+            check("(x+1)^2\n= x^2 + 1 \"expand\"\n= x^2 + 1"),
+            Err("2:1: error: this step doesn't hold at x = -2.125".to_string())
+        );
+        assert_eq!(
+            check("x^2*x^3\n= x^5\n= x^6"),
+            Err("3:1: error: this step doesn't hold at x = -2.125".to_string())
+        );
+        assert_eq!(
+            check("2(a + b) = 2a + b \"distribute\""),
+            Err("1:1: error: this step doesn't hold at a = -2.125, b = -3.875".to_string())
+        );
+        assert_eq!(
+            check("f(x) = x^2\nf(x) + 1\n= x^2"),
+            Err("3:1: error: this step doesn't hold at x = -2.125".to_string())
+        );
+        assert_eq!(
+            check("x^2\n<= x^2 + 1\n< x^2"),
+            Err("3:1: error: this step doesn't hold at x = -2.125".to_string())
+        );
+    }
+
+    #[test]
+    fn span() {
+        let source = "x^2*x^3\n= x^6 \"add the powers\"";
+        let tokens = crate::lexer::parse(source).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let [Statement::Derivation(derivation)] = ast.statements.as_slice() else {
+            panic!("{ast:?}");
+        };
+        let diagnostic = derivation.check(&Environment::new()).unwrap_err();
+        assert_eq!(diagnostic.fragment, "= x^6 \"add the powers\"");
+    }
+}
@@ -0,0 +1,106 @@
+//! Desmos backend. Writes one calculator expression per statement, in the LaTeX Desmos accepts.
+//! Desmos has no limits, so statements with one are left out.
+
+use std::fmt;
+
+use crate::latex::{Dialect, Latex};
+
+/// Displays the wrapped node as Desmos expressions
+pub struct Desmos<'a, T: ?Sized>(pub &'a T);
+
+impl<'a, T: ?Sized> fmt::Display for Desmos<'a, T>
+where
+    Latex<'a, T>: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Latex::with_dialect(self.0, Dialect::Desmos))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn desmos(str: &str) -> String {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        Desmos(&ast).to_string()
+    }
+
+    #[test]
+    fn statements() {
+        assert_eq!(desmos("f(x) = x^2 + 1"), r"f\left(x\right) = x^{2} + 1");
+        assert_eq!(desmos("let g = 9.81"), "g = 9.81");
+        assert_eq!(
+            desmos("let speed = 3\ny = 2speed"),
+            "s_{peed} = 3\ny = 2s_{peed}"
+        );
+        assert_eq!(
+            desmos("h(t) = lcm(t, 0xF)(1 + t)"),
+            r"h\left(t\right) = \operatorname{lcm}\left(t, 15\right)\left(1 + t\right)"
+        );
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            desmos("sqrt(x) + sign(x) + conj(z) + area(x)"),
+            r"\sqrt{x} + \operatorname{sign}\left(x\right) + \operatorname{conj}\left(z\right) + a_{rea}\left(x\right)"
+        );
+        assert_eq!(
+            desmos("v_max + speed_1 + x_n"),
+            "v_{max} + s_{peed1} + x_{n}"
+        );
+        assert_eq!(desmos("theta_0 + v_(n+1)"), r"\theta_{0} + v_{n + 1}");
+        assert_eq!(desmos("let v_1 = 3"), "v_{1} = 3");
+    }
+
+    #[test]
+    fn quantities() {
+        assert_eq!(desmos("9.81 m/s^2"), "9.81");
+        assert_eq!(desmos("5 km"), r"5 \cdot 1000");
+        assert_eq!(desmos("36 km/h"), r"36 \cdot \frac{5}{18}");
+    }
+
+    #[test]
+    fn piecewise() {
+        assert_eq!(
+            desmos("piecewise(x^2 if x < 0, x otherwise)"),
+            r"\left\{x < 0: x^{2}, x\right\}"
+        );
+        assert_eq!(
+            desmos("piecewise(1 if x >= 0)"),
+            r"\left\{x \ge 0: 1\right\}"
+        );
+    }
+
+    #[test]
+    fn delimited() {
+        assert_eq!(desmos("|x|"), r"\left|x\right|");
+        assert_eq!(
+            desmos("norm([3, 4])"),
+            r"\sqrt{\operatorname{total}\left(\left(\left[3, 4\right]\right)^{2}\right)}"
+        );
+        assert_eq!(
+            desmos("floor(x) + ceil(x)"),
+            r"\operatorname{floor}\left(x\right) + \operatorname{ceil}\left(x\right)"
+        );
+    }
+
+    #[test]
+    fn calculus() {
+        assert_eq!(desmos("sum(k, 1, 10, k^2)"), r"\sum_{k=1}^{10} k^{2}");
+        assert_eq!(desmos("int(x^2, x, 0, 1)"), r"\int_{0}^{1} x^{2} dx");
+        // Desmos has no limits, so the statement is left out
+        assert_eq!(desmos("lim(x, 0, sin(x)/x)\ny = 2"), "y = 2");
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(desmos("[1, 2, x]"), r"\left[1, 2, x\right]");
+        assert_eq!(
+            desmos("[[1, 2], [3, 4]]"),
+            r"\left[\left[1, 2\right], \left[3, 4\right]\right]"
+        );
+    }
+}
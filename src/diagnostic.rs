@@ -0,0 +1,103 @@
+//! Problems found in the source that are worth telling the user about.
+
+use std::fmt;
+
+/// Byte range into the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// Span of `fragment` inside `source`, or [`None`] when `fragment` isn't a slice of `source`
+    pub fn of(source: &str, fragment: &str) -> Option<Span> {
+        let start = (fragment.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+        let end = start + fragment.len();
+        (end <= source.len()).then_some(Span { start, end })
+    }
+
+    /// 1-based line and column of the start of the span
+    pub fn line_column(&self, source: &str) -> (usize, usize) {
+        let before = &source[..self.start];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        (line, column)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A message pointing at the piece of source it is about
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{severity}: {message} at `{fragment}`")]
+pub struct Diagnostic<'src> {
+    pub severity: Severity,
+    /// The source text this is about
+    pub fragment: &'src str,
+    pub message: String,
+}
+
+impl<'src> Diagnostic<'src> {
+    pub fn error(fragment: &'src str, message: impl Into<String>) -> Diagnostic<'src> {
+        Diagnostic {
+            severity: Severity::Error,
+            fragment,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(fragment: &'src str, message: impl Into<String>) -> Diagnostic<'src> {
+        Diagnostic {
+            severity: Severity::Warning,
+            fragment,
+            message: message.into(),
+        }
+    }
+
+    pub fn span(&self, source: &str) -> Option<Span> {
+        Span::of(source, self.fragment)
+    }
+
+    /// `line:column: severity: message`, or just `severity: message` when the diagnostic is
+    /// about some other source
+    pub fn render(&self, source: &str) -> String {
+        match self.span(source) {
+            Some(span) => {
+                let (line, column) = span.line_column(source);
+                format!("{line}:{column}: {}: {}", self.severity, self.message)
+            }
+            None => format!("{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let source = "sin(x)\n1e400";
+        let diagnostic = Diagnostic::error(&source[7..], "overflow");
+        assert_eq!(diagnostic.span(source), Some(Span { start: 7, end: 12 }));
+        assert_eq!(diagnostic.render(source), "2:1: error: overflow");
+        let elsewhere = String::from("1e400");
+        let other = Diagnostic::error(&elsewhere, "overflow");
+        assert_eq!(other.span(source), None);
+        assert_eq!(other.render(source), "error: overflow");
+    }
+}
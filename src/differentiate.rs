@@ -0,0 +1,556 @@
+//! Symbolic differentiation with the sum, product, quotient and chain rules. Results are
+//! [simplified](Expression::simplify).
+
+use crate::{ast::*, eval::Number, simplify::starts_with_number};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    #[error("can't differentiate {0}")]
+    Unsupported(&'static str),
+    #[error("can't differentiate `{0}`, which takes more than one input")]
+    SeveralInputs(String),
+}
+
+impl<'src> Expression<'src> {
+    /// Derivative with respect to `variable`. Other variables are constants.
+    pub fn differentiate(&self, variable: &Identifier<'_>) -> Result<Expression<'src>, Error> {
+        Ok(derivative(self, variable)?.simplify())
+    }
+
+    /// Whether `variable` appears free in the expression
+    pub fn mentions(&self, variable: &Identifier<'_>) -> bool {
+        let mentions = |expression: &Expression<'_>| expression.mentions(variable);
+        match self {
+            Expression::Variable(other) => same(&other.identifier, variable),
+            Expression::Literal(_) | Expression::Number(_) | Expression::NumberSet(_) => false,
+            Expression::Quantity(quantity) => mentions(&quantity.value),
+            Expression::FunctionCall(function_call) => function_call.inputs.iter().any(mentions),
+            Expression::Subscript(subscript) => {
+                mentions(&subscript.base) || mentions(&subscript.index)
+            }
+            Expression::Unary(unary) => mentions(&unary.operand),
+            Expression::Binary(binary) => mentions(&binary.left) || mentions(&binary.right),
+            Expression::Relation(relation) => {
+                mentions(&relation.first) || relation.rest.iter().any(|(_, right)| mentions(right))
+            }
+            Expression::BigOperator(big_operator) => {
+                mentions(&big_operator.from)
+                    || mentions(&big_operator.to)
+                    || (!same(&big_operator.index, variable) && mentions(&big_operator.body))
+            }
+            Expression::Integral(integral) => {
+                integral
+                    .bounds
+                    .iter()
+                    .any(|(lower, upper)| mentions(lower) || mentions(upper))
+                    || (integral.bounds.is_none() || !same(&integral.variable, variable))
+                        && mentions(&integral.integrand)
+            }
+            Expression::Limit(limit) => {
+                mentions(&limit.approaching)
+                    || (!same(&limit.variable, variable) && mentions(&limit.body))
+            }
+            Expression::Derivative(derivative) => match derivative {
+                Derivative::Leibniz { body, .. } | Derivative::Partial { body, .. } => {
+                    mentions(body)
+                }
+                Derivative::Prime { inputs, .. } => inputs.iter().flatten().any(mentions),
+            },
+            Expression::Vector(vector) => vector.elements.iter().any(mentions),
+            Expression::Matrix(matrix) => matrix.rows.iter().flatten().any(mentions),
+            Expression::Piecewise(piecewise) => {
+                piecewise
+                    .arms
+                    .iter()
+                    .any(|arm| mentions(&arm.value) || mentions(&arm.condition))
+                    || piecewise.otherwise.as_deref().is_some_and(mentions)
+            }
+            Expression::Set(set) => set.elements.iter().any(mentions),
+            Expression::SetBuilder(set_builder) => {
+                set_builder.domain.as_deref().is_some_and(mentions)
+                    || (!same(&set_builder.variable, variable) && mentions(&set_builder.condition))
+            }
+            Expression::Interval(interval) => {
+                mentions(&interval.lower) || mentions(&interval.upper)
+            }
+            Expression::Delimited(delimited) => mentions(&delimited.inner),
+            Expression::Group(group) => mentions(&group.inner),
+            Expression::List(list) => list.elements.iter().any(mentions),
+        }
+    }
+}
+
+impl<'src> Derivative<'src> {
+    /// Works the derivative out. Primes can only be worked out for functions like `sin` applied
+    /// to a variable, since the definition of `f` isn't known here.
+    pub fn compute(&self) -> Result<Expression<'src>, Error> {
+        match self {
+            Derivative::Leibniz { variable, body } => body.differentiate(variable),
+            Derivative::Partial { body, variables } => variables
+                .iter()
+                .try_fold(body.as_ref().clone(), |body, variable| {
+                    body.differentiate(variable)
+                }),
+            Derivative::Prime {
+                function,
+                order,
+                inputs: Some(inputs),
+            } => {
+                let [Expression::Variable(variable)] = inputs.as_slice() else {
+                    return Err(Error::Unsupported("a prime derivative at a point"));
+                };
+                if derivative_table(function.text, &inputs[0]).is_none() {
+                    return Err(Error::Unsupported("a function without its definition"));
+                }
+                let call = Expression::FunctionCall(FunctionCall {
+                    identifier: function.clone(),
+                    inputs: inputs.clone(),
+                });
+                (0..*order).try_fold(call, |body, _| body.differentiate(&variable.identifier))
+            }
+            Derivative::Prime { inputs: None, .. } => {
+                Err(Error::Unsupported("a prime derivative without inputs"))
+            }
+        }
+    }
+}
+
+/// The same variable, counting `θ` and `theta` as the same
+fn same(left: &Identifier<'_>, right: &Identifier<'_>) -> bool {
+    left.text == right.text
+        || left
+            .symbol()
+            .zip(right.symbol())
+            .is_some_and(|(left, right)| left.name == right.name)
+}
+
+/// The derivative, before simplifying
+fn derivative<'src>(
+    expression: &Expression<'src>,
+    variable: &Identifier<'_>,
+) -> Result<Expression<'src>, Error> {
+    let (derivative, _) = rule(expression, variable, &|part| derivative(part, variable))?;
+    Ok(derivative)
+}
+
+/// Applies one rule to the outside of `expression`, with `d` giving the derivatives of its parts.
+/// Also returns the name of the rule, for [steps](Expression::steps).
+pub(crate) fn rule<'src>(
+    expression: &Expression<'src>,
+    variable: &Identifier<'_>,
+    d: &dyn Fn(&Expression<'src>) -> Result<Expression<'src>, Error>,
+) -> Result<(Expression<'src>, &'static str), Error> {
+    if !expression.mentions(variable) && !matches!(expression, Expression::Relation(_)) {
+        return Ok((number(0), "constant rule"));
+    }
+    let is_variable = |expression: &Expression<'_>| matches!(expression, Expression::Variable(other) if same(&other.identifier, variable));
+    Ok(match expression {
+        Expression::Variable(_) => (number(1), "derivative of the variable"),
+        Expression::Group(group) => return rule(&group.inner, variable, d),
+        Expression::Unary(unary) => (
+            Expression::Unary(Unary {
+                operator: unary.operator,
+                operand: Box::new(d(&unary.operand)?),
+            }),
+            "constant multiple rule",
+        ),
+        Expression::Binary(Binary {
+            operator,
+            left,
+            right,
+        }) => match operator {
+            BinaryOperator::Add | BinaryOperator::PlusMinus => {
+                (binary(*operator, d(left)?, d(right)?), "sum rule")
+            }
+            BinaryOperator::Subtract => (binary(*operator, d(left)?, d(right)?), "difference rule"),
+            BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply
+                if !left.mentions(variable) =>
+            {
+                (
+                    multiply(left.as_ref().clone(), d(right)?),
+                    "constant multiple rule",
+                )
+            }
+            BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply
+                if !right.mentions(variable) =>
+            {
+                (
+                    multiply(d(left)?, right.as_ref().clone()),
+                    "constant multiple rule",
+                )
+            }
+            BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => (
+                add(
+                    multiply(d(left)?, right.as_ref().clone()),
+                    multiply(left.as_ref().clone(), d(right)?),
+                ),
+                "product rule",
+            ),
+            BinaryOperator::Divide if !right.mentions(variable) => (
+                binary(BinaryOperator::Divide, d(left)?, right.as_ref().clone()),
+                "constant multiple rule",
+            ),
+            BinaryOperator::Divide => (
+                binary(
+                    BinaryOperator::Divide,
+                    subtract(
+                        multiply(d(left)?, right.as_ref().clone()),
+                        multiply(left.as_ref().clone(), d(right)?),
+                    ),
+                    power(right.as_ref().clone(), number(2)),
+                ),
+                "quotient rule",
+            ),
+            BinaryOperator::Power if !right.mentions(variable) => {
+                let power_rule = multiply(
+                    right.as_ref().clone(),
+                    power(
+                        left.as_ref().clone(),
+                        subtract(right.as_ref().clone(), number(1)),
+                    ),
+                );
+                match is_variable(left) {
+                    true => (power_rule, "power rule"),
+                    false => (multiply(d(left)?, power_rule), "chain rule"),
+                }
+            }
+            BinaryOperator::Power if !left.mentions(variable) => (
+                multiply(
+                    multiply(expression.clone(), call("ln", left.as_ref().clone())),
+                    d(right)?,
+                ),
+                "exponential rule",
+            ),
+            // a^b (b' ln(a) + b a'/a)
+            BinaryOperator::Power => (
+                multiply(
+                    expression.clone(),
+                    add(
+                        multiply(d(right)?, call("ln", left.as_ref().clone())),
+                        binary(
+                            BinaryOperator::Divide,
+                            multiply(right.as_ref().clone(), d(left)?),
+                            left.as_ref().clone(),
+                        ),
+                    ),
+                ),
+                "logarithmic differentiation",
+            ),
+            BinaryOperator::Union | BinaryOperator::Intersection | BinaryOperator::Difference => {
+                return Err(Error::Unsupported("a set"))
+            }
+        },
+        Expression::Relation(relation) => (
+            Expression::Relation(Relation {
+                first: Box::new(d(&relation.first)?),
+                rest: relation
+                    .rest
+                    .iter()
+                    .map(|(operator, right)| Ok((*operator, d(right)?)))
+                    .collect::<Result<_, Error>>()?,
+            }),
+            "differentiate both sides",
+        ),
+        Expression::FunctionCall(function_call) => {
+            let [input] = function_call.inputs.as_slice() else {
+                return Err(Error::SeveralInputs(
+                    function_call.identifier.text.to_string(),
+                ));
+            };
+            let (outer, rule) = match derivative_table(function_call.identifier.text, input) {
+                Some(outer) => (outer, "standard derivative"),
+                // Some function we don't know, so f'(u)
+                None => (
+                    Expression::Derivative(Derivative::Prime {
+                        function: function_call.identifier.clone(),
+                        order: 1,
+                        inputs: Some(vec![input.clone()]),
+                    }),
+                    "prime notation",
+                ),
+            };
+            match is_variable(input) {
+                true => (outer, rule),
+                false => (multiply(d(input)?, outer), "chain rule"),
+            }
+        }
+        Expression::Derivative(Derivative::Prime {
+            function,
+            order,
+            inputs: Some(inputs),
+        }) if inputs.len() == 1 => {
+            let prime = Expression::Derivative(Derivative::Prime {
+                function: function.clone(),
+                order: order + 1,
+                inputs: Some(inputs.clone()),
+            });
+            match is_variable(&inputs[0]) {
+                true => (prime, "prime notation"),
+                false => (multiply(d(&inputs[0])?, prime), "chain rule"),
+            }
+        }
+        Expression::Derivative(derivative) => {
+            (d(&derivative.compute()?)?, "work out the inner derivative")
+        }
+        Expression::Delimited(Delimited {
+            kind: DelimiterKind::Abs,
+            inner,
+        }) => (
+            multiply(
+                binary(
+                    BinaryOperator::Divide,
+                    inner.as_ref().clone(),
+                    expression.clone(),
+                ),
+                d(inner)?,
+            ),
+            "derivative of the absolute value",
+        ),
+        Expression::Delimited(_) => {
+            return Err(Error::Unsupported("a norm, floor or ceiling"));
+        }
+        Expression::BigOperator(big_operator)
+            if big_operator.kind == BigOperatorKind::Sum
+                && !big_operator.from.mentions(variable)
+                && !big_operator.to.mentions(variable) =>
+        {
+            (
+                Expression::BigOperator(BigOperator {
+                    body: Box::new(d(&big_operator.body)?),
+                    ..big_operator.clone()
+                }),
+                "sum rule",
+            )
+        }
+        Expression::Integral(Integral {
+            integrand,
+            variable: integral_variable,
+            bounds: None,
+        }) if same(integral_variable, variable) => (
+            integrand.as_ref().clone(),
+            "fundamental theorem of calculus",
+        ),
+        Expression::Integral(integral)
+            if !same(&integral.variable, variable)
+                && integral.bounds.iter().all(|(lower, upper)| {
+                    !lower.mentions(variable) && !upper.mentions(variable)
+                }) =>
+        {
+            (
+                Expression::Integral(Integral {
+                    integrand: Box::new(d(&integral.integrand)?),
+                    ..integral.clone()
+                }),
+                "differentiate under the integral",
+            )
+        }
+        Expression::Piecewise(piecewise) => (
+            Expression::Piecewise(Piecewise {
+                arms: piecewise
+                    .arms
+                    .iter()
+                    .map(|arm| {
+                        Ok(Arm {
+                            value: d(&arm.value)?,
+                            condition: arm.condition.clone(),
+                        })
+                    })
+                    .collect::<Result<_, Error>>()?,
+                otherwise: match &piecewise.otherwise {
+                    Some(otherwise) => Some(Box::new(d(otherwise)?)),
+                    None => None,
+                },
+            }),
+            "differentiate each piece",
+        ),
+        Expression::Vector(vector) => (
+            Expression::Vector(Vector {
+                elements: vector.elements.iter().map(d).collect::<Result<_, _>>()?,
+            }),
+            "differentiate each element",
+        ),
+        Expression::List(list) => (
+            Expression::List(List {
+                elements: list.elements.iter().map(d).collect::<Result<_, _>>()?,
+            }),
+            "differentiate each element",
+        ),
+        Expression::Matrix(matrix) => (
+            Expression::Matrix(Matrix {
+                rows: matrix
+                    .rows
+                    .iter()
+                    .map(|row| row.iter().map(d).collect())
+                    .collect::<Result<_, _>>()?,
+            }),
+            "differentiate each element",
+        ),
+        Expression::Subscript(_) => return Err(Error::Unsupported("a subscript")),
+        Expression::BigOperator(big_operator) if big_operator.kind != BigOperatorKind::Sum => {
+            return Err(Error::Unsupported("a product"))
+        }
+        Expression::BigOperator(_) => {
+            return Err(Error::Unsupported("a sum with the variable in its bounds"))
+        }
+        Expression::Integral(_) => {
+            return Err(Error::Unsupported(
+                "an integral with the variable in its bounds",
+            ))
+        }
+        Expression::Limit(_) => return Err(Error::Unsupported("a limit")),
+        Expression::Set(_)
+        | Expression::SetBuilder(_)
+        | Expression::Interval(_)
+        | Expression::NumberSet(_) => return Err(Error::Unsupported("a set")),
+        Expression::Literal(_) | Expression::Number(_) => (number(0), "constant rule"),
+        Expression::Quantity(_) => return Err(Error::Unsupported("a quantity with a unit")),
+    })
+}
+
+/// Derivative of the function `name` at `u`, for functions everyone knows
+fn derivative_table<'src>(name: &str, u: &Expression<'src>) -> Option<Expression<'src>> {
+    let u = || u.clone();
+    let one_over = |expression| binary(BinaryOperator::Divide, number(1), expression);
+    let negate = |expression| {
+        Expression::Unary(Unary {
+            operator: UnaryOperator::Negate,
+            operand: Box::new(expression),
+        })
+    };
+    Some(match name {
+        "sin" => call("cos", u()),
+        "cos" => negate(call("sin", u())),
+        "tan" => power(call("sec", u()), number(2)),
+        "csc" => negate(multiply(call("csc", u()), call("cot", u()))),
+        "sec" => multiply(call("sec", u()), call("tan", u())),
+        "cot" => negate(power(call("csc", u()), number(2))),
+        "arcsin" => one_over(call("sqrt", subtract(number(1), power(u(), number(2))))),
+        "arccos" => negate(one_over(call(
+            "sqrt",
+            subtract(number(1), power(u(), number(2))),
+        ))),
+        "arctan" => one_over(add(number(1), power(u(), number(2)))),
+        "sinh" => call("cosh", u()),
+        "cosh" => call("sinh", u()),
+        "tanh" => subtract(number(1), power(call("tanh", u()), number(2))),
+        "ln" => one_over(u()),
+        "log" => one_over(multiply(u(), call("ln", number(10)))),
+        "lg" => one_over(multiply(u(), call("ln", number(2)))),
+        "exp" => call("exp", u()),
+        "sqrt" => one_over(multiply(number(2), call("sqrt", u()))),
+        _ => return None,
+    })
+}
+
+pub(crate) fn number<'src>(number: i64) -> Expression<'src> {
+    Expression::Number(Number::from(number))
+}
+
+pub(crate) fn call<'src>(name: &'static str, input: Expression<'src>) -> Expression<'src> {
+    Expression::FunctionCall(FunctionCall {
+        identifier: Identifier { text: name },
+        inputs: vec![input],
+    })
+}
+
+pub(crate) fn binary<'src>(
+    operator: BinaryOperator,
+    left: Expression<'src>,
+    right: Expression<'src>,
+) -> Expression<'src> {
+    Expression::Binary(Binary {
+        operator,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+// These leave out zeros and ones, so the steps along the way don't read `cos(x)·1`
+
+pub(crate) fn add<'src>(left: Expression<'src>, right: Expression<'src>) -> Expression<'src> {
+    match (is(&left, 0), is(&right, 0)) {
+        (true, _) => right,
+        (_, true) => left,
+        _ => binary(BinaryOperator::Add, left, right),
+    }
+}
+
+pub(crate) fn subtract<'src>(left: Expression<'src>, right: Expression<'src>) -> Expression<'src> {
+    match is(&right, 0) {
+        true => left,
+        false => binary(BinaryOperator::Subtract, left, right),
+    }
+}
+
+pub(crate) fn multiply<'src>(left: Expression<'src>, right: Expression<'src>) -> Expression<'src> {
+    if is(&left, 0) || is(&right, 0) {
+        return number(0);
+    }
+    match (is(&left, 1), is(&right, 1)) {
+        (true, _) => right,
+        (_, true) => left,
+        // Numbers side by side would read as one number
+        _ if starts_with_number(&right) => binary(BinaryOperator::Multiply, left, right),
+        _ => binary(BinaryOperator::ImplicitMultiply, left, right),
+    }
+}
+
+fn is(expression: &Expression<'_>, whole: i64) -> bool {
+    matches!(expression, Expression::Number(number) if *number == Number::from(whole))
+}
+
+pub(crate) fn power<'src>(base: Expression<'src>, exponent: Expression<'src>) -> Expression<'src> {
+    binary(BinaryOperator::Power, base, exponent)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ast::*, unicode::Unicode};
+
+    fn differentiate(str: &str, variable: &str) -> String {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let [Statement::Expression(expression)] = ast.statements.as_slice() else {
+            panic!("{ast:?}");
+        };
+        let derivative = expression.differentiate(&Identifier { text: variable });
+        Unicode(&derivative.unwrap()).to_string()
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(differentiate("x^3 + 2x + 1", "x"), "3x² + 2");
+        assert_eq!(differentiate("x*y", "x"), "y");
+        assert_eq!(differentiate("x*y", "z"), "0");
+        assert_eq!(differentiate("x*sin(x)", "x"), "sin(x) + x·cos(x)");
+        assert_eq!(differentiate("1/x", "x"), "−1/x²");
+        assert_eq!(differentiate("2^x", "x"), "2^x·ln(2)");
+        assert_eq!(differentiate("sqrt(x)", "x"), "1/(2sqrt(x))");
+    }
+
+    #[test]
+    fn chain_rule() {
+        assert_eq!(differentiate("sin(x^2)", "x"), "2x·cos(x²)");
+        assert_eq!(differentiate("ln(cos(x))", "x"), "−sin(x)/cos(x)");
+        assert_eq!(differentiate("f(2x)", "x"), "2f′(2x)");
+        assert_eq!(differentiate("(x + 1)^2", "x"), "2(x + 1)");
+    }
+
+    #[test]
+    fn derivatives() {
+        assert_eq!(differentiate("diff(x^2, x)", "x"), "2");
+        assert_eq!(differentiate("d/dx(x^2*y^2)", "y"), "4x·y");
+        assert_eq!(differentiate("int(t^2, t)", "t"), "t²");
+        assert!(matches!(
+            Derivative::Prime {
+                function: Identifier { text: "sin" },
+                order: 2,
+                inputs: Some(vec![Expression::Variable(Variable {
+                    identifier: Identifier { text: "t" }
+                })]),
+            }
+            .compute(),
+            Ok(Expression::Unary(_))
+        ));
+    }
+}
@@ -0,0 +1,1020 @@
+//! Numeric evaluation of expressions, for checking an answer before handing it in.
+//! Bind variables and define functions in an [`Environment`], then
+//! [`evaluate`](Environment::evaluate) expressions in it.
+
+use std::{collections::HashMap, f64::consts, fmt};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use crate::{ast::*, diagnostic::Diagnostic, symbol::Symbol};
+
+mod complex;
+mod number;
+
+pub use complex::Complex;
+pub use number::Number;
+
+/// What an expression evaluates to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(Number),
+    /// A number with an imaginary part that isn't zero, like `3 + 4i`
+    Complex(Complex),
+    /// What a relation like `x^2 = 4` evaluates to
+    Bool(bool),
+}
+
+impl Value {
+    pub fn number<'src>(self) -> Result<Number, Error<'src>> {
+        match self {
+            Value::Number(number) => Ok(number),
+            Value::Complex(_) => Err(Error::Type {
+                expected: "a real number",
+                found: "a complex number",
+            }),
+            Value::Bool(_) => Err(Error::Type {
+                expected: "a number",
+                found: "true or false",
+            }),
+        }
+    }
+
+    /// The number, real or not
+    pub fn complex<'src>(self) -> Result<Complex, Error<'src>> {
+        match self {
+            Value::Number(number) => Ok(number.into()),
+            Value::Complex(complex) => Ok(complex),
+            Value::Bool(_) => Err(Error::Type {
+                expected: "a number",
+                found: "true or false",
+            }),
+        }
+    }
+
+    pub fn bool<'src>(self) -> Result<bool, Error<'src>> {
+        match self {
+            Value::Bool(bool) => Ok(bool),
+            Value::Number(_) | Value::Complex(_) => Err(Error::Type {
+                expected: "true or false",
+                found: "a number",
+            }),
+        }
+    }
+}
+
+impl From<Number> for Value {
+    fn from(number: Number) -> Value {
+        Value::Number(number)
+    }
+}
+
+/// A [`Value::Number`] when the imaginary part is exactly zero
+impl From<Complex> for Value {
+    fn from(complex: Complex) -> Value {
+        if complex.im.is_exact() && complex.im.is_zero() {
+            Value::Number(complex.re)
+        } else {
+            Value::Complex(complex)
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(number: f64) -> Value {
+        Value::Number(number.into())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(bool: bool) -> Value {
+        Value::Bool(bool)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{number}"),
+            Value::Complex(complex) => write!(f, "{complex}"),
+            Value::Bool(bool) => write!(f, "{bool}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum Error<'src> {
+    #[error("`{0}` is not defined")]
+    UnboundVariable(String),
+    #[error("`{0}` is not a known function")]
+    UnknownFunction(String),
+    #[error("`{name}` takes {expected}, not {found}")]
+    WrongArity {
+        name: String,
+        expected: Arity,
+        found: usize,
+    },
+    /// Outside where the operation is defined, like `ln(-1)` or `1/0`
+    #[error("{0}")]
+    Domain(String),
+    #[error("expected {expected}, found {found}")]
+    Type {
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("`{0}` calls itself too many times")]
+    TooDeep(String),
+    #[error("{0} can't be evaluated to a number")]
+    Unsupported(&'static str),
+    /// A number too big for a float
+    #[error("{0}")]
+    Literal(Diagnostic<'src>),
+}
+
+impl<'src> From<Diagnostic<'src>> for Error<'src> {
+    fn from(diagnostic: Diagnostic<'src>) -> Error<'src> {
+        Error::Literal(diagnostic)
+    }
+}
+
+/// How many inputs a function takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+    /// Like `int`, which takes bounds or not
+    Either(usize, usize),
+}
+
+impl Arity {
+    pub fn allows(&self, count: usize) -> bool {
+        match *self {
+            Arity::Exactly(arity) => count == arity,
+            Arity::AtLeast(arity) => count >= arity,
+            Arity::Either(one, other) => count == one || count == other,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, count) = match *self {
+            Arity::Exactly(count) => (String::new(), count),
+            Arity::AtLeast(count) => ("at least ".to_string(), count),
+            Arity::Either(one, other) => (format!("{one} or "), other),
+        };
+        let plural = if count == 1 { "" } else { "s" };
+        write!(f, "{prefix}{count} input{plural}")
+    }
+}
+
+/// Functions every environment has
+pub(crate) const BUILTINS: &[(&str, Arity)] = &[
+    ("sin", Arity::Exactly(1)),
+    ("cos", Arity::Exactly(1)),
+    ("tan", Arity::Exactly(1)),
+    ("csc", Arity::Exactly(1)),
+    ("sec", Arity::Exactly(1)),
+    ("cot", Arity::Exactly(1)),
+    ("arcsin", Arity::Exactly(1)),
+    ("arccos", Arity::Exactly(1)),
+    ("arctan", Arity::Exactly(1)),
+    ("sinh", Arity::Exactly(1)),
+    ("cosh", Arity::Exactly(1)),
+    ("tanh", Arity::Exactly(1)),
+    ("ln", Arity::Exactly(1)),
+    ("log", Arity::Exactly(1)),
+    ("lg", Arity::Exactly(1)),
+    ("exp", Arity::Exactly(1)),
+    ("sqrt", Arity::Exactly(1)),
+    ("abs", Arity::Exactly(1)),
+    ("floor", Arity::Exactly(1)),
+    ("ceil", Arity::Exactly(1)),
+    ("round", Arity::Exactly(1)),
+    ("sign", Arity::Exactly(1)),
+    ("max", Arity::AtLeast(1)),
+    ("min", Arity::AtLeast(1)),
+    ("mod", Arity::Exactly(2)),
+    ("gcd", Arity::Exactly(2)),
+    ("lcm", Arity::Exactly(2)),
+    ("re", Arity::Exactly(1)),
+    ("im", Arity::Exactly(1)),
+    ("conj", Arity::Exactly(1)),
+    ("arg", Arity::Exactly(1)),
+];
+
+/// How many inputs the builtin function takes, or [`None`] when there's no builtin by that name
+pub(crate) fn builtin_arity(name: &str) -> Option<Arity> {
+    let (_, arity) = BUILTINS.iter().find(|(builtin, _)| *builtin == name)?;
+    Some(*arity)
+}
+
+/// Builtins that take complex numbers. The rest only take real ones.
+const COMPLEX_BUILTINS: &[&str] = &["re", "im", "conj", "arg", "abs"];
+
+/// User function calls nested deeper than this are assumed to never finish
+const MAX_DEPTH: usize = 256;
+
+/// Most terms a `sum` or `prod` may have
+const MAX_TERMS: f64 = 1_000_000.0;
+
+/// Intervals Simpson's rule splits an integral into
+const INTEGRAL_STEPS: usize = 1000;
+
+/// Variable bindings and function definitions to evaluate in. A function call or the variable of
+/// a sum gets a scope of its own inside the one it's in, so nothing is copied.
+#[derive(Debug, Clone)]
+pub struct Environment<'p, 'src> {
+    variables: HashMap<String, Value>,
+    functions: HashMap<String, FunctionDefinition<'src>>,
+    /// The scope this one is inside. Names not bound here are looked up there.
+    parent: Option<&'p Environment<'p, 'src>>,
+    /// User function calls this is nested in
+    depth: usize,
+    /// Name of the square root of -1, when there is one. Only kept by the outermost scope.
+    imaginary_unit: Option<String>,
+}
+
+impl Default for Environment<'_, '_> {
+    fn default() -> Self {
+        Environment {
+            variables: HashMap::new(),
+            functions: HashMap::new(),
+            parent: None,
+            depth: 0,
+            imaginary_unit: Some("i".to_string()),
+        }
+    }
+}
+
+impl<'p, 'src> Environment<'p, 'src> {
+    pub fn new() -> Environment<'p, 'src> {
+        Environment::default()
+    }
+
+    /// An empty scope inside this one
+    pub(crate) fn scope(&self) -> Environment<'_, 'src> {
+        Environment {
+            variables: HashMap::new(),
+            functions: HashMap::new(),
+            parent: Some(self),
+            depth: self.depth,
+            imaginary_unit: None,
+        }
+    }
+
+    /// The outermost scope, where the definitions are
+    fn root(&self) -> &Environment<'_, 'src> {
+        match self.parent {
+            Some(parent) => parent.root(),
+            None => self,
+        }
+    }
+
+    /// Names the imaginary unit, like `j` for electrical engineering, or takes it away with
+    /// [`None`] so `i` is a variable like any other. It's `i` to begin with. A variable bound
+    /// with the same name hides it, so `i` still works as the index of a sum.
+    pub fn set_imaginary_unit(&mut self, name: Option<&str>) {
+        self.imaginary_unit = name.map(canonical_name);
+    }
+
+    /// Sets variable `name`. Greek letters can be given by name or character.
+    pub fn bind(&mut self, name: &str, value: impl Into<Value>) {
+        self.variables.insert(canonical_name(name), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.lookup(&canonical_name(name)).cloned()
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Value> {
+        match self.variables.get(name) {
+            Some(value) => Some(value),
+            None => self.parent?.lookup(name),
+        }
+    }
+
+    pub fn define(&mut self, function: FunctionDefinition<'src>) {
+        self.functions
+            .insert(canonical_name(function.identifier.text), function);
+    }
+
+    /// Binds definitions and defines functions. Anything else is evaluated and its value given
+    /// back.
+    pub fn run(&mut self, statement: &Statement<'src>) -> Result<Option<Value>, Error<'src>> {
+        match statement {
+            Statement::Definition(definition) => {
+                let value = self.evaluate(&definition.value)?;
+                let name = match definition.name() {
+                    Expression::Subscript(subscript) => subscript_name(&subscript)
+                        .ok_or(Error::Unsupported("defining this subscript"))?,
+                    _ => definition.identifier.text.to_string(),
+                };
+                self.bind(&name, value);
+                Ok(None)
+            }
+            Statement::FunctionDefinition(function_definition) => {
+                self.define(function_definition.clone());
+                Ok(None)
+            }
+            Statement::Equation(relation) => self.relation(relation).map(Some),
+            Statement::Expression(expression) => self.evaluate(expression).map(Some),
+            Statement::Derivation(_) => Err(Error::Unsupported("a derivation")),
+        }
+    }
+
+    pub fn evaluate(&self, expression: &Expression<'src>) -> Result<Value, Error<'src>> {
+        let number = |number: Number| Ok(Value::Number(number));
+        match expression {
+            Expression::Literal(Literal::Whole(whole)) => {
+                number(BigInt::from(whole.value.clone()).into())
+            }
+            Expression::Literal(literal) => number(literal.to_f64()?.into()),
+            Expression::Number(value) => number(value.clone()),
+            Expression::Quantity(quantity) => number(self.quantity(quantity)?),
+            Expression::Variable(variable) => self.variable(&variable.identifier),
+            Expression::FunctionCall(function_call) => {
+                let inputs = function_call
+                    .inputs
+                    .iter()
+                    .map(|input| self.evaluate(input))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(function_call.identifier.text, &inputs)
+            }
+            Expression::Subscript(subscript) => {
+                let name = subscript_name(subscript).ok_or(Error::Unsupported("this subscript"))?;
+                self.get(&name).ok_or(Error::UnboundVariable(name))
+            }
+            Expression::Unary(unary) => self.unary(unary),
+            Expression::Binary(binary) => self.binary(binary),
+            Expression::Relation(relation) => self.relation(relation),
+            Expression::BigOperator(big_operator) => number(self.big_operator(big_operator)?),
+            Expression::Integral(integral) => number(self.integral(integral)?.into()),
+            Expression::Limit(limit) => number(self.limit(limit)?.into()),
+            Expression::Derivative(derivative) => number(self.derivative(derivative)?.into()),
+            Expression::Piecewise(piecewise) => {
+                for arm in &piecewise.arms {
+                    if self.evaluate(&arm.condition)?.bool()? {
+                        return self.evaluate(&arm.value);
+                    }
+                }
+                match &piecewise.otherwise {
+                    Some(otherwise) => self.evaluate(otherwise),
+                    None => Err(Error::Domain("no piecewise condition holds".to_string())),
+                }
+            }
+            Expression::Delimited(delimited) => number(self.delimited(delimited)?),
+            Expression::Group(group) => self.evaluate(&group.inner),
+            Expression::List(_) => Err(Error::Unsupported("a list")),
+            Expression::Vector(_) | Expression::Matrix(_) => Err(Error::Unsupported("a matrix")),
+            Expression::Set(_)
+            | Expression::SetBuilder(_)
+            | Expression::Interval(_)
+            | Expression::NumberSet(_) => Err(Error::Unsupported("a set")),
+        }
+    }
+
+    /// In SI base units, so `1 km` and `1000 m` are equal
+    fn quantity(&self, quantity: &Quantity<'src>) -> Result<Number, Error<'src>> {
+        let value = self.evaluate(&quantity.value)?.number()?;
+        Ok(&value * &quantity.unit.scale())
+    }
+
+    fn variable(&self, identifier: &Identifier<'src>) -> Result<Value, Error<'src>> {
+        if let Some(value) = self.get(identifier.text) {
+            return Ok(value);
+        }
+        let name = canonical_name(identifier.text);
+        if self.root().imaginary_unit.as_ref() == Some(&name) {
+            return Ok(Value::Complex(Complex::i()));
+        }
+        let constant = match name.as_str() {
+            "pi" => consts::PI,
+            "e" => consts::E,
+            "infinity" => f64::INFINITY,
+            _ => return Err(Error::UnboundVariable(identifier.text.to_string())),
+        };
+        Ok(Value::from(constant))
+    }
+
+    /// Calls a user defined function, or a builtin when there isn't one by that name
+    fn call(&self, name: &str, inputs: &[Value]) -> Result<Value, Error<'src>> {
+        // Functions are only defined in the outermost scope, and their bodies only see that
+        let root = self.root();
+        let Some(function) = root.functions.get(&canonical_name(name)) else {
+            return call_builtin(name, inputs);
+        };
+        if function.parameters.len() != inputs.len() {
+            return Err(Error::WrongArity {
+                name: name.to_string(),
+                expected: Arity::Exactly(function.parameters.len()),
+                found: inputs.len(),
+            });
+        }
+        if self.depth >= MAX_DEPTH {
+            return Err(Error::TooDeep(name.to_string()));
+        }
+        let mut scope = root.scope();
+        scope.depth = self.depth + 1;
+        for (parameter, input) in function.parameters.iter().zip(inputs) {
+            scope.bind(parameter.text, input.clone());
+        }
+        scope.evaluate(&function.body)
+    }
+
+    /// `body` as a function of `variable`, with everything else as it is now
+    fn with<'a>(
+        &'a self,
+        variable: &'a Identifier<'src>,
+        body: &'a Expression<'src>,
+    ) -> impl Fn(Number) -> Result<Number, Error<'src>> + 'a {
+        move |value| {
+            let mut scope = self.scope();
+            scope.bind(variable.text, value);
+            scope.evaluate(body)?.number()
+        }
+    }
+
+    /// [`with`](Environment::with), in floats for the numeric methods
+    fn with_float<'a>(
+        &'a self,
+        variable: &'a Identifier<'src>,
+        body: &'a Expression<'src>,
+    ) -> impl Fn(f64) -> Result<f64, Error<'src>> + 'a {
+        let function = self.with(variable, body);
+        move |value| Ok(function(value.into())?.to_f64())
+    }
+
+    fn unary(&self, unary: &Unary<'src>) -> Result<Value, Error<'src>> {
+        let operand = self.evaluate(&unary.operand)?.complex()?;
+        match unary.operator {
+            UnaryOperator::Negate => Ok((-&operand).into()),
+            UnaryOperator::PlusMinus => Err(Error::Unsupported("`±`, which has two values,")),
+        }
+    }
+
+    /// `|z|` is the modulus of a complex number
+    fn delimited(&self, delimited: &Delimited<'src>) -> Result<Number, Error<'src>> {
+        let inner = self.evaluate(&delimited.inner)?;
+        Ok(match delimited.kind {
+            DelimiterKind::Abs | DelimiterKind::Norm => inner.complex()?.abs(),
+            DelimiterKind::Floor => inner.number()?.floor(),
+            DelimiterKind::Ceil => inner.number()?.ceil(),
+        })
+    }
+
+    fn binary(&self, binary: &Binary<'src>) -> Result<Value, Error<'src>> {
+        let left = self.evaluate(&binary.left)?;
+        let right = self.evaluate(&binary.right)?;
+        let (left, right) = match (left, right) {
+            (Value::Number(left), Value::Number(right)) => (left, right),
+            (left, right) => return complex_binary(binary.operator, left, right),
+        };
+        let value = match binary.operator {
+            BinaryOperator::Add => &left + &right,
+            BinaryOperator::Subtract => &left - &right,
+            BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => &left * &right,
+            BinaryOperator::Divide => left
+                .checked_div(&right)
+                .ok_or_else(|| Error::Domain("division by zero".to_string()))?,
+            BinaryOperator::Power => match left.pow(&right) {
+                Some(power) => power,
+                None if left.is_zero() => {
+                    return Err(Error::Domain("zero to a negative power".to_string()))
+                }
+                None => {
+                    return Err(Error::Domain(
+                        "negative number to a fractional power".to_string(),
+                    ))
+                }
+            },
+            BinaryOperator::PlusMinus => {
+                return Err(Error::Unsupported("`±`, which has two values,"))
+            }
+            BinaryOperator::Union | BinaryOperator::Intersection | BinaryOperator::Difference => {
+                return Err(Error::Unsupported("a set"))
+            }
+        };
+        Ok(Value::Number(value))
+    }
+
+    /// True when every neighbouring pair holds. Complex numbers can only be compared for
+    /// equality.
+    fn relation(&self, relation: &Relation<'src>) -> Result<Value, Error<'src>> {
+        let mut left = self.evaluate(&relation.first)?;
+        let mut holds = true;
+        for (operator, right) in &relation.rest {
+            let right = self.evaluate(right)?;
+            holds &= compare(*operator, left, right.clone())?;
+            left = right;
+        }
+        Ok(Value::Bool(holds))
+    }
+
+    fn big_operator(&self, big_operator: &BigOperator<'src>) -> Result<Number, Error<'src>> {
+        let from = self.evaluate(&big_operator.from)?.number()?.to_integer();
+        let to = self.evaluate(&big_operator.to)?.number()?.to_integer();
+        let (Some(from), Some(to)) = (from, to) else {
+            return Err(Error::Domain(
+                "sum and product bounds must be whole numbers".to_string(),
+            ));
+        };
+        if (&to - &from).to_f64().unwrap_or(f64::INFINITY) >= MAX_TERMS {
+            return Err(Error::Domain(format!(
+                "more than {MAX_TERMS} terms is too many to add up"
+            )));
+        }
+        let term = self.with(&big_operator.index, &big_operator.body);
+        let mut index = from;
+        let mut total = match big_operator.kind {
+            BigOperatorKind::Sum => Number::from(0),
+            BigOperatorKind::Product => Number::from(1),
+        };
+        while index <= to {
+            let value = term(index.clone().into())?;
+            total = match big_operator.kind {
+                BigOperatorKind::Sum => &total + &value,
+                BigOperatorKind::Product => &total * &value,
+            };
+            index += 1;
+        }
+        Ok(total)
+    }
+
+    /// Simpson's rule
+    fn integral(&self, integral: &Integral<'src>) -> Result<f64, Error<'src>> {
+        let Some((lower, upper)) = &integral.bounds else {
+            return Err(Error::Unsupported("an integral without bounds"));
+        };
+        let lower = self.evaluate(lower)?.number()?.to_f64();
+        let upper = self.evaluate(upper)?.number()?.to_f64();
+        if !lower.is_finite() || !upper.is_finite() {
+            return Err(Error::Unsupported("an integral to infinity"));
+        }
+        let integrand = self.with_float(&integral.variable, &integral.integrand);
+        let step = (upper - lower) / INTEGRAL_STEPS as f64;
+        let mut total = integrand(lower)? + integrand(upper)?;
+        for i in 1..INTEGRAL_STEPS {
+            let weight = if i % 2 == 1 { 4.0 } else { 2.0 };
+            total += weight * integrand(lower + i as f64 * step)?;
+        }
+        Ok(total * step / 3.0)
+    }
+
+    /// Looks at both sides of the point, or far out for a limit to infinity
+    fn limit(&self, limit: &Limit<'src>) -> Result<f64, Error<'src>> {
+        let approaching = self.evaluate(&limit.approaching)?.number()?.to_f64();
+        let body = self.with_float(&limit.variable, &limit.body);
+        let (near, nearer) = if approaching.is_infinite() {
+            (
+                body(approaching.signum() * 1e7)?,
+                body(approaching.signum() * 1e8)?,
+            )
+        } else {
+            let step = 1e-7 * approaching.abs().max(1.0);
+            (body(approaching - step)?, body(approaching + step)?)
+        };
+        if Number::from(near).close(&nearer.into(), 1e-4) {
+            Ok((near + nearer) / 2.0)
+        } else {
+            Err(Error::Domain("the limit doesn't exist".to_string()))
+        }
+    }
+
+    /// Finite differences at the current values of the variables
+    fn derivative(&self, derivative: &Derivative<'src>) -> Result<f64, Error<'src>> {
+        match derivative {
+            Derivative::Leibniz { variable, body } => self.partial(body, &[(variable, 1)]),
+            Derivative::Prime {
+                function,
+                order,
+                inputs,
+            } => {
+                let Some(inputs) = inputs else {
+                    return Err(Error::Unsupported("a derivative without a point"));
+                };
+                let [input] = inputs.as_slice() else {
+                    return Err(Error::WrongArity {
+                        name: format!("{}{}", function.text, "'".repeat(*order)),
+                        expected: Arity::Exactly(1),
+                        found: inputs.len(),
+                    });
+                };
+                let at = self.evaluate(input)?.number()?.to_f64();
+                let function = |x: f64| {
+                    let value = self.call(function.text, &[x.into()])?;
+                    Ok(value.number()?.to_f64())
+                };
+                differentiate(function, at, *order)
+            }
+            Derivative::Partial { body, .. } => self.partial(body, &derivative.grouped_variables()),
+        }
+    }
+
+    /// Differentiates by the first variable, of the derivative by the rest
+    fn partial(
+        &self,
+        body: &Expression<'src>,
+        variables: &[(&Identifier<'src>, usize)],
+    ) -> Result<f64, Error<'src>> {
+        let Some(((variable, order), rest)) = variables.split_first() else {
+            return Ok(self.evaluate(body)?.number()?.to_f64());
+        };
+        let at = self.variable(variable)?.number()?.to_f64();
+        let inner = |x| {
+            let mut scope = self.scope();
+            scope.bind(variable.text, x);
+            scope.partial(body, rest)
+        };
+        differentiate(inner, at, *order)
+    }
+}
+
+/// Name a variable is stored under. Symbols go by their name, so `θ` and `theta` are the same.
+pub(crate) fn canonical_name(name: &str) -> String {
+    match Symbol::recognize(name) {
+        Some(symbol) => symbol.name.to_string(),
+        None => name.to_string(),
+    }
+}
+
+/// `x_1` for a subscript of a variable by a variable or number
+fn subscript_name(subscript: &Subscript<'_>) -> Option<String> {
+    let Expression::Variable(base) = subscript.base.as_ref() else {
+        return None;
+    };
+    let index = match subscript.index.as_ref() {
+        Expression::Variable(index) => canonical_name(index.identifier.text),
+        Expression::Literal(index) => index.text().to_string(),
+        _ => return None,
+    };
+    Some(format!("{}_{index}", canonical_name(base.identifier.text)))
+}
+
+/// Central difference of `order`, with a step that balances truncation against rounding
+fn differentiate<'src>(
+    f: impl Fn(f64) -> Result<f64, Error<'src>>,
+    x: f64,
+    order: usize,
+) -> Result<f64, Error<'src>> {
+    let step = f64::EPSILON.powf(1.0 / (order as f64 + 2.0)) * x.abs().max(1.0);
+    let mut total = 0.0;
+    let mut binomial = 1.0;
+    for k in 0..=order {
+        let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+        total += sign * binomial * f(x + (order as f64 / 2.0 - k as f64) * step)?;
+        binomial = binomial * (order - k) as f64 / (k + 1) as f64;
+    }
+    Ok(total / step.powi(order as i32))
+}
+
+/// Whether `left operator right` holds
+fn compare<'src>(
+    operator: RelationOperator,
+    left: Value,
+    right: Value,
+) -> Result<bool, Error<'src>> {
+    if let (Value::Number(left), Value::Number(right)) = (&left, &right) {
+        return Ok(match operator {
+            RelationOperator::Equal => left.close(right, 1e-9),
+            RelationOperator::NotEqual => !left.close(right, 1e-9),
+            RelationOperator::Less => left < right,
+            RelationOperator::LessEqual => left <= right || left.close(right, 1e-9),
+            RelationOperator::Greater => left > right,
+            RelationOperator::GreaterEqual => left >= right || left.close(right, 1e-9),
+            RelationOperator::Approximately => left.close(right, 1e-2),
+            RelationOperator::In | RelationOperator::Subset => {
+                return Err(Error::Unsupported("a set"))
+            }
+        });
+    }
+    let (left, right) = (left.complex()?, right.complex()?);
+    match operator {
+        RelationOperator::Equal => Ok(left.close(&right, 1e-9)),
+        RelationOperator::NotEqual => Ok(!left.close(&right, 1e-9)),
+        RelationOperator::Approximately => Ok(left.close(&right, 1e-2)),
+        RelationOperator::In | RelationOperator::Subset => Err(Error::Unsupported("a set")),
+        _ => Err(Error::Type {
+            expected: "a real number",
+            found: "a complex number",
+        }),
+    }
+}
+
+/// [`Environment::binary`] when either side isn't real
+fn complex_binary<'src>(
+    operator: BinaryOperator,
+    left: Value,
+    right: Value,
+) -> Result<Value, Error<'src>> {
+    let (left, right) = (left.complex()?, right.complex()?);
+    let value = match operator {
+        BinaryOperator::Add => &left + &right,
+        BinaryOperator::Subtract => &left - &right,
+        BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => &left * &right,
+        BinaryOperator::Divide => match left.checked_div(&right) {
+            Some(quotient) => quotient,
+            None => return Err(Error::Domain("division by zero".to_string())),
+        },
+        BinaryOperator::Power => match left.pow(&right) {
+            Some(power) => power,
+            None => {
+                return Err(Error::Domain(
+                    "zero to a negative or complex power".to_string(),
+                ))
+            }
+        },
+        BinaryOperator::PlusMinus => return Err(Error::Unsupported("`±`, which has two values,")),
+        BinaryOperator::Union | BinaryOperator::Intersection | BinaryOperator::Difference => {
+            return Err(Error::Unsupported("a set"))
+        }
+    };
+    Ok(value.into())
+}
+
+/// Calls a builtin with real or complex inputs, depending on what it takes
+fn call_builtin<'src>(name: &str, inputs: &[Value]) -> Result<Value, Error<'src>> {
+    if COMPLEX_BUILTINS.contains(&name) {
+        let inputs = inputs.iter().cloned().map(Value::complex);
+        return complex_builtin(name, &inputs.collect::<Result<Vec<_>, _>>()?);
+    }
+    let inputs = inputs.iter().cloned().map(Value::number);
+    builtin(name, &inputs.collect::<Result<Vec<_>, _>>()?).map(Value::Number)
+}
+
+/// The builtins in [`COMPLEX_BUILTINS`]
+fn complex_builtin<'src>(name: &str, inputs: &[Complex]) -> Result<Value, Error<'src>> {
+    let [z] = inputs else {
+        return Err(Error::WrongArity {
+            name: name.to_string(),
+            expected: Arity::Exactly(1),
+            found: inputs.len(),
+        });
+    };
+    Ok(match name {
+        "re" => Value::Number(z.re.clone()),
+        "im" => Value::Number(z.im.clone()),
+        "conj" => z.conj().into(),
+        "arg" => Value::Number(z.arg()),
+        "abs" => Value::Number(z.abs()),
+        _ => unreachable!("only called for COMPLEX_BUILTINS"),
+    })
+}
+
+fn builtin<'src>(name: &str, inputs: &[Number]) -> Result<Number, Error<'src>> {
+    let Some(arity) = builtin_arity(name) else {
+        return Err(Error::UnknownFunction(name.to_string()));
+    };
+    if !arity.allows(inputs.len()) {
+        return Err(Error::WrongArity {
+            name: name.to_string(),
+            expected: arity,
+            found: inputs.len(),
+        });
+    }
+    let domain = |message: &str| Err(Error::Domain(message.to_string()));
+    // Exact when the answer is rational
+    match (name, inputs) {
+        ("sqrt", [x]) if x.is_negative() => return domain("square root of a negative number"),
+        ("sqrt", [x]) => {
+            let half = Number::Exact(BigRational::new(1.into(), 2.into()));
+            return Ok(x.pow(&half).expect("x isn't negative"));
+        }
+        ("abs", [x]) => return Ok(x.abs()),
+        ("floor", [x]) => return Ok(x.floor()),
+        ("ceil", [x]) => return Ok(x.ceil()),
+        ("round", [x]) => return Ok(x.round()),
+        ("sign", [x]) => return Ok(x.signum()),
+        ("max" | "min", [first, rest @ ..]) => {
+            let mut extreme = first;
+            for x in rest {
+                if (name == "max" && x > extreme) || (name == "min" && x < extreme) {
+                    extreme = x;
+                }
+            }
+            return Ok(extreme.clone());
+        }
+        ("mod", [_, y]) if y.is_zero() => return domain("mod by zero"),
+        ("mod", [x, y]) => return Ok(x - &(y * &(x / y).floor())),
+        ("gcd" | "lcm", [x, y]) => {
+            let (Some(x), Some(y)) = (x.to_integer(), y.to_integer()) else {
+                return domain(&format!("{name} of numbers that aren't whole"));
+            };
+            let gcd = gcd(x.clone(), y.clone());
+            return Ok(match name {
+                "gcd" => gcd.into(),
+                _ if gcd.is_zero() => Number::from(0),
+                _ => (x * y).abs().checked_div(&gcd).unwrap().into(),
+            });
+        }
+        _ => {}
+    }
+    let inputs: Vec<f64> = inputs.iter().map(Number::to_f64).collect();
+    let value = match (name, inputs.as_slice()) {
+        ("sin", [x]) => x.sin(),
+        ("cos", [x]) => x.cos(),
+        ("tan", [x]) => x.tan(),
+        ("csc", [x]) => 1.0 / x.sin(),
+        ("sec", [x]) => 1.0 / x.cos(),
+        ("cot", [x]) => 1.0 / x.tan(),
+        ("arcsin" | "arccos", [x]) if x.abs() > 1.0 => {
+            return domain(&format!("{name} is only defined from -1 to 1"))
+        }
+        ("arcsin", [x]) => x.asin(),
+        ("arccos", [x]) => x.acos(),
+        ("arctan", [x]) => x.atan(),
+        ("sinh", [x]) => x.sinh(),
+        ("cosh", [x]) => x.cosh(),
+        ("tanh", [x]) => x.tanh(),
+        ("ln" | "log" | "lg", [x]) if *x <= 0.0 => {
+            return domain("logarithm of a number that isn't positive")
+        }
+        ("ln", [x]) => x.ln(),
+        ("log", [x]) => x.log10(),
+        ("lg", [x]) => x.log2(),
+        ("exp", [x]) => x.exp(),
+        _ => unreachable!("arity is checked against BUILTINS"),
+    };
+    Ok(value.into())
+}
+
+fn gcd(x: BigInt, y: BigInt) -> BigInt {
+    let (mut x, mut y) = (x.abs(), y.abs());
+    while !y.is_zero() {
+        (x, y) = (y.clone(), x % y);
+    }
+    x
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Runs every statement, giving the value of the last one
+    fn eval(str: &str) -> Result<Value, String> {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let mut environment = Environment::new();
+        let mut value = None;
+        for statement in &ast.statements {
+            value = environment.run(statement).map_err(|e| e.to_string())?;
+        }
+        Ok(value.unwrap())
+    }
+
+    fn number(str: &str) -> f64 {
+        eval(str).unwrap().number().unwrap().to_f64()
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(number("1 + 2*3"), 7.0);
+        assert_eq!(number("2^3^2"), 512.0);
+        assert_eq!(number("-(1 - 4) / 2"), 1.5);
+        assert_eq!(number("let x = 3; 2x^2"), 18.0);
+        assert_eq!(number("let v_1 = 3; let v_max = 4; v_1*v_max"), 12.0);
+        assert_eq!(number("0x10 + 1e3"), 1016.0);
+        assert_eq!(number("|-3| + floor(2.5) + ceil(2.5)"), 8.0);
+    }
+
+    #[test]
+    fn functions() {
+        assert!((number("sin(pi/2)") - 1.0).abs() < 1e-12);
+        assert_eq!(number("sqrt(16) + ln(e)"), 5.0);
+        assert_eq!(number("max(1, 5, 3) + gcd(12, 18)"), 11.0);
+        assert_eq!(number("f(x) = x^2 + 1; f(3)"), 10.0);
+        assert_eq!(number("let area(w, h) = w*h/2\narea(3, 4)"), 6.0);
+        assert_eq!(number("let θ = pi; cos(theta)"), -1.0);
+        // A body sees the definitions, not the variables of the sum it's called in
+        assert_eq!(number("let y = 10; f(x) = x + y; sum(y, 1, 3, f(y))"), 36.0);
+        assert_eq!(number("f(x) = x^2; g(x) = f(x + 1); g(2) + f(2)"), 13.0);
+    }
+
+    #[test]
+    fn relations() {
+        assert_eq!(eval("let x = 2; x^2 = 4"), Ok(Value::Bool(true)));
+        assert_eq!(eval("0.1 + 0.2 = 0.3"), Ok(Value::Bool(true)));
+        assert_eq!(eval("1 < 2 < 2"), Ok(Value::Bool(false)));
+        assert_eq!(eval("pi ~= 3.14"), Ok(Value::Bool(true)));
+        assert_eq!(
+            number("let x = -2; piecewise(x^2 if x < 0, x otherwise)"),
+            4.0
+        );
+    }
+
+    #[test]
+    fn calculus() {
+        assert_eq!(number("sum(i, 1, 100, i)"), 5050.0);
+        assert_eq!(number("prod(k, 1, 5, k)"), 120.0);
+        assert!((number("int(x^2, x, 0, 3)") - 9.0).abs() < 1e-9);
+        assert!((number("lim(x, 0, sin(x)/x)") - 1.0).abs() < 1e-6);
+        assert!((number("let x = 2; d/dx(x^3)") - 12.0).abs() < 1e-4);
+        assert!((number("f(t) = t^3; f''(2)") - 12.0).abs() < 1e-3);
+        assert!((number("let x = 1; let y = 2; pd(x^2*y^3, x, y)") - 24.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn exact() {
+        let exact = |str| {
+            let Ok(Value::Number(number)) = eval(str) else {
+                panic!("{str}");
+            };
+            assert!(number.is_exact(), "{str} = {number}");
+            number
+        };
+        let half = exact("1/3 + 1/6");
+        assert_eq!(half, Number::Exact(BigRational::new(1.into(), 2.into())));
+        assert_eq!(crate::latex::Latex::new(&half).to_string(), r"\frac{1}{2}");
+        assert_eq!(crate::unicode::Unicode(&-&half).to_string(), "−1/2");
+        assert_eq!(
+            exact("2^100").to_string(),
+            "1267650600228229401496703205376"
+        );
+        assert_eq!(exact("sqrt(9/4) + (-8)^(1/3)").to_string(), "-1/2");
+        assert_eq!(exact("sum(k, 1, 4, 1/k)").to_string(), "25/12");
+        assert_eq!(exact("mod(-7, 3) + gcd(12, 18)").to_string(), "8");
+        assert_eq!(eval("1/3 * 3 = 1"), Ok(Value::Bool(true)));
+
+        assert!(!eval("sqrt(2)").unwrap().number().unwrap().is_exact());
+        assert!(!eval("0.5 + 1/2").unwrap().number().unwrap().is_exact());
+        assert_eq!(number("(-8)^(2/3 + 0)"), 4.0);
+        assert!((number("(-2)^(1/3)") + 2f64.cbrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn complex() {
+        let complex = |str| eval(str).unwrap().to_string();
+        assert_eq!(complex("(1 + 2i)(3 + 4i)"), "-5 + 10i");
+        assert_eq!(complex("i^2"), "-1");
+        assert_eq!(complex("(3 + 4i)/(1 - 2i)"), "-1 + 2i");
+        assert_eq!(complex("conj(2 - i) + im(3 + 4i)"), "6 + i");
+        assert_eq!(complex("re(3 + 4i) + abs(3 + 4i) + |3 - 4i|"), "13");
+        assert!((number("arg(-i)") + consts::FRAC_PI_2).abs() < 1e-12);
+        assert!(number("abs(i * exp(1000))").is_infinite());
+        assert_eq!(number("abs(i*1.0e200*1.0e200)"), f64::INFINITY);
+        assert!((number("abs(3.0e200 + 4.0e200i)") / 5.0e200 - 1.0).abs() < 1e-12);
+        assert!(eval("(1 + i)^(1/2)").unwrap().complex().unwrap().close(
+            &Complex::new(
+                Number::Float(1.0986841134678098),
+                Number::Float(0.45508986056222733)
+            ),
+            1e-12
+        ));
+        assert_eq!(eval("i^4 = 1"), Ok(Value::Bool(true)));
+        assert_eq!(number("sum(i, 1, 4, i)"), 10.0);
+
+        let value = eval("1/2 - 3i").unwrap();
+        assert_eq!(
+            crate::latex::Latex::new(&value).to_string(),
+            r"\frac{1}{2} - 3i"
+        );
+        assert_eq!(crate::unicode::Unicode(&value).to_string(), "1/2 − 3i");
+
+        assert_eq!(
+            eval("i < 1"),
+            Err("expected a real number, found a complex number".to_string())
+        );
+        assert_eq!(
+            eval("sin(i)"),
+            Err("expected a real number, found a complex number".to_string())
+        );
+        assert_eq!(eval("i/0"), Err("division by zero".to_string()));
+
+        let tokens = crate::lexer::parse("j^2 + i").unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let mut environment = Environment::new();
+        environment.set_imaginary_unit(Some("j"));
+        environment.bind("i", Number::from(3));
+        let value = environment.run(&ast.statements[0]).unwrap().unwrap();
+        assert_eq!(value, Value::Number(Number::from(2)));
+        environment.set_imaginary_unit(None);
+        assert!(environment.run(&ast.statements[0]).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(eval("y + 1"), Err("`y` is not defined".to_string()));
+        assert_eq!(
+            eval("sin(1, 2)"),
+            Err("`sin` takes 1 input, not 2".to_string())
+        );
+        assert_eq!(
+            eval("f(x, y) = x; f(1)"),
+            Err("`f` takes 2 inputs, not 1".to_string())
+        );
+        assert_eq!(
+            eval("ln(-1)"),
+            Err("logarithm of a number that isn't positive".to_string())
+        );
+        assert_eq!(eval("1/(1 - 1)"), Err("division by zero".to_string()));
+        assert_eq!(
+            eval("g(x) = g(x); g(1)"),
+            Err("`g` calls itself too many times".to_string())
+        );
+        assert_eq!(
+            eval("frob(1)"),
+            Err("`frob` is not a known function".to_string())
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+
+use super::{
+    number::{bits, MAX_EXACT_BITS},
+    Number,
+};
+
+/// `re + im i`, with each part exact as long as it can be
+#[derive(Debug, Clone, PartialEq)]
+pub struct Complex {
+    pub re: Number,
+    pub im: Number,
+}
+
+impl Complex {
+    pub fn new(re: Number, im: Number) -> Complex {
+        Complex { re, im }
+    }
+
+    /// The imaginary unit
+    pub fn i() -> Complex {
+        Complex::new(Number::from(0), Number::from(1))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.re.is_zero() && self.im.is_zero()
+    }
+
+    /// Complex conjugate, `re - im i`
+    pub fn conj(&self) -> Complex {
+        Complex::new(self.re.clone(), -&self.im)
+    }
+
+    /// Distance from 0, exact when it's rational like `|3 + 4i| = 5`. NaN when a part is.
+    pub fn abs(&self) -> Number {
+        let square = &(&self.re * &self.re) + &(&self.im * &self.im);
+        let half = Number::Exact(BigRational::new(1.into(), 2.into()));
+        // Squaring a float part can overflow or give NaN, which `hypot` doesn't
+        square
+            .pow(&half)
+            .filter(|abs| abs.is_exact() || abs.to_f64().is_finite())
+            .unwrap_or_else(|| Number::Float(self.re.to_f64().hypot(self.im.to_f64())))
+    }
+
+    /// Angle from the positive real axis, between -π and π
+    pub fn arg(&self) -> Number {
+        if self.im.is_zero() && !self.re.is_negative() {
+            return Number::from(0);
+        }
+        Number::Float(self.im.to_f64().atan2(self.re.to_f64()))
+    }
+
+    /// [`None`] when that would divide by zero
+    pub fn checked_div(&self, other: &Complex) -> Option<Complex> {
+        let denominator = &(&other.re * &other.re) + &(&other.im * &other.im);
+        if denominator.is_zero() {
+            return None;
+        }
+        let numerator = self * &other.conj();
+        Some(Complex::new(
+            &numerator.re / &denominator,
+            &numerator.im / &denominator,
+        ))
+    }
+
+    /// `self` to the power of `exponent`. Whole powers are multiplied out so they stay exact,
+    /// others are the principal value. [`None`] for zero to a negative or complex power.
+    pub fn pow(&self, exponent: &Complex) -> Option<Complex> {
+        let one = Complex::new(Number::from(1), Number::from(0));
+        if self.is_zero() {
+            return match (exponent.im.is_zero(), &exponent.re) {
+                (true, re) if re.is_zero() => Some(one),
+                (true, re) if !re.is_negative() => Some(self.clone()),
+                _ => None,
+            };
+        }
+        // Multiplied out while the answer stays a size worth having, like for `Number::pow`.
+        // Past that the principal value is used.
+        let size = [&self.re, &self.im]
+            .into_iter()
+            .map(|part| match part {
+                Number::Exact(exact) => bits(exact),
+                Number::Float(_) => 0,
+            })
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let whole = exponent
+            .re
+            .to_integer()
+            .and_then(|power| power.to_i64())
+            .filter(|power| {
+                exponent.im.is_zero() && size.saturating_mul(power.unsigned_abs()) <= MAX_EXACT_BITS
+            });
+        if let Some(power) = whole {
+            let (mut result, mut base, mut remaining) =
+                (one.clone(), self.clone(), power.unsigned_abs());
+            while remaining > 0 {
+                if remaining % 2 == 1 {
+                    result = &result * &base;
+                }
+                base = &base * &base;
+                remaining /= 2;
+            }
+            return if power < 0 {
+                one.checked_div(&result)
+            } else {
+                Some(result)
+            };
+        }
+        // e^(exponent ln(self)), with the logarithm's angle between -π and π
+        let (ln_modulus, angle) = (self.abs().to_f64().ln(), self.arg().to_f64());
+        let (c, d) = (exponent.re.to_f64(), exponent.im.to_f64());
+        let modulus = (c * ln_modulus - d * angle).exp();
+        let angle = d * ln_modulus + c * angle;
+        Some(Complex::new(
+            Number::Float(modulus * angle.cos()),
+            Number::Float(modulus * angle.sin()),
+        ))
+    }
+
+    /// Whether both parts are [`close`](Number::close)
+    pub fn close(&self, other: &Complex, tolerance: f64) -> bool {
+        self.re.close(&other.re, tolerance) && self.im.close(&other.im, tolerance)
+    }
+
+    /// Writes it as `3 + 4i`, with `part` writing each part, `minus` for taking away and `unit`
+    /// for `i`. Parts that are zero are left out, and so is a 1 in front of `i`.
+    pub(crate) fn write_with(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        part: impl Fn(&Number) -> String,
+        minus: &str,
+        unit: &str,
+    ) -> fmt::Result {
+        if self.im.is_zero() {
+            return write!(f, "{}", part(&self.re));
+        }
+        let (sign, im) = if self.im.is_negative() {
+            (minus, -&self.im)
+        } else {
+            ("+", self.im.clone())
+        };
+        let imaginary = match im.is_one() {
+            true => unit.to_string(),
+            false => format!("{}{unit}", part(&im)),
+        };
+        match (self.re.is_zero(), sign) {
+            (true, "+") => write!(f, "{imaginary}"),
+            (true, _) => write!(f, "{minus}{imaginary}"),
+            (false, _) => write!(f, "{} {sign} {imaginary}", part(&self.re)),
+        }
+    }
+}
+
+impl From<Number> for Complex {
+    fn from(re: Number) -> Complex {
+        Complex::new(re, Number::from(0))
+    }
+}
+
+impl Add for &Complex {
+    type Output = Complex;
+
+    fn add(self, other: &Complex) -> Complex {
+        Complex::new(&self.re + &other.re, &self.im + &other.im)
+    }
+}
+
+impl Sub for &Complex {
+    type Output = Complex;
+
+    fn sub(self, other: &Complex) -> Complex {
+        Complex::new(&self.re - &other.re, &self.im - &other.im)
+    }
+}
+
+impl Mul for &Complex {
+    type Output = Complex;
+
+    fn mul(self, other: &Complex) -> Complex {
+        Complex::new(
+            &(&self.re * &other.re) - &(&self.im * &other.im),
+            &(&self.re * &other.im) + &(&self.im * &other.re),
+        )
+    }
+}
+
+impl Neg for &Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex::new(-&self.re, -&self.im)
+    }
+}
+
+/// `3 + 4i`
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(f, Number::to_string, "-", "i")
+    }
+}
@@ -0,0 +1,325 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+
+/// Most bits a power is worked out exactly with, about 20000 digits. Past this the answer is
+/// too big to be useful, and slow to find.
+pub(super) const MAX_EXACT_BITS: u64 = 1 << 16;
+
+/// Largest root that's taken exactly, like the 3 of a cube root
+const MAX_EXACT_ROOT: u32 = 64;
+
+/// A number that stays exact as long as it can, falling back to a float when it has to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    /// A whole number or fraction, kept exactly
+    Exact(BigRational),
+    Float(f64),
+}
+
+impl Number {
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Number::Exact(_))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Exact(exact) => exact.to_f64().unwrap_or(f64::NAN),
+            Number::Float(float) => *float,
+        }
+    }
+
+    /// The whole number this is, if it is one
+    pub fn to_integer(&self) -> Option<BigInt> {
+        match self {
+            Number::Exact(exact) if exact.is_integer() => Some(exact.to_integer()),
+            Number::Exact(_) => None,
+            Number::Float(float) if float.fract() == 0.0 => BigInt::from_f64(*float),
+            Number::Float(_) => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Exact(exact) => exact.is_zero(),
+            Number::Float(float) => *float == 0.0,
+        }
+    }
+
+    pub fn is_one(&self) -> bool {
+        match self {
+            Number::Exact(exact) => exact.is_one(),
+            Number::Float(float) => *float == 1.0,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Number::Exact(exact) => exact.is_negative(),
+            Number::Float(float) => *float < 0.0,
+        }
+    }
+
+    pub fn abs(&self) -> Number {
+        self.map(BigRational::abs, f64::abs)
+    }
+
+    pub fn floor(&self) -> Number {
+        self.map(BigRational::floor, f64::floor)
+    }
+
+    pub fn ceil(&self) -> Number {
+        self.map(BigRational::ceil, f64::ceil)
+    }
+
+    /// To the nearest whole number, halves away from zero
+    pub fn round(&self) -> Number {
+        self.map(BigRational::round, f64::round)
+    }
+
+    /// -1, 0 or 1
+    pub fn signum(&self) -> Number {
+        match self {
+            Number::Float(float) if *float == 0.0 => Number::Float(0.0),
+            number => number.map(BigRational::signum, f64::signum),
+        }
+    }
+
+    /// [`None`] when dividing by zero
+    pub fn checked_div(&self, other: &Number) -> Option<Number> {
+        (!other.is_zero()).then(|| self / other)
+    }
+
+    fn map(&self, exact: impl Fn(&BigRational) -> BigRational, float: fn(f64) -> f64) -> Number {
+        match self {
+            Number::Exact(number) => Number::Exact(exact(number)),
+            Number::Float(number) => Number::Float(float(*number)),
+        }
+    }
+
+    /// `self` to the power of `exponent`, exactly when the answer is rational. [`None`] when it
+    /// isn't a real number, or is a division by zero.
+    pub fn pow(&self, exponent: &Number) -> Option<Number> {
+        if self.is_zero() && exponent.is_negative() {
+            return None;
+        }
+        if let (Number::Exact(base), Number::Exact(exponent)) = (self, exponent) {
+            if let Some(power) = exact_pow(base, exponent) {
+                return power;
+            }
+        }
+        let (float_base, float_exponent) = (self.to_f64(), exponent.to_f64());
+        if float_base >= 0.0 || float_exponent.fract() == 0.0 {
+            return Some(Number::Float(float_base.powf(float_exponent)));
+        }
+        // A root with an odd denominator, like a cube root, is real for negative numbers
+        let Number::Exact(exponent) = exponent else {
+            return None;
+        };
+        if !exponent.denom().bit(0) {
+            return None;
+        }
+        let sign = if exponent.numer().bit(0) { -1.0 } else { 1.0 };
+        Some(Number::Float(sign * float_base.abs().powf(float_exponent)))
+    }
+
+    /// Whether the numbers are equal, allowing for float rounding
+    pub fn close(&self, other: &Number, tolerance: f64) -> bool {
+        match (self, other) {
+            (Number::Exact(left), Number::Exact(right)) => left == right,
+            _ => {
+                let (left, right) = (self.to_f64(), other.to_f64());
+                left == right
+                    || (left - right).abs() <= tolerance * left.abs().max(right.abs()).max(1.0)
+            }
+        }
+    }
+}
+
+/// `Some(None)` when the power isn't real, [`None`] when it can't be found exactly
+fn exact_pow(base: &BigRational, exponent: &BigRational) -> Option<Option<Number>> {
+    let power = exponent.numer().to_i32()?;
+    let root = exponent
+        .denom()
+        .to_u32()
+        .filter(|root| *root <= MAX_EXACT_ROOT)?;
+    let base = if root == 1 {
+        base.clone()
+    } else {
+        if base.is_negative() && root % 2 == 0 {
+            return Some(None);
+        }
+        let numerator = base.numer().nth_root(root);
+        let denominator = base.denom().nth_root(root);
+        if numerator.pow(root) != *base.numer() || denominator.pow(root) != *base.denom() {
+            return None;
+        }
+        BigRational::new(numerator, denominator)
+    };
+    if bits(&base).saturating_mul(u64::from(power.unsigned_abs())) > MAX_EXACT_BITS {
+        return None;
+    }
+    Some(Some(Number::Exact(base.pow(power))))
+}
+
+/// About how many bits each power of `number` adds, like 1 for 2 and 0 for 1
+pub(super) fn bits(number: &BigRational) -> u64 {
+    number.numer().bits().saturating_sub(1) + number.denom().bits().saturating_sub(1)
+}
+
+impl From<BigRational> for Number {
+    fn from(exact: BigRational) -> Number {
+        Number::Exact(exact)
+    }
+}
+
+impl From<BigInt> for Number {
+    fn from(whole: BigInt) -> Number {
+        Number::Exact(BigRational::from_integer(whole))
+    }
+}
+
+impl From<i64> for Number {
+    fn from(whole: i64) -> Number {
+        Number::from(BigInt::from(whole))
+    }
+}
+
+impl From<f64> for Number {
+    fn from(float: f64) -> Number {
+        Number::Float(float)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Exact(left), Number::Exact(right)) => left.partial_cmp(right),
+            _ => self.to_f64().partial_cmp(&other.to_f64()),
+        }
+    }
+}
+
+macro_rules! operator {
+    ($trait:ident, $method:ident) => {
+        impl $trait for &Number {
+            type Output = Number;
+
+            fn $method(self, other: &Number) -> Number {
+                match (self, other) {
+                    (Number::Exact(left), Number::Exact(right)) => {
+                        Number::Exact(left.$method(right))
+                    }
+                    _ => Number::Float(self.to_f64().$method(other.to_f64())),
+                }
+            }
+        }
+    };
+}
+
+operator!(Add, add);
+operator!(Sub, sub);
+operator!(Mul, mul);
+
+/// Dividing by an exact zero gives a float infinity or NaN, as it would for floats. Use
+/// [`Number::checked_div`] to catch it instead.
+impl Div for &Number {
+    type Output = Number;
+
+    fn div(self, other: &Number) -> Number {
+        match (self, other) {
+            (Number::Exact(left), Number::Exact(right)) if !right.is_zero() => {
+                Number::Exact(left / right)
+            }
+            _ => Number::Float(self.to_f64() / other.to_f64()),
+        }
+    }
+}
+
+impl Neg for &Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Exact(exact) => Number::Exact(-exact),
+            Number::Float(float) => Number::Float(-float),
+        }
+    }
+}
+
+/// Fractions as `1/3`
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Exact(exact) => write!(f, "{exact}"),
+            Number::Float(float) => write!(f, "{float}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fraction(numerator: i64, denominator: i64) -> Number {
+        Number::Exact(BigRational::new(numerator.into(), denominator.into()))
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(&fraction(1, 3) + &fraction(1, 6), fraction(1, 2));
+        assert_eq!(&fraction(1, 2) * &Number::Float(3.0), Number::Float(1.5));
+        assert_eq!(&Number::from(1) / &Number::from(4), fraction(1, 4));
+        assert_eq!(-&fraction(2, 3), fraction(-2, 3));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let (one, zero) = (Number::from(1), Number::from(0));
+        assert_eq!(&one / &zero, Number::Float(f64::INFINITY));
+        assert_eq!(&-&one / &zero, Number::Float(f64::NEG_INFINITY));
+        assert!((&zero / &zero).to_f64().is_nan());
+        assert_eq!(one.checked_div(&zero), None);
+        assert_eq!(one.checked_div(&Number::Float(0.0)), None);
+        assert_eq!(one.checked_div(&Number::from(2)), Some(fraction(1, 2)));
+    }
+
+    #[test]
+    fn pow() {
+        assert_eq!(fraction(4, 9).pow(&fraction(1, 2)), Some(fraction(2, 3)));
+        assert_eq!(
+            Number::from(-8).pow(&fraction(1, 3)),
+            Some(Number::from(-2))
+        );
+        assert_eq!(Number::from(-4).pow(&fraction(1, 2)), None);
+        assert_eq!(Number::from(0).pow(&Number::from(-1)), None);
+        assert_eq!(
+            Number::from(2).pow(&Number::Float(0.5)),
+            Some(Number::Float(2f64.sqrt()))
+        );
+        // Too big to work out exactly, however small the exponent
+        let huge = Number::from(10).pow(&Number::from(4096)).unwrap();
+        assert!(huge.is_exact());
+        assert_eq!(
+            huge.pow(&Number::from(4096)),
+            Some(Number::Float(f64::INFINITY))
+        );
+        assert_eq!(
+            Number::from(1).pow(&Number::from(1_000_000)),
+            Some(Number::from(1))
+        );
+    }
+
+    #[test]
+    fn to_integer() {
+        assert_eq!(Number::Float(3.0).to_integer(), Some(BigInt::from(3)));
+        assert_eq!(fraction(3, 2).to_integer(), None);
+        assert_eq!(Number::Float(f64::NAN).to_integer(), None);
+    }
+}
@@ -0,0 +1,1362 @@
+//! LaTeX backend. Wrap any [`ast`](crate::ast) node in [`Latex`] and format it.
+
+use std::{collections::HashSet, fmt, rc::Rc};
+
+use num_traits::Signed;
+
+use crate::{
+    ast::*,
+    eval::{self, Number, Value},
+};
+
+/// Displays the wrapped node as LaTeX math
+pub struct Latex<'a, T: ?Sized> {
+    node: &'a T,
+    dialect: Dialect,
+    /// Write derivatives as what they work out to, rather than as derivatives
+    computed: bool,
+    /// Write quantities with the siunitx package
+    siunitx: bool,
+    /// Addresses of the products of two matrices, from [`Binary::matrix_products`] once the
+    /// outermost product has been reached
+    matrix_products: Option<Rc<HashSet<usize>>>,
+}
+
+/// Flavour of LaTeX to write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Dialect {
+    Standard,
+    /// What the Desmos calculator accepts, see [`crate::desmos`]
+    Desmos,
+}
+
+impl<'a, T: ?Sized> Latex<'a, T> {
+    pub fn new(node: &'a T) -> Latex<'a, T> {
+        Latex::with_dialect(node, Dialect::Standard)
+    }
+
+    pub(crate) fn with_dialect(node: &'a T, dialect: Dialect) -> Latex<'a, T> {
+        Latex {
+            node,
+            dialect,
+            computed: false,
+            siunitx: false,
+            matrix_products: None,
+        }
+    }
+
+    /// Writes `diff(x^2, x)` as `2x` rather than `\frac{d}{dx}x^{2}`, where the derivative can be
+    /// worked out
+    pub fn computing_derivatives(self) -> Latex<'a, T> {
+        Latex {
+            computed: true,
+            ..self
+        }
+    }
+
+    /// Writes `9.81 m/s^2` as `\qty{9.81}{m/s^{2}}` rather than `9.81\,\mathrm{m/s^{2}}`, for
+    /// documents using the siunitx package
+    pub fn using_siunitx(self) -> Latex<'a, T> {
+        Latex {
+            siunitx: true,
+            ..self
+        }
+    }
+
+    /// `node` in the same dialect
+    fn child<U: ?Sized>(&self, node: &'a U) -> Latex<'a, U> {
+        Latex {
+            node,
+            dialect: self.dialect,
+            computed: self.computed,
+            siunitx: self.siunitx,
+            matrix_products: self.matrix_products.clone(),
+        }
+    }
+
+    fn left_bracket(&self) -> &'static str {
+        match self.dialect {
+            Dialect::Standard => "(",
+            Dialect::Desmos => r"\left(",
+        }
+    }
+
+    fn right_bracket(&self) -> &'static str {
+        match self.dialect {
+            Dialect::Standard => ")",
+            Dialect::Desmos => r"\right)",
+        }
+    }
+
+    /// `expression`, bracketed if it binds looser than `precedence`
+    fn operand(&self, expression: &'a Expression<'_>, precedence: Precedence) -> String {
+        if expression.precedence() < precedence {
+            self.bracketed(expression)
+        } else {
+            self.child(expression).to_string()
+        }
+    }
+
+    /// Right hand side of a binary operator. Also brackets negation so `a - (-b)` stays readable.
+    fn right_operand(&self, expression: &'a Expression<'_>, precedence: Precedence) -> String {
+        match expression {
+            Expression::Unary(_) => self.bracketed(expression),
+            Expression::Number(number) if number.is_negative() => self.bracketed(expression),
+            _ => self.operand(expression, precedence),
+        }
+    }
+
+    /// `(expression)`, with brackets that grow to fit when it's tall
+    fn bracketed(&self, expression: &'a Expression<'_>) -> String {
+        if is_tall(expression) {
+            return format!(r"\left({}\right)", self.child(expression));
+        }
+        format!(
+            "{}{}{}",
+            self.left_bracket(),
+            self.child(expression),
+            self.right_bracket()
+        )
+    }
+}
+
+/// Does it stick out above or below a line of text, so that normal brackets would look too small
+fn is_tall(expression: &Expression<'_>) -> bool {
+    match expression {
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Divide,
+            ..
+        })
+        | Expression::BigOperator(_)
+        | Expression::Integral(_)
+        | Expression::Limit(_)
+        | Expression::Vector(_)
+        | Expression::Matrix(_)
+        | Expression::Piecewise(_)
+        | Expression::Derivative(Derivative::Leibniz { .. } | Derivative::Partial { .. }) => true,
+        Expression::Binary(binary) => is_tall(&binary.left) || is_tall(&binary.right),
+        Expression::Unary(unary) => is_tall(&unary.operand),
+        Expression::Relation(relation) => {
+            is_tall(&relation.first) || relation.rest.iter().any(|(_, right)| is_tall(right))
+        }
+        Expression::FunctionCall(function_call) => function_call.inputs.iter().any(is_tall),
+        Expression::Derivative(Derivative::Prime { inputs, .. }) => {
+            inputs.iter().flatten().any(is_tall)
+        }
+        Expression::Set(set) => set.elements.iter().any(is_tall),
+        Expression::List(list) => list.elements.iter().any(is_tall),
+        Expression::SetBuilder(set_builder) => is_tall(&set_builder.condition),
+        Expression::Interval(interval) => is_tall(&interval.lower) || is_tall(&interval.upper),
+        Expression::Delimited(delimited) => is_tall(&delimited.inner),
+        Expression::Group(group) => is_tall(&group.inner),
+        Expression::Number(number) => number.is_exact() && number.to_integer().is_none(),
+        Expression::Quantity(quantity) => is_tall(&quantity.value),
+        Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Subscript(_)
+        | Expression::NumberSet(_) => false,
+    }
+}
+
+/// Functions LaTeX has a dedicated command for
+const LATEX_FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "csc", "sec", "cot", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "coth", "log", "ln", "lg", "exp", "det", "dim", "gcd", "max", "min", "deg",
+];
+
+/// Functions Desmos knows that LaTeX has no command for
+const DESMOS_FUNCTIONS: &[&str] = &[
+    "lcm", "mod", "floor", "ceil", "round", "abs", "sign", "arcsec", "arccsc", "arccot", "sech",
+    "csch", "mean", "median", "total", "length", "stdev",
+];
+
+/// Desmos has no limits, so statements with one are left out
+impl fmt::Display for Latex<'_, Ast<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let statements = self
+            .node
+            .statements
+            .iter()
+            .filter(|statement| self.dialect == Dialect::Standard || !has_limit(statement));
+        for (i, statement) in statements.enumerate() {
+            if i != 0 {
+                match self.dialect {
+                    Dialect::Standard => writeln!(f, r" \\")?,
+                    Dialect::Desmos => writeln!(f)?,
+                }
+            }
+            write!(f, "{}", self.child(statement))?;
+        }
+        Ok(())
+    }
+}
+
+fn has_limit(statement: &Statement<'_>) -> bool {
+    let mut found = false;
+    let mut look = |expression: &Expression<'_>| {
+        expression.rewrite(&mut |expression| {
+            found |= matches!(expression, Expression::Limit(_));
+            None
+        });
+    };
+    match statement {
+        Statement::Definition(definition) => look(&definition.value),
+        Statement::FunctionDefinition(function_definition) => look(&function_definition.body),
+        Statement::Equation(relation) => look(&Expression::Relation(relation.clone())),
+        Statement::Expression(expression) => look(expression),
+        Statement::Derivation(derivation) => {
+            for step in &derivation.steps {
+                look(&step.expression);
+            }
+        }
+    }
+    found
+}
+
+impl fmt::Display for Latex<'_, Statement<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.node {
+            Statement::Definition(definition) => write!(
+                f,
+                "{} = {}",
+                self.child(&definition.name()),
+                self.child(&definition.value)
+            ),
+            Statement::FunctionDefinition(function_definition) => {
+                write!(f, "{}", self.child(function_definition))
+            }
+            Statement::Equation(relation) => write!(f, "{}", self.child(relation)),
+            Statement::Expression(expression) => write!(f, "{}", self.child(expression)),
+            Statement::Derivation(derivation) => write!(f, "{}", self.child(derivation)),
+        }
+    }
+}
+
+impl fmt::Display for Latex<'_, Derivation<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dialect == Dialect::Desmos {
+            // Desmos has no alignment, so just list what each step claims
+            let claims = self
+                .node
+                .steps
+                .iter()
+                .filter(|step| !matches!(step.kind, StepKind::Continues(_)));
+            for (i, step) in claims.enumerate() {
+                if i != 0 {
+                    writeln!(f)?;
+                }
+                write!(f, "{}", self.child(&step.expression))?;
+            }
+            return Ok(());
+        }
+
+        let mut lines: Vec<String> = vec![];
+        let mut steps = self.node.steps.iter().peekable();
+        while let Some(step) = steps.next() {
+            let mut line = match step.kind {
+                StepKind::Start => self.aligned(&step.expression),
+                StepKind::Implies => format!(r"\implies {}", self.aligned(&step.expression)),
+                StepKind::Continues(operator) => {
+                    format!(
+                        "&{} {}",
+                        self.child(&operator),
+                        self.child(&step.expression)
+                    )
+                }
+            };
+            let mut justification = step.justification;
+            // `(x+1)^2` followed by `= x^2 + 2x + 1` reads better on one line
+            let starts_chain = step.kind == StepKind::Start
+                && justification.is_none()
+                && !matches!(step.expression, Expression::Relation(_));
+            if starts_chain {
+                if let Some(next) =
+                    steps.next_if(|next| matches!(next.kind, StepKind::Continues(_)))
+                {
+                    let StepKind::Continues(operator) = next.kind else {
+                        unreachable!()
+                    };
+                    line = format!(
+                        "{line} &{} {}",
+                        self.child(&operator),
+                        self.child(&next.expression)
+                    );
+                    justification = next.justification;
+                }
+            }
+            if let Some(justification) = justification {
+                line = format!(r"{line} && \text{{{}}}", escape_text(justification));
+            }
+            lines.push(line);
+        }
+        writeln!(f, r"\begin{{align*}}")?;
+        writeln!(f, "{}", lines.join(" \\\\\n"))?;
+        write!(f, r"\end{{align*}}")
+    }
+}
+
+impl<'a> Latex<'a, Derivation<'_>> {
+    /// Relation with an alignment point before its first operator
+    fn aligned(&self, expression: &'a Expression<'_>) -> String {
+        match expression {
+            Expression::Relation(relation) => {
+                let mut aligned = self.operand(&relation.first, Precedence::Sum);
+                for (i, (operator, right)) in relation.rest.iter().enumerate() {
+                    let align = if i == 0 { "&" } else { "" };
+                    aligned = format!(
+                        "{aligned} {align}{} {}",
+                        self.child(operator),
+                        self.operand(right, Precedence::Sum)
+                    );
+                }
+                aligned
+            }
+            expression => self.child(expression).to_string(),
+        }
+    }
+}
+
+/// Escapes characters that mean something in LaTeX text mode
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\textbackslash{}"),
+            '~' => escaped.push_str(r"\textasciitilde{}"),
+            '^' => escaped.push_str(r"\textasciicircum{}"),
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl fmt::Display for Latex<'_, FunctionDefinition<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let FunctionDefinition {
+            identifier,
+            parameters,
+            body,
+        } = self.node;
+        self.write_function_name(f, identifier)?;
+        write!(f, "{}", self.left_bracket())?;
+        for (i, parameter) in parameters.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", self.child(parameter))?;
+        }
+        write!(f, "{} = {}", self.right_bracket(), self.child(body))
+    }
+}
+
+impl fmt::Display for Latex<'_, Expression<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.node {
+            Expression::Literal(literal) => write!(f, "{}", self.child(literal)),
+            Expression::Number(number) => write!(f, "{}", self.child(number)),
+            Expression::Quantity(quantity) => write!(f, "{}", self.child(quantity)),
+            Expression::Variable(variable) => write!(f, "{}", self.child(&variable.identifier)),
+            Expression::FunctionCall(function_call) => write!(f, "{}", self.child(function_call)),
+            Expression::Subscript(subscript) => write!(f, "{}", self.child(subscript)),
+            Expression::Unary(unary) => write!(f, "{}", self.child(unary)),
+            Expression::Binary(binary) => write!(f, "{}", self.child(binary)),
+            Expression::Relation(relation) => write!(f, "{}", self.child(relation)),
+            Expression::BigOperator(big_operator) => write!(f, "{}", self.child(big_operator)),
+            Expression::Integral(integral) => write!(f, "{}", self.child(integral)),
+            Expression::Limit(limit) => write!(f, "{}", self.child(limit)),
+            Expression::Derivative(derivative) => write!(f, "{}", self.child(derivative)),
+            Expression::Vector(vector) => write!(f, "{}", self.child(vector)),
+            Expression::Matrix(matrix) => write!(f, "{}", self.child(matrix)),
+            Expression::Piecewise(piecewise) => write!(f, "{}", self.child(piecewise)),
+            Expression::Set(set) => write!(f, "{}", self.child(set)),
+            Expression::SetBuilder(set_builder) => write!(f, "{}", self.child(set_builder)),
+            Expression::Interval(interval) => write!(f, "{}", self.child(interval)),
+            Expression::NumberSet(number_set) => write!(f, "{}", self.child(number_set)),
+            Expression::Delimited(delimited) => write!(f, "{}", self.child(delimited)),
+            Expression::Group(group) => write!(f, "{}", self.bracketed(&group.inner)),
+            Expression::List(list) => write!(f, "{}", self.child(list)),
+        }
+    }
+}
+
+impl fmt::Display for Latex<'_, BigOperator<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let BigOperator {
+            kind,
+            index,
+            from,
+            to,
+            body,
+        } = self.node;
+        let command = match kind {
+            BigOperatorKind::Sum => r"\sum",
+            BigOperatorKind::Product => r"\prod",
+        };
+        write!(
+            f,
+            "{command}_{{{}={}}}^{{{}}} {}",
+            self.child(index),
+            self.child(from.as_ref()),
+            self.child(to.as_ref()),
+            self.operand(body, Precedence::Product)
+        )
+    }
+}
+
+impl fmt::Display for Latex<'_, Integral<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Integral {
+            integrand,
+            variable,
+            bounds,
+        } = self.node;
+        write!(f, r"\int")?;
+        if let Some((lower, upper)) = bounds {
+            write!(
+                f,
+                "_{{{}}}^{{{}}}",
+                self.child(lower.as_ref()),
+                self.child(upper.as_ref())
+            )?;
+        }
+        let differential = match self.dialect {
+            Dialect::Standard => r"\,d",
+            Dialect::Desmos => " d",
+        };
+        write!(
+            f,
+            " {}{differential}{}",
+            self.operand(integrand, Precedence::Product),
+            self.child(variable)
+        )
+    }
+}
+
+impl fmt::Display for Latex<'_, Limit<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Limit {
+            variable,
+            approaching,
+            body,
+        } = self.node;
+        write!(
+            f,
+            r"\lim_{{{}\to {}}} {}",
+            self.child(variable),
+            self.child(approaching.as_ref()),
+            self.operand(body, Precedence::Product)
+        )
+    }
+}
+
+/// Column vector, or a list in Desmos
+impl fmt::Display for Latex<'_, Vector<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements = self
+            .node
+            .elements
+            .iter()
+            .map(|element| self.child(element).to_string());
+        match self.dialect {
+            Dialect::Standard => write!(
+                f,
+                r"\begin{{bmatrix}}{}\end{{bmatrix}}",
+                elements.collect::<Vec<_>>().join(r" \\ ")
+            ),
+            Dialect::Desmos => write!(
+                f,
+                r"\left[{}\right]",
+                elements.collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+}
+
+/// Desmos has no matrices, so there it's a list of rows
+impl fmt::Display for Latex<'_, Matrix<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (column_seperator, row_seperator) = match self.dialect {
+            Dialect::Standard => (" & ", r" \\ "),
+            Dialect::Desmos => (", ", ", "),
+        };
+        let rows: Vec<String> = self
+            .node
+            .rows
+            .iter()
+            .map(|row| {
+                let row: Vec<String> = row
+                    .iter()
+                    .map(|element| self.child(element).to_string())
+                    .collect();
+                match self.dialect {
+                    Dialect::Standard => row.join(column_seperator),
+                    Dialect::Desmos => format!(r"\left[{}\right]", row.join(column_seperator)),
+                }
+            })
+            .collect();
+        match self.dialect {
+            Dialect::Standard => write!(
+                f,
+                r"\begin{{bmatrix}}{}\end{{bmatrix}}",
+                rows.join(row_seperator)
+            ),
+            Dialect::Desmos => write!(f, r"\left[{}\right]", rows.join(row_seperator)),
+        }
+    }
+}
+
+impl fmt::Display for Latex<'_, Delimited<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.child(self.node.inner.as_ref());
+        match (self.node.kind, self.dialect) {
+            (DelimiterKind::Abs, Dialect::Standard) => write!(f, r"\left| {inner} \right|"),
+            (DelimiterKind::Abs, Dialect::Desmos) => write!(f, r"\left|{inner}\right|"),
+            (DelimiterKind::Norm, Dialect::Standard) => write!(f, r"\lVert {inner} \rVert"),
+            (DelimiterKind::Floor, Dialect::Standard) => write!(f, r"\lfloor {inner} \rfloor"),
+            (DelimiterKind::Ceil, Dialect::Standard) => write!(f, r"\lceil {inner} \rceil"),
+            // Desmos has the functions but not the brackets
+            (DelimiterKind::Floor, Dialect::Desmos) => {
+                write!(f, r"\operatorname{{floor}}\left({inner}\right)")
+            }
+            (DelimiterKind::Ceil, Dialect::Desmos) => {
+                write!(f, r"\operatorname{{ceil}}\left({inner}\right)")
+            }
+            // Nor a norm, so it's the root of the total of the squares
+            (DelimiterKind::Norm, Dialect::Desmos) => write!(
+                f,
+                r"\sqrt{{\operatorname{{total}}\left(\left({inner}\right)^{{2}}\right)}}"
+            ),
+        }
+    }
+}
+
+/// Desmos has no sets, so there it's a list
+impl fmt::Display for Latex<'_, Set<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements: Vec<String> = self
+            .node
+            .elements
+            .iter()
+            .map(|element| self.child(element).to_string())
+            .collect();
+        match self.dialect {
+            Dialect::Standard => write!(f, r"\{{{}\}}", elements.join(", ")),
+            Dialect::Desmos => write!(f, r"\left[{}\right]", elements.join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for Latex<'_, List<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements: Vec<String> = self
+            .node
+            .elements
+            .iter()
+            .map(|element| self.operand(element, Precedence::Relation))
+            .collect();
+        write!(f, "{}", elements.join(", "))
+    }
+}
+
+impl fmt::Display for Latex<'_, SetBuilder<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let SetBuilder {
+            variable,
+            domain,
+            condition,
+        } = self.node;
+        write!(f, r"\{{{}", self.child(variable))?;
+        if let Some(domain) = domain {
+            write!(f, r" \in {}", self.child(domain.as_ref()))?;
+        }
+        write!(f, r" \mid {}\}}", self.child(condition.as_ref()))
+    }
+}
+
+impl fmt::Display for Latex<'_, Interval<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Interval {
+            lower,
+            upper,
+            lower_closed,
+            upper_closed,
+        } = self.node;
+        let (left, right) = match self.dialect {
+            Dialect::Standard => ("", ""),
+            Dialect::Desmos => (r"\left", r"\right"),
+        };
+        write!(
+            f,
+            "{left}{}{}, {}{right}{}",
+            if *lower_closed { "[" } else { "(" },
+            self.child(lower.as_ref()),
+            self.child(upper.as_ref()),
+            if *upper_closed { "]" } else { ")" }
+        )
+    }
+}
+
+impl fmt::Display for Latex<'_, NumberSet> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, r"\mathbb{{{}}}", self.node.letter())
+    }
+}
+
+impl fmt::Display for Latex<'_, Piecewise<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Piecewise { arms, otherwise } = self.node;
+        if self.dialect == Dialect::Desmos {
+            let mut arms: Vec<String> = arms
+                .iter()
+                .map(|arm| format!("{}: {}", self.child(&arm.condition), self.child(&arm.value)))
+                .collect();
+            if let Some(otherwise) = otherwise {
+                arms.push(self.child(otherwise.as_ref()).to_string());
+            }
+            return write!(f, r"\left\{{{}\right\}}", arms.join(", "));
+        }
+        let mut arms: Vec<String> = arms
+            .iter()
+            .map(|arm| {
+                format!(
+                    r"{} & \text{{if }} {}",
+                    self.child(&arm.value),
+                    self.child(&arm.condition)
+                )
+            })
+            .collect();
+        if let Some(otherwise) = otherwise {
+            arms.push(format!(
+                r"{} & \text{{otherwise}}",
+                self.child(otherwise.as_ref())
+            ));
+        }
+        write!(f, r"\begin{{cases}}{}\end{{cases}}", arms.join(r" \\ "))
+    }
+}
+
+impl fmt::Display for Latex<'_, Derivative<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let (true, Ok(computed)) = (self.computed, self.node.compute()) {
+            let latex = Latex {
+                node: &computed,
+                dialect: self.dialect,
+                computed: true,
+                siunitx: self.siunitx,
+                matrix_products: None,
+            };
+            // The brackets around it were decided for the derivative
+            return if computed.precedence() < self.node.precedence() {
+                write!(f, r"\left({latex}\right)")
+            } else {
+                write!(f, "{latex}")
+            };
+        }
+        match self.node {
+            Derivative::Leibniz { variable, body } => write!(
+                f,
+                r"\frac{{d}}{{d{}}}{}",
+                self.child(variable),
+                self.operand(body, Precedence::Atom)
+            ),
+            Derivative::Prime {
+                function,
+                order,
+                inputs,
+            } => {
+                self.write_function_name(f, function)?;
+                write!(f, "{}", "'".repeat(*order))?;
+                let Some(inputs) = inputs else {
+                    return Ok(());
+                };
+                write!(f, "{}", self.left_bracket())?;
+                for (i, input) in inputs.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", self.child(input))?;
+                }
+                write!(f, "{}", self.right_bracket())
+            }
+            Derivative::Partial { body, .. } => {
+                let denominator: Vec<String> = self
+                    .node
+                    .grouped_variables()
+                    .into_iter()
+                    .map(|(variable, count)| {
+                        format!(r"\partial {}{}", self.child(variable), power(count))
+                    })
+                    .collect();
+                let numerator = format!(r"\partial{}", power(self.node.order()));
+                match body.as_ref() {
+                    Expression::Variable(variable) => write!(
+                        f,
+                        r"\frac{{{numerator} {}}}{{{}}}",
+                        self.child(&variable.identifier),
+                        denominator.join(" ")
+                    ),
+                    body => write!(
+                        f,
+                        r"\frac{{{numerator}}}{{{}}}{}",
+                        denominator.join(" "),
+                        self.operand(body, Precedence::Atom)
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// `^2` for repeated differentiation, nothing for once
+fn power(count: usize) -> String {
+    match count {
+        1 => String::new(),
+        2..=9 => format!("^{count}"),
+        _ => format!("^{{{count}}}"),
+    }
+}
+
+impl fmt::Display for Latex<'_, Unary<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.node.operator {
+            UnaryOperator::Negate => write!(
+                f,
+                "-{}",
+                self.operand(&self.node.operand, Precedence::Power)
+            ),
+            UnaryOperator::PlusMinus => write!(
+                f,
+                r"\pm {}",
+                self.operand(&self.node.operand, Precedence::Power)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Latex<'_, Binary<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Binary {
+            operator,
+            left,
+            right,
+        } = self.node;
+        match operator {
+            BinaryOperator::Add => write!(
+                f,
+                "{} + {}",
+                self.operand(left, Precedence::Sum),
+                self.right_operand(right, Precedence::Sum)
+            ),
+            BinaryOperator::Subtract => write!(
+                f,
+                "{} - {}",
+                self.operand(left, Precedence::Sum),
+                self.right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::PlusMinus => write!(
+                f,
+                r"{} \pm {}",
+                self.operand(left, Precedence::Sum),
+                self.right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::Multiply => {
+                let matrix_products = match &self.matrix_products {
+                    Some(matrix_products) => Rc::clone(matrix_products),
+                    None => Rc::new(self.node.matrix_products()),
+                };
+                // Products of two matrices get a cross, anything else like a dot product gets a dot
+                let times = match matrix_products.contains(&(self.node as *const _ as usize)) {
+                    true => r"\times",
+                    false => r"\cdot",
+                };
+                let latex = Latex {
+                    matrix_products: Some(matrix_products),
+                    ..self.child(self.node)
+                };
+                write!(
+                    f,
+                    "{} {times} {}",
+                    latex.operand(left, Precedence::Product),
+                    latex.right_operand(right, Precedence::Product)
+                )
+            }
+            BinaryOperator::ImplicitMultiply => {
+                let left = self.operand(left, Precedence::Product);
+                let right = self.right_operand(right, Precedence::Product);
+                write!(f, "{}", juxtapose(&left, &right))
+            }
+            BinaryOperator::Divide => write!(
+                f,
+                r"\frac{{{}}}{{{}}}",
+                self.child(left.ungrouped()),
+                self.child(right.ungrouped())
+            ),
+            BinaryOperator::Power => write!(
+                f,
+                "{}^{{{}}}",
+                self.operand(left, Precedence::Atom),
+                self.child(right.ungrouped())
+            ),
+            BinaryOperator::Union => write!(
+                f,
+                r"{} \cup {}",
+                self.operand(left, Precedence::Sum),
+                self.right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::Intersection => write!(
+                f,
+                r"{} \cap {}",
+                self.operand(left, Precedence::Product),
+                self.right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::Difference => write!(
+                f,
+                r"{} \setminus {}",
+                self.operand(left, Precedence::Sum),
+                self.right_operand(right, Precedence::Product)
+            ),
+        }
+    }
+}
+
+/// `left` times `right` without an operator, unless that would merge them into something else
+fn juxtapose(left: &str, right: &str) -> String {
+    let left_end = left.chars().next_back();
+    let right_start = right.chars().next();
+    match (left_end, right_start) {
+        (_, Some(c)) if c.is_ascii_digit() => format!(r"{left} \cdot {right}"),
+        (Some(l), Some(r)) if l.is_ascii_alphabetic() && r.is_ascii_alphabetic() => {
+            format!("{left} {right}")
+        }
+        _ => format!("{left}{right}"),
+    }
+}
+
+impl fmt::Display for Latex<'_, Relation<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.operand(&self.node.first, Precedence::Sum))?;
+        for (operator, right) in &self.node.rest {
+            // `x = 2, 3` reads fine without brackets, since the list is all that's left
+            let right = match right {
+                Expression::List(_) => self.child(right).to_string(),
+                right => self.operand(right, Precedence::Sum),
+            };
+            write!(f, " {} {right}", self.child(operator))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Latex<'_, RelationOperator> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let latex = match self.node {
+            RelationOperator::Equal => "=",
+            RelationOperator::NotEqual => r"\neq",
+            RelationOperator::Less => "<",
+            RelationOperator::LessEqual => r"\le",
+            RelationOperator::Greater => ">",
+            RelationOperator::GreaterEqual => r"\ge",
+            RelationOperator::Approximately => r"\approx",
+            RelationOperator::In => r"\in",
+            RelationOperator::Subset => r"\subseteq",
+        };
+        write!(f, "{latex}")
+    }
+}
+
+impl fmt::Display for Latex<'_, Literal<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.node {
+            Literal::Whole(whole) => write!(f, "{}", self.child(whole)),
+            Literal::Float(float) => write!(f, "{}", self.digits(float.text)),
+            Literal::Scientific(scientific) => {
+                let (significand, exponent) = scientific.parts();
+                let exponent = exponent.trim_start_matches('+');
+                write!(
+                    f,
+                    r"{} \times 10^{{{}}}",
+                    self.digits(significand),
+                    self.digits(exponent)
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for Latex<'_, Whole<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dialect == Dialect::Desmos {
+            // Desmos only reads decimal
+            return write!(f, "{}", self.node.value);
+        }
+        let digits = self.digits(self.node.digits());
+        match self.node.radix {
+            Radix::Decimal => write!(f, "{digits}"),
+            Radix::Binary => write!(f, r"{digits}_{{2}}"),
+            Radix::Octal => write!(f, r"{digits}_{{8}}"),
+            Radix::Hexadecimal => write!(f, r"\mathrm{{{digits}}}_{{16}}"),
+        }
+    }
+}
+
+/// `9.81\,\mathrm{m/s^{2}}`, or `\qty{9.81}{m/s^{2}}` with siunitx
+impl fmt::Display for Latex<'_, Quantity<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Quantity { value, unit } = self.node;
+        // Desmos has no units, so it gets the number of SI base units
+        if self.dialect == Dialect::Desmos {
+            let scale = unit.scale();
+            let value = self.operand(value, Precedence::Product);
+            return match scale.is_one() {
+                true => write!(f, "{value}"),
+                false => write!(f, r"{value} \cdot {}", self.child(&scale)),
+            };
+        }
+        if !self.siunitx {
+            let value = self.operand(value, Precedence::Product);
+            return write!(f, r"{value}\,\mathrm{{{}}}", unit_text(unit, r"\,"));
+        }
+        let unit = unit_text(unit, ".");
+        // `\qty` only takes a number as it would be written
+        let number = match value.as_ref() {
+            Expression::Literal(Literal::Whole(whole)) if whole.radix != Radix::Decimal => None,
+            Expression::Literal(literal) => Some(digits_text(literal.text())),
+            Expression::Number(number) if number.to_integer().is_some() || !number.is_exact() => {
+                Some(number.to_string())
+            }
+            _ => None,
+        };
+        match number {
+            Some(number) => write!(f, r"\qty{{{number}}}{{{unit}}}"),
+            None => {
+                let value = self.operand(value, Precedence::Product);
+                write!(f, r"{value}\,\unit{{{unit}}}")
+            }
+        }
+    }
+}
+
+/// `m/s^{2}`, with `separator` between units multiplied together
+fn unit_text(unit: &Unit<'_>, separator: &str) -> String {
+    let written = |powers: &[(&str, i32)]| {
+        let written: Vec<String> = powers
+            .iter()
+            .map(|(symbol, exponent)| {
+                let symbol = symbol
+                    .replace(['µ', 'μ'], r"{\mu}")
+                    .replace('Ω', r"{\Omega}");
+                match exponent {
+                    1 => symbol,
+                    _ => format!("{symbol}^{{{exponent}}}"),
+                }
+            })
+            .collect();
+        written.join(separator)
+    };
+    let (numerator, denominator) = unit.fraction();
+    match denominator.as_slice() {
+        [] => written(&numerator),
+        _ => format!("{}/{}", written(&numerator), written(&denominator)),
+    }
+}
+
+impl fmt::Display for Latex<'_, Value> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.node {
+            Value::Number(number) => write!(f, "{}", self.child(number)),
+            Value::Complex(complex) => {
+                complex.write_with(f, |part| self.child(part).to_string(), "-", "i")
+            }
+            Value::Bool(bool) => write!(f, r"\text{{{bool}}}"),
+        }
+    }
+}
+
+/// Fractions as `\frac{1}{3}`
+impl fmt::Display for Latex<'_, Number> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.node.is_negative() { "-" } else { "" };
+        match self.node {
+            Number::Exact(exact) if exact.is_integer() => write!(f, "{exact}"),
+            Number::Exact(exact) => {
+                let numerator = exact.numer().abs();
+                write!(f, r"{sign}\frac{{{numerator}}}{{{}}}", exact.denom())
+            }
+            Number::Float(float) if float.is_infinite() => write!(f, r"{sign}\infty"),
+            Number::Float(float) => write!(f, "{float}"),
+        }
+    }
+}
+
+impl<T: ?Sized> Latex<'_, T> {
+    /// Digits as written, with digit seperators turned into thin spaces
+    fn digits(&self, text: &str) -> String {
+        match self.dialect {
+            Dialect::Standard => text.replace('_', r"\,"),
+            Dialect::Desmos => text.replace('_', ""),
+        }
+    }
+
+    fn write_function_name(&self, f: &mut fmt::Formatter<'_>, name: &Identifier) -> fmt::Result {
+        let text = name.text;
+        // Builtins keep their names in Desmos too, only the user's own are made into one letter
+        let known = LATEX_FUNCTIONS.contains(&text)
+            || (self.dialect == Dialect::Desmos && DESMOS_FUNCTIONS.contains(&text))
+            || eval::builtin_arity(text).is_some();
+        if LATEX_FUNCTIONS.contains(&text) {
+            write!(f, r"\{text}")
+        } else if known || (self.dialect == Dialect::Standard && text.chars().count() > 1) {
+            write!(f, r"\operatorname{{{text}}}")
+        } else {
+            write!(f, "{}", Latex::with_dialect(name, self.dialect))
+        }
+    }
+}
+
+impl fmt::Display for Latex<'_, FunctionCall<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The radical sign's bar already groups what's under it
+        if let ("sqrt", [input]) = (self.node.identifier.text, self.node.inputs.as_slice()) {
+            return write!(f, r"\sqrt{{{}}}", self.child(input.ungrouped()));
+        }
+        self.write_function_name(f, &self.node.identifier)?;
+        write!(f, "{}", self.left_bracket())?;
+        for (i, input) in self.node.inputs.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", self.child(input))?;
+        }
+        write!(f, "{}", self.right_bracket())
+    }
+}
+
+impl fmt::Display for Latex<'_, Subscript<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Subscript { base, index } = self.node;
+        // Desmos subscripts are part of the name, so `v_max` is `v_{max}` not `v_{m_{ax}}`
+        if let (Dialect::Desmos, Expression::Variable(base)) = (self.dialect, base.as_ref()) {
+            let index = match index.as_ref() {
+                Expression::Variable(index) if index.identifier.symbol().is_none() => {
+                    Some(index.identifier.text)
+                }
+                Expression::Literal(Literal::Whole(whole)) if whole.radix == Radix::Decimal => {
+                    Some(whole.text)
+                }
+                _ => None,
+            };
+            if let Some(index) = index {
+                return write_desmos_name(f, &base.identifier, index);
+            }
+        }
+        write!(
+            f,
+            "{}_{{{}}}",
+            self.child(base.as_ref()),
+            self.child(index.as_ref())
+        )
+    }
+}
+
+impl fmt::Display for Latex<'_, Identifier<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = self.node.text;
+        match (self.node.symbol(), self.dialect) {
+            (Some(symbol), _) => write!(f, "{}", symbol.latex),
+            (None, Dialect::Standard) => write!(f, "{text}"),
+            (None, Dialect::Desmos) => write_desmos_name(f, self.node, ""),
+        }
+    }
+}
+
+/// Desmos names are one letter with an optional subscript, so the rest of the name goes in the
+/// subscript ahead of `index`
+fn write_desmos_name(f: &mut fmt::Formatter<'_>, name: &Identifier, index: &str) -> fmt::Result {
+    let (first, rest) = match name.symbol() {
+        Some(symbol) => (symbol.latex.to_string(), ""),
+        None => {
+            let mut chars = name.text.chars();
+            let first = chars.next().unwrap_or_default();
+            (first.to_string(), chars.as_str())
+        }
+    };
+    let subscript = format!("{rest}{}", index.replace('_', ""));
+    if subscript.is_empty() {
+        write!(f, "{first}")
+    } else {
+        write!(f, "{first}_{{{subscript}}}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn latex(str: &str) -> String {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        Latex::new(&ast).to_string()
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(latex("6.022e23"), r"6.022 \times 10^{23}");
+        assert_eq!(latex("1e-9"), r"1 \times 10^{-9}");
+        assert_eq!(latex(".5"), ".5");
+        assert_eq!(latex("0.10"), "0.10");
+        assert_eq!(latex("1_000_000"), r"1\,000\,000");
+        assert_eq!(
+            latex("1234567890123456789012345"),
+            "1234567890123456789012345"
+        );
+        assert_eq!(latex("0xff"), r"\mathrm{ff}_{16}");
+        assert_eq!(latex("0b1010"), "1010_{2}");
+    }
+
+    #[test]
+    fn symbols() {
+        assert_eq!(latex("theta"), r"\theta");
+        assert_eq!(latex("θ"), r"\theta");
+        assert_eq!(latex("Delta"), r"\Delta");
+        assert_eq!(latex("infinity"), r"\infty");
+        assert_eq!(latex("∞"), r"\infty");
+        assert_eq!(latex("f(alpha)"), r"f(\alpha)");
+    }
+
+    #[test]
+    fn subscript() {
+        assert_eq!(latex("x_1"), "x_{1}");
+        assert_eq!(latex("a_n"), "a_{n}");
+        assert_eq!(latex("v_max"), "v_{max}");
+        assert_eq!(latex("theta_0"), r"\theta_{0}");
+        assert_eq!(latex("`v_max`"), "v_max");
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(latex("x^2 + 1"), "x^{2} + 1");
+        assert_eq!(latex("2x + 3y"), "2x + 3y");
+        assert_eq!(latex("a - (b - c)"), "a - (b - c)");
+        assert_eq!(latex("(a - b) - c"), "(a - b) - c");
+        assert_eq!(latex("-(x + 1)"), "-(x + 1)");
+        assert_eq!(latex("-x^2"), "-x^{2}");
+        assert_eq!(latex("(x-2)(x+2)"), "(x - 2)(x + 2)");
+        assert_eq!(latex("(a*b)^2"), r"(a \cdot b)^{2}");
+        assert_eq!(latex("2^3^4"), "2^{3^{4}}");
+        assert_eq!(latex("1/(x+1)"), r"\frac{1}{x + 1}");
+        assert_eq!(latex("a + -b"), "a + (-b)");
+        assert_eq!(latex("2alpha"), r"2\alpha");
+        assert_eq!(latex("sqrt(x + 1) + sqrt((2))"), r"\sqrt{x + 1} + \sqrt{2}");
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(latex("x^(n+1)"), "x^{n + 1}");
+        assert_eq!(latex("(1/2)^n"), r"\left(\frac{1}{2}\right)^{n}");
+        assert_eq!(latex("(a + b)/(c)"), r"\frac{a + b}{c}");
+        assert_eq!(
+            latex("2(sum(i, 1, n, i))"),
+            r"2\left(\sum_{i=1}^{n} i\right)"
+        );
+        assert_eq!(latex("((x))"), "((x))");
+        assert_eq!(
+            latex("(x/2 + 1)(x - 1)"),
+            r"\left(\frac{x}{2} + 1\right)(x - 1)"
+        );
+    }
+
+    #[test]
+    fn relation() {
+        assert_eq!(latex("0 < x <= 1"), r"0 < x \le 1");
+        assert_eq!(latex("a >= b != c"), r"a \ge b \neq c");
+        assert_eq!(latex("pi ~= 3.14"), r"\pi \approx 3.14");
+        assert_eq!(latex("x^2 - 4 = 0"), "x^{2} - 4 = 0");
+    }
+
+    #[test]
+    fn statements() {
+        assert_eq!(latex("let g = 9.81"), "g = 9.81");
+        assert_eq!(latex("f(x) = x^2 + 1"), "f(x) = x^{2} + 1");
+        assert_eq!(
+            latex("let area(w, h) = w*h/2"),
+            r"\operatorname{area}(w, h) = \frac{w \cdot h}{2}"
+        );
+        assert_eq!(latex("a = 1; b = 2"), "a = 1 \\\\\nb = 2");
+    }
+
+    #[test]
+    fn derivation() {
+        assert_eq!(
+            latex("x^2 - 4 = 0 => (x-2)(x+2) = 0 \"factor\" => x = ±2"),
+            [
+                r"\begin{align*}",
+                r"x^{2} - 4 &= 0 \\",
+                r"\implies (x - 2)(x + 2) &= 0 && \text{factor} \\",
+                r"\implies x &= \pm 2",
+                r"\end{align*}",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            latex("(x+1)^2\n  = (x+1)(x+1)\n  = x^2 + 2x + 1 \"expand & collect\""),
+            [
+                r"\begin{align*}",
+                r"(x + 1)^{2} &= (x + 1)(x + 1) \\",
+                r"&= x^{2} + 2x + 1 && \text{expand \& collect}",
+                r"\end{align*}",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn big_operators() {
+        assert_eq!(latex("sum(i, 1, n, i^2)"), r"\sum_{i=1}^{n} i^{2}");
+        assert_eq!(latex("prod(k, 1, n, k + 1)"), r"\prod_{k=1}^{n} (k + 1)");
+        assert_eq!(latex("int(f(x), x, 0, 1)"), r"\int_{0}^{1} f(x)\,dx");
+        assert_eq!(latex("int(x^2, x)"), r"\int x^{2}\,dx");
+        assert_eq!(
+            latex("lim(x, 0, sin(x)/x)"),
+            r"\lim_{x\to 0} \frac{\sin(x)}{x}"
+        );
+        assert_eq!(latex("sum(1, 2)"), r"\operatorname{sum}(1, 2)");
+    }
+
+    #[test]
+    fn derivatives() {
+        assert_eq!(latex("d/dx(f(x))"), r"\frac{d}{dx}f(x)");
+        assert_eq!(latex("d/dx(x^2 + 1)"), r"\frac{d}{dx}(x^{2} + 1)");
+        assert_eq!(
+            latex("d/dtheta(sin(theta))"),
+            r"\frac{d}{d\theta}\sin(\theta)"
+        );
+        assert_eq!(latex("f'(x)"), "f'(x)");
+        assert_eq!(latex("f''(x) + y'"), "f''(x) + y'");
+        assert_eq!(
+            latex("pd(f, x, y)"),
+            r"\frac{\partial^2 f}{\partial x \partial y}"
+        );
+        assert_eq!(latex("pd(f, x, x)"), r"\frac{\partial^2 f}{\partial x^2}");
+        assert_eq!(
+            latex("pd(x^2*y, y)"),
+            r"\frac{\partial}{\partial y}(x^{2} \cdot y)"
+        );
+
+        let computed = |str| {
+            let tokens = crate::lexer::parse(str).unwrap();
+            let ast = crate::ast::parse(&tokens).unwrap();
+            Latex::new(&ast).computing_derivatives().to_string()
+        };
+        assert_eq!(latex("diff(x^3, x)"), r"\frac{d}{dx}(x^{3})");
+        assert_eq!(computed("diff(x^3, x)"), r"3x^{2}");
+        assert_eq!(computed("2diff(x^2 + x, x)"), r"2\left(2x + 1\right)");
+        assert_eq!(computed("pd(x^2*y, x)"), r"2x y");
+        assert_eq!(computed("f'(x)"), "f'(x)");
+    }
+
+    #[test]
+    fn quantities() {
+        assert_eq!(latex("9.81 m/s^2"), r"9.81\,\mathrm{m/s^{2}}");
+        assert_eq!(
+            latex("3 kg * 2 m/s^2"),
+            r"3\,\mathrm{kg} \cdot 2\,\mathrm{m/s^{2}}"
+        );
+        assert_eq!(latex("10 m / 2 s"), r"\frac{10\,\mathrm{m}}{2\,\mathrm{s}}");
+        assert_eq!(latex("5 µm"), r"5\,\mathrm{{\mu}m}");
+        assert_eq!(
+            latex("8.3 J kg^-1 K^-1"),
+            r"8.3\,\mathrm{J\,kg^{-1}\,K^{-1}}"
+        );
+
+        let siunitx = |str| {
+            let tokens = crate::lexer::parse(str).unwrap();
+            let ast = crate::ast::parse(&tokens).unwrap().simplify();
+            Latex::new(&ast).using_siunitx().to_string()
+        };
+        assert_eq!(siunitx("9.81 m/s^2"), r"\qty{9.81}{m/s^{2}}");
+        assert_eq!(siunitx("1_000 kg m"), r"\qty{1000}{kg.m}");
+        assert_eq!(siunitx("to(1 min, h)"), r"\frac{1}{60}\,\unit{h}");
+    }
+
+    #[test]
+    fn matrices() {
+        assert_eq!(
+            latex("[1, 2, 3]"),
+            r"\begin{bmatrix}1 \\ 2 \\ 3\end{bmatrix}"
+        );
+        assert_eq!(
+            latex("[[1, 2], [3, x^2]]"),
+            r"\begin{bmatrix}1 & 2 \\ 3 & x^{2}\end{bmatrix}"
+        );
+        assert_eq!(
+            latex("[[1, 2]] * 2"),
+            r"\begin{bmatrix}1 & 2\end{bmatrix} \cdot 2"
+        );
+        assert_eq!(
+            latex("2*[1, 2]"),
+            r"2 \cdot \begin{bmatrix}1 \\ 2\end{bmatrix}"
+        );
+        assert_eq!(
+            latex("[[1, 2], [3, 4]] * [5, 6]"),
+            r"\begin{bmatrix}1 & 2 \\ 3 & 4\end{bmatrix} \cdot \begin{bmatrix}5 \\ 6\end{bmatrix}"
+        );
+        assert_eq!(
+            latex("[[1, 2], [3, 4]] * [[5], [6]] * [[7, 8]]"),
+            [
+                r"\begin{bmatrix}1 & 2 \\ 3 & 4\end{bmatrix} \times \begin{bmatrix}5 \\ 6\end{bmatrix}",
+                r"\times \begin{bmatrix}7 & 8\end{bmatrix}",
+            ]
+            .join(" ")
+        );
+        assert_eq!(
+            latex("[1, 2] * [3, 4]"),
+            r"\begin{bmatrix}1 \\ 2\end{bmatrix} \cdot \begin{bmatrix}3 \\ 4\end{bmatrix}"
+        );
+    }
+
+    #[test]
+    fn piecewise() {
+        assert_eq!(
+            latex("f(x) = piecewise(x^2 if x < 0, x otherwise)"),
+            r"f(x) = \begin{cases}x^{2} & \text{if } x < 0 \\ x & \text{otherwise}\end{cases}"
+        );
+    }
+
+    #[test]
+    fn sets() {
+        assert_eq!(latex("{1, 2, 3}"), r"\{1, 2, 3\}");
+        assert_eq!(latex("x in [0, 1)"), r"x \in [0, 1)");
+        assert_eq!(latex("(a, b]"), "(a, b]");
+        assert_eq!(latex("x in (a, b)"), r"x \in (a, b)");
+        assert_eq!(latex("(1, 2)"), "(1, 2)");
+        assert_eq!(
+            latex("{x in R | x > 0}"),
+            r"\{x \in \mathbb{R} \mid x > 0\}"
+        );
+        assert_eq!(latex("A union B intersect D"), r"A \cup B \cap D");
+        assert_eq!(latex("(A union B) \\ {0}"), r"(A \cup B) \setminus \{0\}");
+        assert_eq!(latex("A union (B \\ D)"), r"A \cup (B \setminus D)");
+        assert_eq!(latex("[0, 1] subset R"), r"[0, 1] \subseteq \mathbb{R}");
+        assert_eq!(latex("{}"), r"\{\}");
+    }
+
+    #[test]
+    fn delimited() {
+        assert_eq!(latex("|x - 1| < 2"), r"\left| x - 1 \right| < 2");
+        assert_eq!(latex("||v||"), r"\lVert v \rVert");
+        assert_eq!(latex("floor(x/2)"), r"\lfloor \frac{x}{2} \rfloor");
+        assert_eq!(latex("ceil(x)"), r"\lceil x \rceil");
+        assert_eq!(latex("abs(x)^2"), r"\left| x \right|^{2}");
+    }
+
+    #[test]
+    fn function_call() {
+        assert_eq!(latex("sin(x)"), r"\sin(x)");
+        assert_eq!(latex("f(x, 2)"), "f(x, 2)");
+        assert_eq!(latex("lcm(4, 6)"), r"\operatorname{lcm}(4, 6)");
+    }
+}
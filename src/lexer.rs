@@ -1,8 +1,8 @@
-use std::{collections::HashMap, fmt, ops, slice, sync::OnceLock};
+use std::{cmp, collections::HashMap, fmt, hash, ops, slice, sync::OnceLock};
 
 mod parse;
 
-pub fn parse(str: &str) -> Result<Tokens, nom::Err<nom::error::Error<&str>>> {
+pub fn parse(str: &str) -> Result<Tokens<'_>, nom::Err<nom::error::Error<&str>>> {
     match parse::tokens(str) {
         Ok((_, tokens)) => Ok(tokens),
         Err(e) => Err(e),
@@ -47,15 +47,76 @@ impl<'src> Tokens<'src> {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Compared, hashed and printed by its fragment and kind alone, so the same token is equal
+/// wherever it is
+#[derive(Clone, Copy)]
 pub struct Token<'src> {
     pub fragment: &'src str,
     pub kind: TokenKind,
+    /// The source from the start of this token to the end, so the text spanning several
+    /// tokens can be found. Just the fragment until [`parse`] fills it in.
+    rest: &'src str,
+}
+
+impl<'src> Token<'src> {
+    fn key(&self) -> (&'src str, TokenKind) {
+        (self.fragment, self.kind)
+    }
+}
+
+impl PartialEq for Token<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Token<'_> {}
+
+impl PartialOrd for Token<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Token<'_> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl hash::Hash for Token<'_> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl fmt::Debug for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("fragment", &self.fragment)
+            .field("kind", &self.kind)
+            .finish()
+    }
 }
 
 impl<'src> Token<'src> {
     pub fn new(fragment: &'src str, kind: TokenKind) -> Token<'src> {
-        Token { fragment, kind }
+        Token {
+            fragment,
+            kind,
+            rest: fragment,
+        }
+    }
+
+    /// Source from the start of this token to the end of `last`, with whatever's between
+    /// them. Just this token's fragment when `last` doesn't come after it in the same source.
+    pub fn text_to(&self, last: &Token<'src>) -> &'src str {
+        self.rest
+            .len()
+            .checked_sub(last.rest.len())
+            .and_then(|before| self.rest.get(..before + last.fragment.len()))
+            .filter(|text| text.ends_with(last.fragment))
+            .unwrap_or(self.fragment)
     }
 
     pub fn fragment_if_kind(&self, kind: TokenKind) -> Option<&'src str> {
@@ -79,7 +140,8 @@ impl<'src> Token<'src> {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TokenKind {
-    /// String of ascii lowercase, uppercase alphabet, underscore, or number. Cannot start with number
+    /// String of unicode letters or numbers. Cannot start with number.
+    /// A lone symbol like `∞`, or anything between backticks is also an identifier.
     Identifier,
     Literal(Literal),
     Operator(Operator),
@@ -115,41 +177,47 @@ macro_rules! operators {
 }
 
 operators! {
-    // DoubleAsterisk => "**",
-    // RightThickArrow => "=>",
-    // LeftThickArrow => "<=",
-
-    // LeftCurlyBracket => "{",
-    // RightCurlyBracket => "}",
-    // LeftSquareBracket => "[",
-    // RightSquareBracket => "]",
+    DoubleAsterisk => "**",
+    RightThickArrow => "=>",
+    LeftAngleBracketEqual => "<=",
+    RightAngleBracketEqual => ">=",
+    BangEqual => "!=",
+    TildeEqual => "~=",
+
+    LeftCurlyBracket => "{",
+    RightCurlyBracket => "}",
+    LeftSquareBracket => "[",
+    RightSquareBracket => "]",
     LeftRoundBracket => "(",
     RightRoundBracket => ")",
-    // LeftAngleBracket => "<",
-    // RightAngleBracket => ">",
-
-    // Equal => "=",
-    // Plus => "+",
-    // Minus => "-",
-    // Asterisk => "*",
-    // ForwardSlash => "/",
-    // BackSlash => "\\",
+    LeftAngleBracket => "<",
+    RightAngleBracket => ">",
+
+    Equal => "=",
+    Plus => "+",
+    PlusMinus => "±",
+    PlusSlashMinus => "+/-",
+    Minus => "-",
+    Asterisk => "*",
+    ForwardSlash => "/",
+    BackSlash => "\\",
     // Percentage => "%",
-    // Caret => "^",
+    Caret => "^",
     // DollarSign => "$",
     // Hash => "#",
     // At => "@",
     // Bang => "!",
     // QuestionMark => "?",
     // Ampersand => "&",
-    // Semicolon => ";",
+    Semicolon => ";",
     // Colon => ":",
     Comma => ",",
+    Underscore => "_",
     // Dot => ".",
     // Tilde => "~",
     // Backtick => "`",
-    // Pipe => "|",
-    // SingleQuote => "'",
+    Pipe => "|",
+    SingleQuote => "'",
     // DoubleQuote => "\"",
 }
 
@@ -169,6 +237,34 @@ impl Operator {
     pub fn recognize(str: &str) -> Option<Operator> {
         Operator::map().get(str).copied()
     }
+
+    pub fn is_opening_bracket(&self) -> bool {
+        matches!(
+            self,
+            Operator::LeftRoundBracket | Operator::LeftSquareBracket | Operator::LeftCurlyBracket
+        )
+    }
+
+    pub fn is_closing_bracket(&self) -> bool {
+        matches!(
+            self,
+            Operator::RightRoundBracket
+                | Operator::RightSquareBracket
+                | Operator::RightCurlyBracket
+        )
+    }
+
+    /// Character count of the longest operator
+    pub fn max_len() -> usize {
+        static MAX_LEN: OnceLock<usize> = OnceLock::new();
+        *MAX_LEN.get_or_init(|| {
+            Operator::VALUE_ARRAY
+                .iter()
+                .map(|v| v.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+    }
 }
 
 impl fmt::Display for Operator {
@@ -179,8 +275,19 @@ impl fmt::Display for Operator {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Literal {
+    /// `1_000_000`
     Digits,
+    /// `23.13` or `.13`
     Float,
+    /// `6.022e23`
+    Scientific,
+    /// `0b1010`
+    Binary,
+    /// `0o17`
+    Octal,
+    /// `0xFF`
+    Hexadecimal,
+    /// `"text"`
     String,
 }
 
@@ -195,9 +302,36 @@ pub enum WhiteSpace {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_new_lines() {
+        let result = parse("a = 1\nf(\n  x\n)\n\nb").unwrap();
+        let new_lines = result
+            .as_slice()
+            .iter()
+            .filter(|t| t.kind == TokenKind::WhiteSpace(WhiteSpace::NewLine))
+            .count();
+        assert_eq!(new_lines, 2);
+    }
+
     #[test]
     fn test_lexer() {
         let result = parse("std(hello(23,23.8), 2398)").unwrap();
-        panic!("{result}");
+        let kinds: Vec<TokenKind> = result.as_slice().iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Identifier,
+                TokenKind::Operator(Operator::LeftRoundBracket),
+                TokenKind::Identifier,
+                TokenKind::Operator(Operator::LeftRoundBracket),
+                TokenKind::Literal(Literal::Digits),
+                TokenKind::Operator(Operator::Comma),
+                TokenKind::Literal(Literal::Float),
+                TokenKind::Operator(Operator::RightRoundBracket),
+                TokenKind::Operator(Operator::Comma),
+                TokenKind::Literal(Literal::Digits),
+                TokenKind::Operator(Operator::RightRoundBracket),
+            ]
+        );
     }
 }
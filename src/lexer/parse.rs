@@ -1,15 +1,17 @@
 use nom::{
     branch::alt,
     bytes::complete::take,
-    bytes::complete::take_while1,
-    combinator::{map, verify},
+    bytes::complete::{tag, take_while, take_while1},
+    character::complete::{char, one_of},
+    combinator::{map, not, opt, recognize, verify},
     error::{Error as NomError, ErrorKind as NomErrorKind},
     multi::many0,
-    sequence::tuple,
+    sequence::{delimited, pair, preceded, terminated, tuple},
     Err as ErrorCase,
 };
 
 use super::*;
+use crate::{diagnostic::Span, symbol::Symbol};
 
 #[cfg(test)]
 mod test;
@@ -17,79 +19,151 @@ mod test;
 type IResult<'a, O> = nom::IResult<&'a str, O>;
 type Input<'a> = &'a str;
 
-pub fn tokens(input: Input) -> IResult<Tokens<'_>> {
-    let token = map(token, |t| Some(t));
-    let white_space = map(white_space, |_| None);
+pub fn tokens(input: Input<'_>) -> IResult<'_, Tokens<'_>> {
+    let token = map(token, Some);
+    let white_space = map(white_space, |str: &str| {
+        str.contains('\n')
+            .then(|| Token::new(str, TokenKind::WhiteSpace(WhiteSpace::NewLine)))
+    });
+    let source = input;
     let (input, output) = many0(alt((white_space, token)))(input)?;
+    let tokens = output.into_iter().flatten().map(|token| Token {
+        rest: Span::of(source, token.fragment).map_or(token.fragment, |span| &source[span.start..]),
+        ..token
+    });
     Ok((
         input,
         Tokens {
-            tokens: output.into_iter().flatten().collect(),
+            tokens: top_level_new_lines(tokens),
         },
     ))
 }
 
-fn white_space(input: Input) -> IResult<&str> {
+/// Drops new lines inside brackets, so only new lines that end a statement are left
+fn top_level_new_lines<'src>(tokens: impl Iterator<Item = Token<'src>>) -> Vec<Token<'src>> {
+    let mut depth = 0usize;
+    tokens
+        .filter(|token| match token.kind {
+            TokenKind::Operator(operator) if operator.is_opening_bracket() => {
+                depth += 1;
+                true
+            }
+            TokenKind::Operator(operator) if operator.is_closing_bracket() => {
+                depth = depth.saturating_sub(1);
+                true
+            }
+            TokenKind::WhiteSpace(WhiteSpace::NewLine) => depth == 0,
+            _ => true,
+        })
+        .collect()
+}
+
+fn white_space(input: Input<'_>) -> IResult<'_, &str> {
     take_while1(|c: char| matches!(c, ' ' | '\t' | '\r' | '\n'))(input)
 }
 
-fn token(input: Input) -> IResult<Token<'_>> {
+fn token(input: Input<'_>) -> IResult<'_, Token<'_>> {
     let invalid_token = map(take(1usize), |s: &str| Token::new(s, TokenKind::Invalid));
     alt((identifier, literal, operator, invalid_token))(input)
 }
 
-fn operator(input: Input) -> IResult<Token<'_>> {
-    // NOTE: potentially slow and invalid code with longer operator
-    let (input, output) = take(1usize)(input)?;
-    match Operator::recognize(output) {
-        Some(operator) => Ok((input, Token::new(output, TokenKind::Operator(operator)))),
-        None => Err(ErrorCase::Error(NomError::new(input, NomErrorKind::Verify))),
+/// Longest operator at the start of the input, so `<=` wins over `<`
+fn operator(input: Input<'_>) -> IResult<'_, Token<'_>> {
+    for len in (1..=Operator::max_len()).rev() {
+        let Ok((rest, output)) = take::<_, _, NomError<_>>(len)(input) else {
+            continue;
+        };
+        if let Some(operator) = Operator::recognize(output) {
+            return Ok((rest, Token::new(output, TokenKind::Operator(operator))));
+        }
     }
+    Err(ErrorCase::Error(NomError::new(input, NomErrorKind::Verify)))
 }
 
-fn identifier(input: Input) -> IResult<Token<'_>> {
-    let first_char_not_digit = |s: &str| {
-        let first_char = s.as_bytes()[0] as char;
-        !first_char.is_ascii_digit()
-    };
-    let char_alphanumeric_or_underscore = |c: char| c.is_ascii_alphanumeric() || c == '_';
+fn identifier(input: Input<'_>) -> IResult<'_, Token<'_>> {
+    let first_char_not_digit = |s: &str| !s.chars().next().unwrap().is_numeric();
     let identifier = verify(
-        take_while1(char_alphanumeric_or_underscore),
+        take_while1(|c: char| c.is_alphanumeric()),
         first_char_not_digit,
     );
-    map(identifier, |str: &str| {
+    let symbol = verify(take(1usize), |s: &str| {
+        Symbol::is_symbol_char(s.chars().next().unwrap())
+    });
+    map(alt((identifier, symbol, raw_identifier)), |str: &str| {
         Token::new(str, TokenKind::Identifier)
     })(input)
 }
 
-fn literal(input: Input) -> IResult<Token<'_>> {
-    alt((float, digits))(input)
+/// Anything between backticks, so `` `v_max` `` keeps its underscore instead of being a subscript
+fn raw_identifier(input: Input<'_>) -> IResult<'_, &str> {
+    delimited(char('`'), take_while1(|c: char| c != '`'), char('`'))(input)
+}
+
+fn literal(input: Input<'_>) -> IResult<'_, Token<'_>> {
+    alt((radix_digits, scientific, float, digits, string))(input)
 }
 
-fn digits(input: Input) -> IResult<Token<'_>> {
-    map(take_while1(|c: char| c.is_ascii_digit()), |str: &str| {
+fn string(input: Input<'_>) -> IResult<'_, Token<'_>> {
+    let string = recognize(delimited(
+        char('"'),
+        take_while(|c: char| c != '"'),
+        char('"'),
+    ));
+    map(string, |str: &str| {
+        Token::new(str, TokenKind::Literal(Literal::String))
+    })(input)
+}
+
+/// Digits of `radix`, optionally seperated by single underscores like `1_000_000`.
+/// Must start and end with a digit, so `1__000` and `1_000_` aren't numbers.
+fn seperated_digits<'a>(radix: u32) -> impl FnMut(Input<'a>) -> IResult<'a, &'a str> {
+    let digits = move |input: Input<'a>| take_while1(move |c: char| c.is_digit(radix))(input);
+    terminated(
+        recognize(pair(digits, many0(preceded(opt(char('_')), digits)))),
+        not(char('_')),
+    )
+}
+
+fn digits(input: Input<'_>) -> IResult<'_, Token<'_>> {
+    map(seperated_digits(10), |str: &str| {
         Token::new(str, TokenKind::Literal(Literal::Digits))
     })(input)
 }
 
-fn dots_and_digits(input: Input) -> IResult<&str> {
-    take_while1(|c: char| c.is_ascii_digit() || c == '.')(input)
+fn radix_digits(input: Input<'_>) -> IResult<'_, Token<'_>> {
+    let radix = |prefix, radix, kind| {
+        map(
+            recognize(pair(tag(prefix), seperated_digits(radix))),
+            move |str: &str| Token::new(str, TokenKind::Literal(kind)),
+        )
+    };
+    alt((
+        radix("0x", 16, Literal::Hexadecimal),
+        radix("0o", 8, Literal::Octal),
+        radix("0b", 2, Literal::Binary),
+    ))(input)
 }
 
-fn float(input: Input) -> IResult<Token<'_>> {
-    let float = verify(dots_and_digits, |s: &str| {
-        let mut dot_count = 0usize;
-        for char in s.chars() {
-            if char == '.' {
-                dot_count += 1;
-                if dot_count > 1 {
-                    return false;
-                }
-            }
-        }
-        dot_count == 1
-    });
-    map(float, |float| {
+/// `23.13` or `.13`
+fn float_str(input: Input<'_>) -> IResult<'_, &str> {
+    recognize(tuple((
+        opt(seperated_digits(10)),
+        char('.'),
+        seperated_digits(10),
+    )))(input)
+}
+
+fn float(input: Input<'_>) -> IResult<'_, Token<'_>> {
+    map(float_str, |float| {
         Token::new(float, TokenKind::Literal(Literal::Float))
     })(input)
 }
+
+/// `6.022e23`, `1e-9` or `.5E+3`
+fn scientific(input: Input<'_>) -> IResult<'_, Token<'_>> {
+    let significand = alt((float_str, seperated_digits(10)));
+    let exponent = tuple((one_of("eE"), opt(one_of("+-")), seperated_digits(10)));
+    map(recognize(pair(significand, exponent)), |str: &str| {
+        Token::new(str, TokenKind::Literal(Literal::Scientific))
+    })(input)
+}
@@ -4,45 +4,139 @@ use super::*;
 fn test_identifier() {
     assert_eq!(
         identifier("hello guys"),
-        Ok((" guys", Identifier { str: "hello" }))
+        Ok((" guys", Token::new("hello", TokenKind::Identifier)))
     );
     assert_eq!(
-        identifier("_he_ll23o_ guys"),
-        Ok((" guys", Identifier { str: "_he_ll23o_" }))
+        identifier("x_1"),
+        Ok(("_1", Token::new("x", TokenKind::Identifier)))
+    );
+    assert_eq!(
+        identifier("`_he_ll23o_` guys"),
+        Ok((" guys", Token::new("_he_ll23o_", TokenKind::Identifier)))
     );
     assert_eq!(
         identifier("2mama_sd guys"),
         Err(ErrorCase::Error(NomError::new(
             "2mama_sd guys",
-            NomErrorKind::Verify
+            NomErrorKind::Char
         )))
     );
 }
 
+#[test]
+fn test_unicode_identifier() {
+    assert_eq!(
+        identifier("θ + 1"),
+        Ok((" + 1", Token::new("θ", TokenKind::Identifier)))
+    );
+    assert_eq!(
+        identifier("Δx"),
+        Ok(("", Token::new("Δx", TokenKind::Identifier)))
+    );
+    assert_eq!(
+        identifier("∞)"),
+        Ok((")", Token::new("∞", TokenKind::Identifier)))
+    );
+}
+
 #[test]
 fn test_float() {
+    let float_kind = TokenKind::Literal(Literal::Float);
     assert_eq!(
         float("23.13 yaya"),
+        Ok((" yaya", Token::new("23.13", float_kind)))
+    );
+    assert_eq!(float(".13 no"), Ok((" no", Token::new(".13", float_kind))));
+    assert!(float("34. haha").is_err());
+}
+
+#[test]
+fn test_literal() {
+    let literal_token = |str, kind| Ok(("", Token::new(str, TokenKind::Literal(kind))));
+    assert_eq!(
+        literal("1_000_000"),
+        literal_token("1_000_000", Literal::Digits)
+    );
+    assert_eq!(literal("1e-9"), literal_token("1e-9", Literal::Scientific));
+    assert_eq!(
+        literal("6.022e23"),
+        literal_token("6.022e23", Literal::Scientific)
+    );
+    assert_eq!(
+        literal(".5E+3"),
+        literal_token(".5E+3", Literal::Scientific)
+    );
+    assert_eq!(
+        literal("0xFF_FF"),
+        literal_token("0xFF_FF", Literal::Hexadecimal)
+    );
+    assert_eq!(literal("0o17"), literal_token("0o17", Literal::Octal));
+    assert_eq!(literal("0b1010"), literal_token("0b1010", Literal::Binary));
+    assert_eq!(
+        literal("\"factor\" x"),
         Ok((
-            " yaya",
-            Float {
-                left_to_dot: Digits { str: "23" },
-                right_to_dot: Digits { str: "13" }
-            }
+            " x",
+            Token::new("\"factor\"", TokenKind::Literal(Literal::String))
         ))
     );
+    assert!(literal("1_000_ x").is_err());
+    assert!(literal("1__000").is_err());
     assert_eq!(
-        float(".13 no"),
-        Err(ErrorCase::Error(NomError::new(
-            ".13 no",
-            NomErrorKind::TakeWhile1
-        )))
+        literal("2e x"),
+        Ok(("e x", Token::new("2", TokenKind::Literal(Literal::Digits))))
     );
+}
+
+#[test]
+fn test_operator() {
+    let operator_token = |str, operator| Token::new(str, TokenKind::Operator(operator));
     assert_eq!(
-        identifier("34. haha"),
-        Err(ErrorCase::Error(NomError::new(
-            "34. haha",
-            NomErrorKind::Verify
-        )))
+        operator("<= 1"),
+        Ok((" 1", operator_token("<=", Operator::LeftAngleBracketEqual)))
+    );
+    assert_eq!(
+        operator("<1"),
+        Ok(("1", operator_token("<", Operator::LeftAngleBracket)))
+    );
+    assert_eq!(
+        operator("~=x"),
+        Ok(("x", operator_token("~=", Operator::TildeEqual)))
+    );
+    assert_eq!(
+        operator("!=x"),
+        Ok(("x", operator_token("!=", Operator::BangEqual)))
+    );
+    assert_eq!(
+        operator("**2"),
+        Ok(("2", operator_token("**", Operator::DoubleAsterisk)))
+    );
+    assert_eq!(
+        operator("=>x"),
+        Ok(("x", operator_token("=>", Operator::RightThickArrow)))
+    );
+    assert_eq!(
+        operator("+/-x"),
+        Ok(("x", operator_token("+/-", Operator::PlusSlashMinus)))
+    );
+    assert!(operator("~x").is_err());
+}
+
+#[test]
+fn test_text_to() {
+    let (_, tokens) = tokens("(x + 1)^2\n  = x^2").unwrap();
+    let tokens = tokens.as_slice();
+    assert_eq!(tokens[0].text_to(&tokens[6]), "(x + 1)^2");
+    assert_eq!(
+        tokens[1].text_to(&tokens[tokens.len() - 1]),
+        "x + 1)^2\n  = x^2"
+    );
+    assert_eq!(tokens[3].text_to(&tokens[1]), "1");
+    assert_eq!(
+        Token::new("x", TokenKind::Identifier).text_to(&tokens[1]),
+        "x"
     );
+    // Where a token is doesn't change what it is
+    assert_eq!(tokens[1], tokens[tokens.len() - 3]);
+    assert_eq!(tokens[1], Token::new("x", TokenKind::Identifier));
+    assert!(!format!("{:?}", tokens[0]).contains("x + 1"));
 }
@@ -1,2 +1,17 @@
 pub mod ast;
+pub mod check;
+pub mod desmos;
+pub mod diagnostic;
+pub mod differentiate;
+pub mod eval;
+pub mod latex;
 pub mod lexer;
+pub mod resolve;
+pub mod simplify;
+pub mod solve;
+pub mod steps;
+pub mod symbol;
+pub mod types;
+pub mod unicode;
+pub mod units;
+pub mod wolfram;
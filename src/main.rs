@@ -0,0 +1,150 @@
+//! `hometex simplify [--latex] [file]`, `hometex steps [file]` or `hometex check [file]`,
+//! reading standard input when there's no file
+
+use std::{
+    env, fs,
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use anyhow::{anyhow, bail, Context};
+use hometex::{
+    ast::{self, Statement},
+    diagnostic::Severity,
+    eval::Environment,
+    latex::Latex,
+    lexer,
+    types::Types,
+    unicode::Unicode,
+};
+
+const USAGE: &str = "usage: hometex simplify [--latex] [file]\n       hometex steps [file]\n       hometex check [file]";
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1).collect()) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{error:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> anyhow::Result<String> {
+    let mut args = args.into_iter();
+    let command = args.next().ok_or_else(|| anyhow!(USAGE))?;
+    if !["simplify", "steps", "check"].contains(&command.as_str()) {
+        bail!(USAGE);
+    }
+    let mut latex = false;
+    let mut path = None;
+    for arg in args {
+        match arg.as_str() {
+            "--latex" if command == "simplify" => latex = true,
+            _ if arg.starts_with("--") || path.is_some() => bail!(USAGE),
+            _ => path = Some(arg),
+        }
+    }
+
+    let source = match path {
+        Some(path) => fs::read_to_string(&path).with_context(|| format!("couldn't read {path}"))?,
+        None => {
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source)?;
+            source
+        }
+    };
+    output(&command, latex, &source)
+}
+
+/// What `command` prints for `source`
+fn output(command: &str, latex: bool, source: &str) -> anyhow::Result<String> {
+    let tokens = lexer::parse(source).map_err(|error| anyhow!("couldn't lex: {error}"))?;
+    // Formatted now, since the error points into `tokens`
+    let ast = ast::parse(&tokens).map_err(|error| anyhow!("{error:#}"))?;
+    // Names that can't work stop everything, the rest only warn
+    let (errors, warnings): (Vec<_>, Vec<_>) = ast
+        .resolve()
+        .diagnostics
+        .into_iter()
+        .partition(|diagnostic| diagnostic.severity == Severity::Error);
+    for warning in warnings {
+        eprintln!("{}", warning.render(source));
+    }
+    if !errors.is_empty() {
+        let errors: Vec<String> = errors.iter().map(|error| error.render(source)).collect();
+        bail!(errors.join("\n"));
+    }
+    if command == "check" {
+        // Types and units that don't match, then the first wrong step of each derivation
+        let mut types = Types::default();
+        let mut environment = Environment::new();
+        let mistakes: Vec<String> = ast
+            .statements
+            .iter()
+            .zip(&ast.sources)
+            .filter_map(|(statement, statement_source)| {
+                let types = statement.check_types(statement_source, &mut types).err();
+                types
+                    .or_else(|| statement.check_units(statement_source).err())
+                    .or_else(|| match statement {
+                        Statement::Derivation(derivation) => derivation.check(&environment).err(),
+                        Statement::Definition(_) | Statement::FunctionDefinition(_) => {
+                            // Known from here on. One that can't be worked out stays unknown.
+                            environment.run(statement).ok();
+                            None
+                        }
+                        _ => None,
+                    })
+            })
+            .map(|diagnostic| diagnostic.render(source))
+            .collect();
+        if !mistakes.is_empty() {
+            bail!(mistakes.join("\n"));
+        }
+        return Ok("every step holds".to_string());
+    }
+    if command == "steps" {
+        // The working out for each expression and equation, as an `align*` block
+        return Ok(ast
+            .steps()
+            .iter()
+            .map(|derivation| Latex::new(derivation).to_string())
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+
+    let ast = ast.simplify();
+    Ok(if latex {
+        Latex::new(&ast).to_string()
+    } else {
+        Unicode(&ast).to_string()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_errors() {
+        for source in ["2 +", "f(x) = = 2", "x^2 sin(x) +", "(1, 2"] {
+            let error = output("simplify", false, source).unwrap_err();
+            assert!(format!("{error:#}").contains("expecting"), "{source}");
+        }
+    }
+
+    #[test]
+    fn simplify() {
+        assert_eq!(output("simplify", false, "2x + 3x").unwrap(), "5x");
+        assert_eq!(
+            output("check", false, "(x+1)^2\n= x^2 + 1")
+                .unwrap_err()
+                .to_string(),
+            "2:1: error: this step doesn't hold at x = -2.125"
+        );
+    }
+}
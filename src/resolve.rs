@@ -0,0 +1,524 @@
+//! Name resolution. Works out what every name refers to before anything is evaluated or written
+//! out, and finds calls that can't work, like `sin(1, 2)` or a misspelled `sqr(x)`. Names are
+//! resolved in the order they're written, so only what's defined above is known.
+
+use std::{collections::HashMap, slice};
+
+use crate::{
+    ast::*,
+    diagnostic::Diagnostic,
+    eval::{self, canonical_name, Arity, Environment},
+};
+
+/// Calls the parser turns into their own node when they have the right inputs, like `sum`, and
+/// the ones the simplifier works out itself, like `to` for converting units. `solve` takes an
+/// equation and a variable, or a system and its variables.
+const SPECIAL_FORMS: &[SpecialForm] = &[
+    SpecialForm::new("sum", Arity::Exactly(4), false),
+    SpecialForm::new("prod", Arity::Exactly(4), false),
+    SpecialForm::new("lim", Arity::Exactly(3), false),
+    SpecialForm::new("int", Arity::Either(2, 4), false),
+    SpecialForm::new("diff", Arity::Exactly(2), false),
+    SpecialForm::new("pd", Arity::AtLeast(2), false),
+    SpecialForm::new("norm", Arity::Exactly(1), false),
+    SpecialForm::new("to", Arity::Exactly(2), true),
+    SpecialForm::new("solve", Arity::AtLeast(2), false),
+];
+
+/// A call with its own meaning, from [`SPECIAL_FORMS`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SpecialForm {
+    pub(crate) name: &'static str,
+    pub(crate) arity: Arity,
+    /// Whether it's left as a call that takes numbers and gives a number, like `to`, rather than
+    /// becoming its own node or taking an equation like `solve`
+    pub(crate) numeric: bool,
+}
+
+impl SpecialForm {
+    const fn new(name: &'static str, arity: Arity, numeric: bool) -> Self {
+        SpecialForm {
+            name,
+            arity,
+            numeric,
+        }
+    }
+}
+
+/// The special form called `name`, like `sum` or `to`
+pub(crate) fn special_form(name: &str) -> Option<&'static SpecialForm> {
+    SPECIAL_FORMS.iter().find(|form| form.name == name)
+}
+
+/// What a name refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Resolution {
+    /// A builtin function like `sqrt`, or a constant like `pi`
+    Builtin,
+    /// Something defined with `let`, or a function like `f(x) = x^2`
+    Definition,
+    /// A parameter of the function it's in, or the variable of a `sum`, integral, limit or set
+    Bound,
+    /// A name nothing gives a meaning to, like the `x` of `x^2 - 4 = 0`
+    Free,
+}
+
+/// What [`Ast::resolve`] found
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved<'src> {
+    /// Every name where it's written, with what it refers to
+    pub names: Vec<(&'src str, Resolution)>,
+    /// Calls that can't work are errors. Unknown functions, which may be meant as symbols like
+    /// the `f` of `f(x) + f(x) = 2f(x)`, and shadowed definitions are warnings.
+    pub diagnostics: Vec<Diagnostic<'src>>,
+}
+
+impl<'src> Ast<'src> {
+    pub fn resolve(&self) -> Resolved<'src> {
+        let mut resolver = Resolver::default();
+        for statement in &self.statements {
+            resolver.statement(statement);
+        }
+        Resolved {
+            names: resolver.names,
+            diagnostics: resolver.diagnostics,
+        }
+    }
+}
+
+/// A name given a meaning by the user
+enum Defined {
+    Variable,
+    /// With this many parameters
+    Function(usize),
+}
+
+#[derive(Default)]
+struct Resolver<'src> {
+    definitions: HashMap<String, Defined>,
+    /// Names bound around what's being resolved, innermost last
+    bound: Vec<String>,
+    names: Vec<(&'src str, Resolution)>,
+    diagnostics: Vec<Diagnostic<'src>>,
+}
+
+impl<'src> Resolver<'src> {
+    fn statement(&mut self, statement: &Statement<'src>) {
+        match statement {
+            Statement::Definition(definition) => {
+                self.expression(&definition.value);
+                match &definition.index {
+                    // Each subscript is its own name, which is only looked up when evaluating
+                    Some(_) => self
+                        .names
+                        .push((definition.identifier.text, Resolution::Definition)),
+                    None => self.define(&definition.identifier, Defined::Variable),
+                }
+            }
+            Statement::FunctionDefinition(function) => {
+                // Before the body, so it can call itself
+                let parameters = function.parameters.len();
+                self.define(&function.identifier, Defined::Function(parameters));
+                self.bind(&function.parameters, |resolver| {
+                    resolver.expression(&function.body)
+                });
+            }
+            Statement::Equation(relation) => self.relation(relation),
+            Statement::Expression(expression) => self.expression(expression),
+            Statement::Derivation(derivation) => {
+                for step in &derivation.steps {
+                    self.expression(&step.expression);
+                }
+            }
+        }
+    }
+
+    fn define(&mut self, identifier: &Identifier<'src>, defined: Defined) {
+        let name = canonical_name(identifier.text);
+        let text = identifier.text;
+        if self.definitions.contains_key(&name) {
+            let message = format!("`{text}` is already defined, this replaces it");
+            self.diagnostics.push(Diagnostic::warning(text, message));
+        } else if is_builtin(identifier) {
+            let message = format!("`{text}` shadows the builtin `{text}`");
+            self.diagnostics.push(Diagnostic::warning(text, message));
+        }
+        self.names.push((text, Resolution::Definition));
+        self.definitions.insert(name, defined);
+    }
+
+    /// Resolves `within` with `variables` bound
+    fn bind(&mut self, variables: &[Identifier<'src>], within: impl FnOnce(&mut Self)) {
+        for variable in variables {
+            let name = canonical_name(variable.text);
+            if self.definitions.contains_key(&name) {
+                let text = variable.text;
+                let message = format!("`{text}` shadows the definition of `{text}`");
+                self.diagnostics.push(Diagnostic::warning(text, message));
+            }
+            self.names.push((variable.text, Resolution::Bound));
+            self.bound.push(name);
+        }
+        within(self);
+        self.bound.truncate(self.bound.len() - variables.len());
+    }
+
+    fn variable(&mut self, identifier: &Identifier<'src>) {
+        let name = canonical_name(identifier.text);
+        let resolution = if self.bound.contains(&name) {
+            Resolution::Bound
+        } else if self.definitions.contains_key(&name) {
+            Resolution::Definition
+        } else if is_constant(identifier) {
+            Resolution::Builtin
+        } else {
+            Resolution::Free
+        };
+        self.names.push((identifier.text, resolution));
+    }
+
+    /// A call of `identifier` with `inputs` inputs, if it's known how many
+    fn function(&mut self, identifier: &Identifier<'src>, inputs: Option<usize>) {
+        let text = identifier.text;
+        let (resolution, arity) = match self.definitions.get(&canonical_name(text)) {
+            Some(Defined::Function(parameters)) => {
+                (Resolution::Definition, Arity::Exactly(*parameters))
+            }
+            Some(Defined::Variable) => {
+                let message = format!("`{text}` is a variable, not a function");
+                self.diagnostics.push(Diagnostic::error(text, message));
+                self.names.push((text, Resolution::Definition));
+                return;
+            }
+            None => match builtin_arity(text) {
+                Some(arity) => (Resolution::Builtin, arity),
+                None => {
+                    let unknown = eval::Error::UnknownFunction(text.to_string());
+                    let message = match self.suggestion(text) {
+                        Some(suggestion) => format!("{unknown}, did you mean `{suggestion}`?"),
+                        None => unknown.to_string(),
+                    };
+                    self.diagnostics.push(Diagnostic::warning(text, message));
+                    self.names.push((text, Resolution::Free));
+                    return;
+                }
+            },
+        };
+        self.names.push((text, resolution));
+        if let Some(found) = inputs.filter(|found| !arity.allows(*found)) {
+            let wrong = eval::Error::WrongArity {
+                name: text.to_string(),
+                expected: arity,
+                found,
+            };
+            self.diagnostics
+                .push(Diagnostic::error(text, wrong.to_string()));
+        }
+    }
+
+    /// The known function closest to `name`, when it's close enough to be a typo of it
+    fn suggestion(&self, name: &str) -> Option<String> {
+        let allowed = match name.chars().count() {
+            0..=2 => return None,
+            3..=5 => 1,
+            _ => 2,
+        };
+        let special_forms = SPECIAL_FORMS.iter().map(|form| form.name);
+        let builtins = eval::BUILTINS.iter().map(|(builtin, _)| *builtin);
+        let functions = self
+            .definitions
+            .iter()
+            .filter(|(_, defined)| matches!(defined, Defined::Function(_)))
+            .map(|(name, _)| name.as_str());
+        builtins
+            .chain(special_forms)
+            .chain(functions)
+            .map(|known| (distance(name, known), known))
+            .filter(|(distance, _)| *distance <= allowed)
+            .min()
+            .map(|(_, known)| known.to_string())
+    }
+
+    fn relation(&mut self, relation: &Relation<'src>) {
+        self.expression(&relation.first);
+        for (_, right) in &relation.rest {
+            self.expression(right);
+        }
+    }
+
+    fn expressions(&mut self, expressions: &[Expression<'src>]) {
+        for expression in expressions {
+            self.expression(expression);
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression<'src>) {
+        match expression {
+            Expression::Literal(_) | Expression::Number(_) | Expression::NumberSet(_) => {}
+            Expression::Quantity(quantity) => {
+                self.expression(&quantity.value);
+                // `2 s` is two seconds even with `let s = 5`, which is easy to trip over
+                for factor in &quantity.unit.factors {
+                    let symbol = factor.symbol;
+                    if let Some(Defined::Variable) = self.definitions.get(&canonical_name(symbol)) {
+                        let message = format!(
+                            "`{symbol}` is the unit here, not the variable `{symbol}`, write \
+                             `*{symbol}` to multiply by the variable"
+                        );
+                        self.diagnostics.push(Diagnostic::warning(symbol, message));
+                    }
+                }
+            }
+            Expression::Variable(variable) => self.variable(&variable.identifier),
+            Expression::FunctionCall(call) => {
+                self.function(&call.identifier, Some(call.inputs.len()));
+                self.expressions(&call.inputs);
+            }
+            Expression::Subscript(subscript) => {
+                self.expression(&subscript.base);
+                self.expression(&subscript.index);
+            }
+            Expression::Unary(unary) => self.expression(&unary.operand),
+            Expression::Binary(binary) => {
+                self.expression(&binary.left);
+                self.expression(&binary.right);
+            }
+            Expression::Relation(relation) => self.relation(relation),
+            Expression::BigOperator(big_operator) => {
+                self.expression(&big_operator.from);
+                self.expression(&big_operator.to);
+                self.bind(slice::from_ref(&big_operator.index), |resolver| {
+                    resolver.expression(&big_operator.body)
+                });
+            }
+            Expression::Integral(integral) => {
+                if let Some((lower, upper)) = &integral.bounds {
+                    self.expression(lower);
+                    self.expression(upper);
+                }
+                self.bind(slice::from_ref(&integral.variable), |resolver| {
+                    resolver.expression(&integral.integrand)
+                });
+            }
+            Expression::Limit(limit) => {
+                self.expression(&limit.approaching);
+                self.bind(slice::from_ref(&limit.variable), |resolver| {
+                    resolver.expression(&limit.body)
+                });
+            }
+            // Differentiating by `x` is at whatever `x` is, so it isn't bound
+            Expression::Derivative(Derivative::Leibniz { variable, body }) => {
+                self.variable(variable);
+                self.expression(body);
+            }
+            Expression::Derivative(Derivative::Partial { body, variables }) => {
+                for variable in variables {
+                    self.variable(variable);
+                }
+                self.expression(body);
+            }
+            Expression::Derivative(Derivative::Prime {
+                function, inputs, ..
+            }) => {
+                self.function(function, inputs.as_ref().map(Vec::len));
+                self.expressions(inputs.as_deref().unwrap_or_default());
+            }
+            Expression::Vector(vector) => self.expressions(&vector.elements),
+            Expression::Matrix(matrix) => {
+                for row in &matrix.rows {
+                    self.expressions(row);
+                }
+            }
+            Expression::Piecewise(piecewise) => {
+                for arm in &piecewise.arms {
+                    self.expression(&arm.value);
+                    self.expression(&arm.condition);
+                }
+                if let Some(otherwise) = &piecewise.otherwise {
+                    self.expression(otherwise);
+                }
+            }
+            Expression::Set(set) => self.expressions(&set.elements),
+            Expression::SetBuilder(set_builder) => {
+                if let Some(domain) = &set_builder.domain {
+                    self.expression(domain);
+                }
+                self.bind(slice::from_ref(&set_builder.variable), |resolver| {
+                    resolver.expression(&set_builder.condition)
+                });
+            }
+            Expression::Interval(interval) => {
+                self.expression(&interval.lower);
+                self.expression(&interval.upper);
+            }
+            Expression::Delimited(delimited) => self.expression(&delimited.inner),
+            Expression::Group(group) => self.expression(&group.inner),
+            Expression::List(list) => self.expressions(&list.elements),
+        }
+    }
+}
+
+fn builtin_arity(name: &str) -> Option<Arity> {
+    eval::builtin_arity(name).or(special_form(name).map(|form| form.arity))
+}
+
+/// Like `pi`, which every environment knows
+fn is_constant(identifier: &Identifier<'_>) -> bool {
+    let variable = Expression::Variable(Variable {
+        identifier: identifier.clone(),
+    });
+    Environment::new().evaluate(&variable).is_ok()
+}
+
+fn is_builtin(identifier: &Identifier<'_>) -> bool {
+    is_constant(identifier) || builtin_arity(identifier.text).is_some()
+}
+
+/// Edits it takes to turn one into the other, counting inserting, removing or changing a letter,
+/// or swapping two next to each other
+fn distance(from: &str, to: &str) -> usize {
+    let (from, to): (Vec<char>, Vec<char>) = (from.chars().collect(), to.chars().collect());
+    // Distances to each start of `to`, from the start of `from` up to the last letter and the one
+    // before it
+    let mut before: Vec<usize> = vec![0; to.len() + 1];
+    let mut previous: Vec<usize> = (0..=to.len()).collect();
+    for i in 1..=from.len() {
+        let mut row = vec![i; to.len() + 1];
+        for j in 1..=to.len() {
+            let changed = previous[j - 1] + usize::from(from[i - 1] != to[j - 1]);
+            row[j] = changed.min(previous[j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && from[i - 1] == to[j - 2] && from[i - 2] == to[j - 1] {
+                row[j] = row[j].min(before[j - 2] + 1);
+            }
+        }
+        (before, previous) = (previous, row);
+    }
+    previous[to.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn resolve(str: &str) -> Resolved<'_> {
+        let tokens = crate::lexer::parse(str).unwrap();
+        crate::ast::parse(&tokens).unwrap().resolve()
+    }
+
+    fn diagnostics(str: &str) -> Vec<String> {
+        resolve(str)
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(str))
+            .collect()
+    }
+
+    #[test]
+    fn names() {
+        use Resolution::*;
+        assert_eq!(
+            resolve("let g = 9.81\nf(t) = g*t^2 + sin(pi*t) + y\nsum(k, 1, 3, f(k))").names,
+            [
+                ("g", Definition),
+                ("f", Definition),
+                ("t", Bound),
+                ("g", Definition),
+                ("t", Bound),
+                ("sin", Builtin),
+                ("pi", Builtin),
+                ("t", Bound),
+                ("y", Free),
+                ("k", Bound),
+                ("f", Definition),
+                ("k", Bound),
+            ]
+        );
+        assert_eq!(
+            resolve("int(x^2, x, 0, x)").names,
+            [("x", Free), ("x", Bound), ("x", Bound)]
+        );
+        assert_eq!(
+            resolve("solve(x^2 = 4, x)").names,
+            [("solve", Builtin), ("x", Free), ("x", Free)]
+        );
+        assert!(resolve("f(x) + f(x) = 2f(x)\n|x| + sqrt(x)")
+            .diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == crate::diagnostic::Severity::Warning));
+    }
+
+    #[test]
+    fn arity() {
+        assert_eq!(
+            diagnostics("sin(1, 2) + mod(1)"),
+            [
+                "1:1: error: `sin` takes 1 input, not 2",
+                "1:13: error: `mod` takes 2 inputs, not 1",
+            ]
+        );
+        assert_eq!(
+            diagnostics("f(x, y) = x*y\nf(1)"),
+            ["2:1: error: `f` takes 2 inputs, not 1"]
+        );
+        assert_eq!(
+            diagnostics("sum(k, 1, 10)\nint(x, x, 1)"),
+            [
+                "1:1: error: `sum` takes 4 inputs, not 3",
+                "2:1: error: `int` takes 2 or 4 inputs, not 3",
+            ]
+        );
+        assert_eq!(
+            diagnostics("solve(x^2 = 4, x)\nsolve({x + y = 3, x - y = 1}, x, y)\nsolve(x = 1)"),
+            ["3:1: error: `solve` takes at least 2 inputs, not 1"]
+        );
+        assert_eq!(
+            diagnostics("let a = 2; a(3)"),
+            ["1:12: error: `a` is a variable, not a function"]
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(
+            diagnostics("sqr(2) + sine(x) + fnord(x) + g(x)"),
+            [
+                "1:1: warning: `sqr` is not a known function, did you mean `sqrt`?",
+                "1:10: warning: `sine` is not a known function, did you mean `sin`?",
+                "1:20: warning: `fnord` is not a known function",
+                "1:31: warning: `g` is not a known function",
+            ]
+        );
+        assert_eq!(
+            diagnostics("area(w, h) = w*h/2\narae(1, 2)"),
+            ["2:1: warning: `arae` is not a known function, did you mean `area`?"]
+        );
+    }
+
+    #[test]
+    fn shadowing() {
+        assert_eq!(
+            diagnostics("let x = 1\nlet x = 2\nf(x) = x^2\nlet pi = 3"),
+            [
+                "2:5: warning: `x` is already defined, this replaces it",
+                "3:3: warning: `x` shadows the definition of `x`",
+                "4:5: warning: `pi` shadows the builtin `pi`",
+            ]
+        );
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(
+            diagnostics("let s = 5; 2 s + 3*s + 4 m"),
+            ["1:14: warning: `s` is the unit here, not the variable `s`, write `*s` to multiply by the variable"]
+        );
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(super::distance("sqr", "sqrt"), 1);
+        assert_eq!(super::distance("kitten", "sitting"), 3);
+        assert_eq!(super::distance("", "ln"), 2);
+        assert_eq!(super::distance("arae", "area"), 1);
+    }
+}
@@ -0,0 +1,910 @@
+//! Symbolic simplification. Folds constants, collects like terms (`2x + 3x` to `5x`), cancels
+//! common factors (`x y / x` to `y`) and drops identities like `x^0` and `x*1`.
+
+use num_bigint::BigInt;
+
+use crate::{
+    ast::*,
+    eval::{Complex, Environment, Number, Value},
+    solve, units,
+};
+
+impl<'src> Ast<'src> {
+    pub fn simplify(&self) -> Ast<'src> {
+        self.simplify_with_unit(Some("i"))
+    }
+
+    /// Like [`simplify`](Ast::simplify), working out powers and products of `imaginary_unit`,
+    /// or of nothing when it's [`None`], to match
+    /// [`Environment::set_imaginary_unit`](crate::eval::Environment::set_imaginary_unit). Once
+    /// a statement defines the unit's name, like `let i = 2`, it's an ordinary name from then on.
+    pub fn simplify_with_unit(&self, mut imaginary_unit: Option<&'src str>) -> Ast<'src> {
+        let mut statements = Vec::new();
+        for statement in &self.statements {
+            statements.push(statement.simplify_with_unit(imaginary_unit));
+            if imaginary_unit.is_some() && statement.defined_name() == imaginary_unit {
+                imaginary_unit = None;
+            }
+        }
+        Ast {
+            statements,
+            sources: self.sources.clone(),
+        }
+    }
+}
+
+impl<'src> Statement<'src> {
+    /// Simplifies every expression in the statement, keeping the statement itself
+    pub fn simplify(&self) -> Statement<'src> {
+        self.simplify_with_unit(Some("i"))
+    }
+
+    /// [`simplify`](Statement::simplify), with `imaginary_unit` as in
+    /// [`Ast::simplify_with_unit`]
+    fn simplify_with_unit(&self, imaginary_unit: Option<&'src str>) -> Statement<'src> {
+        let simplify = |expression: &Expression<'src>| {
+            expression.simplify_in(&mut Rules::new(usize::MAX), imaginary_unit)
+        };
+        match self {
+            Statement::Definition(definition) => Statement::Definition(Definition {
+                value: simplify(&definition.value),
+                ..definition.clone()
+            }),
+            Statement::FunctionDefinition(function_definition) => {
+                // A parameter named like the unit, as in `f(i) = i^2`, hides it in the body
+                let imaginary_unit = function_definition
+                    .parameters
+                    .iter()
+                    .fold(imaginary_unit, |unit, parameter| unbound(unit, parameter));
+                Statement::FunctionDefinition(FunctionDefinition {
+                    body: function_definition
+                        .body
+                        .simplify_in(&mut Rules::new(usize::MAX), imaginary_unit),
+                    ..function_definition.clone()
+                })
+            }
+            Statement::Equation(relation) => Statement::Equation(
+                relation.simplify_in(&mut Rules::new(usize::MAX), imaginary_unit),
+            ),
+            Statement::Expression(expression) => Statement::Expression(simplify(expression)),
+            Statement::Derivation(derivation) => Statement::Derivation(Derivation {
+                steps: derivation
+                    .steps
+                    .iter()
+                    .map(|step| Step {
+                        expression: simplify(&step.expression),
+                        ..step.clone()
+                    })
+                    .collect(),
+            }),
+        }
+    }
+}
+
+impl<'src> Relation<'src> {
+    /// Simplifies each side on its own, without moving anything across
+    pub fn simplify(&self) -> Relation<'src> {
+        self.simplify_in(&mut Rules::new(usize::MAX), Some("i"))
+    }
+
+    fn simplify_in(&self, rules: &mut Rules, imaginary_unit: Option<&'src str>) -> Relation<'src> {
+        Relation {
+            first: Box::new(self.first.simplify_in(rules, imaginary_unit)),
+            rest: self
+                .rest
+                .iter()
+                .map(|(operator, right)| (*operator, right.simplify_in(rules, imaginary_unit)))
+                .collect(),
+        }
+    }
+}
+
+impl<'src> Expression<'src> {
+    /// An equal expression that's simpler to read. Brackets are dropped, the backends put back
+    /// the ones that are needed.
+    pub fn simplify(&self) -> Expression<'src> {
+        self.simplify_noting(&mut Rules::new(usize::MAX))
+    }
+
+    /// [`simplify`](Expression::simplify), using no more than the limit of `rules` and noting
+    /// each one it uses. What's left once the limit is reached stays as it is.
+    pub(crate) fn simplify_noting(&self, rules: &mut Rules) -> Expression<'src> {
+        self.simplify_in(rules, Some("i"))
+    }
+
+    /// [`simplify_noting`](Expression::simplify_noting), where `imaginary_unit` is the name of
+    /// the imaginary unit, or [`None`] when there isn't one or a parameter, definition, sum or
+    /// integral around the expression gives that name another meaning
+    fn simplify_in(
+        &self,
+        rules: &mut Rules,
+        imaginary_unit: Option<&'src str>,
+    ) -> Expression<'src> {
+        let mut simplify =
+            |expression: &Expression<'src>| Box::new(expression.simplify_in(rules, imaginary_unit));
+        let expression = match self {
+            Expression::Literal(Literal::Whole(whole)) => {
+                return Expression::Number(BigInt::from(whole.value.clone()).into())
+            }
+            Expression::Group(group) => return group.inner.simplify_in(rules, imaginary_unit),
+            Expression::Unary(Unary {
+                operator: UnaryOperator::Negate,
+                operand,
+            }) => {
+                let negated = Expression::Unary(Unary {
+                    operator: UnaryOperator::Negate,
+                    operand: simplify(operand),
+                });
+                return combine(negated, rules);
+            }
+            Expression::Binary(binary) if binary.operator.is_arithmetic() => {
+                let binary = Binary {
+                    operator: binary.operator,
+                    left: simplify(&binary.left),
+                    right: simplify(&binary.right),
+                };
+                // Quantities are multiplied out unit by unit, like `3 m * 2 = 6 m`
+                if let Some(product) =
+                    units::multiply(&binary).filter(|_| rules.apply("multiply units"))
+                {
+                    return product;
+                }
+                let binary = Expression::Binary(binary);
+                let combined = combine(binary, rules);
+                // Powers and products of `i` are worked out, like `i^2 = -1`
+                return match imaginary_unit
+                    .is_some_and(|text| combined.mentions(&Identifier { text }))
+                {
+                    true => fold(combined, rules, imaginary_unit),
+                    false => combined,
+                };
+            }
+            Expression::Relation(relation) => {
+                return Expression::Relation(Relation {
+                    first: simplify(&relation.first),
+                    rest: relation
+                        .rest
+                        .iter()
+                        .map(|(operator, right)| (*operator, *simplify(right)))
+                        .collect(),
+                })
+            }
+            Expression::Literal(_)
+            | Expression::Number(_)
+            | Expression::Variable(_)
+            | Expression::NumberSet(_) => return self.clone(),
+            Expression::Quantity(quantity) => {
+                return Expression::Quantity(Quantity {
+                    value: simplify(&quantity.value),
+                    unit: quantity.unit.clone(),
+                })
+            }
+            Expression::Unary(unary) => Expression::Unary(Unary {
+                operator: unary.operator,
+                operand: simplify(&unary.operand),
+            }),
+            Expression::Binary(binary) => Expression::Binary(Binary {
+                operator: binary.operator,
+                left: simplify(&binary.left),
+                right: simplify(&binary.right),
+            }),
+            Expression::FunctionCall(function_call) if function_call.identifier.text == "solve" => {
+                let inputs: Vec<_> = function_call
+                    .inputs
+                    .iter()
+                    .map(|expression| *simplify(expression))
+                    .collect();
+                // Solved when it can be, otherwise left as a call
+                if let Some(solved) = solve::call(&inputs).filter(|_| rules.apply("solve")) {
+                    return solved;
+                }
+                return Expression::FunctionCall(FunctionCall {
+                    identifier: function_call.identifier.clone(),
+                    inputs,
+                });
+            }
+            Expression::FunctionCall(function_call) if function_call.identifier.text == "to" => {
+                let inputs: Vec<_> = function_call
+                    .inputs
+                    .iter()
+                    .map(|expression| *simplify(expression))
+                    .collect();
+                // Converted when the units measure the same thing, otherwise left as a call
+                if let Some(converted) =
+                    units::convert(&inputs).filter(|_| rules.apply("convert units"))
+                {
+                    return converted;
+                }
+                return Expression::FunctionCall(FunctionCall {
+                    identifier: function_call.identifier.clone(),
+                    inputs,
+                });
+            }
+            Expression::FunctionCall(function_call) => Expression::FunctionCall(FunctionCall {
+                identifier: function_call.identifier.clone(),
+                inputs: function_call
+                    .inputs
+                    .iter()
+                    .map(|expression| *simplify(expression))
+                    .collect(),
+            }),
+            Expression::Subscript(subscript) => Expression::Subscript(Subscript {
+                base: simplify(&subscript.base),
+                index: simplify(&subscript.index),
+            }),
+            Expression::BigOperator(big_operator) => {
+                let (from, to) = (simplify(&big_operator.from), simplify(&big_operator.to));
+                let imaginary_unit = unbound(imaginary_unit, &big_operator.index);
+                Expression::BigOperator(BigOperator {
+                    from,
+                    to,
+                    body: Box::new(big_operator.body.simplify_in(rules, imaginary_unit)),
+                    ..big_operator.clone()
+                })
+            }
+            Expression::Integral(integral) => {
+                let bounds = integral
+                    .bounds
+                    .as_ref()
+                    .map(|(lower, upper)| (simplify(lower), simplify(upper)));
+                let imaginary_unit = unbound(imaginary_unit, &integral.variable);
+                Expression::Integral(Integral {
+                    integrand: Box::new(integral.integrand.simplify_in(rules, imaginary_unit)),
+                    variable: integral.variable.clone(),
+                    bounds,
+                })
+            }
+            Expression::Limit(limit) => {
+                let approaching = simplify(&limit.approaching);
+                let imaginary_unit = unbound(imaginary_unit, &limit.variable);
+                Expression::Limit(Limit {
+                    variable: limit.variable.clone(),
+                    approaching,
+                    body: Box::new(limit.body.simplify_in(rules, imaginary_unit)),
+                })
+            }
+            Expression::Derivative(derivative) => {
+                let simplified = Expression::Derivative(match derivative {
+                    Derivative::Leibniz { variable, body } => Derivative::Leibniz {
+                        variable: variable.clone(),
+                        body: simplify(body),
+                    },
+                    Derivative::Prime {
+                        function,
+                        order,
+                        inputs,
+                    } => Derivative::Prime {
+                        function: function.clone(),
+                        order: *order,
+                        inputs: inputs.as_ref().map(|inputs| {
+                            inputs
+                                .iter()
+                                .map(|expression| *simplify(expression))
+                                .collect()
+                        }),
+                    },
+                    Derivative::Partial { body, variables } => Derivative::Partial {
+                        body: simplify(body),
+                        variables: variables.clone(),
+                    },
+                });
+                // Worked out when it can be, otherwise left as a derivative
+                return match derivative.compute() {
+                    Ok(computed) if rules.apply("differentiate") => computed,
+                    _ => simplified,
+                };
+            }
+            Expression::List(list) => Expression::List(List {
+                elements: list
+                    .elements
+                    .iter()
+                    .map(|expression| *simplify(expression))
+                    .collect(),
+            }),
+            Expression::Vector(vector) => Expression::Vector(Vector {
+                elements: vector
+                    .elements
+                    .iter()
+                    .map(|expression| *simplify(expression))
+                    .collect(),
+            }),
+            Expression::Matrix(matrix) => Expression::Matrix(Matrix {
+                rows: matrix
+                    .rows
+                    .iter()
+                    .map(|row| row.iter().map(|expression| *simplify(expression)).collect())
+                    .collect(),
+            }),
+            Expression::Piecewise(piecewise) => Expression::Piecewise(Piecewise {
+                arms: piecewise
+                    .arms
+                    .iter()
+                    .map(|arm| Arm {
+                        value: *simplify(&arm.value),
+                        condition: *simplify(&arm.condition),
+                    })
+                    .collect(),
+                otherwise: piecewise.otherwise.as_deref().map(&mut simplify),
+            }),
+            Expression::Set(set) => Expression::Set(Set {
+                elements: set
+                    .elements
+                    .iter()
+                    .map(|expression| *simplify(expression))
+                    .collect(),
+            }),
+            Expression::SetBuilder(set_builder) => {
+                let domain = set_builder.domain.as_deref().map(&mut simplify);
+                let imaginary_unit = unbound(imaginary_unit, &set_builder.variable);
+                Expression::SetBuilder(SetBuilder {
+                    variable: set_builder.variable.clone(),
+                    domain,
+                    condition: Box::new(set_builder.condition.simplify_in(rules, imaginary_unit)),
+                })
+            }
+            Expression::Interval(interval) => Expression::Interval(Interval {
+                lower: simplify(&interval.lower),
+                upper: simplify(&interval.upper),
+                ..interval.clone()
+            }),
+            Expression::Delimited(delimited) => Expression::Delimited(Delimited {
+                kind: delimited.kind,
+                inner: simplify(&delimited.inner),
+            }),
+        };
+        fold(expression, rules, imaginary_unit)
+    }
+}
+
+impl BinaryOperator {
+    /// Operators the simplifier rewrites into sums of products
+    fn is_arithmetic(&self) -> bool {
+        matches!(
+            self,
+            BinaryOperator::Add
+                | BinaryOperator::Subtract
+                | BinaryOperator::Multiply
+                | BinaryOperator::ImplicitMultiply
+                | BinaryOperator::Divide
+                | BinaryOperator::Power
+        )
+    }
+}
+
+/// The exact value of an expression with no variables in it, like `sqrt(4)`, `sum(k, 1, 4, k)`
+/// or `conj(2i)`. Anything only known as a float is left as written, and so is anything with
+/// units, which would be lost.
+fn fold<'src>(
+    expression: Expression<'src>,
+    rules: &mut Rules,
+    imaginary_unit: Option<&'src str>,
+) -> Expression<'src> {
+    if expression.has_units() {
+        return expression;
+    }
+    let mut environment = Environment::new();
+    environment.set_imaginary_unit(imaginary_unit);
+    let folded = match (environment.evaluate(&expression), imaginary_unit) {
+        (Ok(Value::Number(number)), _) if number.is_exact() => Expression::Number(number),
+        (Ok(Value::Complex(Complex { re, im })), Some(text)) if re.is_exact() && im.is_exact() => {
+            let i = Term::factor(Expression::Variable(Variable {
+                identifier: Identifier { text },
+            }));
+            sum(collect(
+                true,
+                vec![Term::constant(re), i.multiply(Term::constant(im))],
+            ))
+        }
+        _ => return expression,
+    };
+    match folded != expression && rules.apply("evaluate") {
+        true => folded,
+        false => expression,
+    }
+}
+
+/// The imaginary unit inside something that binds `variable`, which hides it when it has the
+/// same name
+fn unbound<'src>(
+    imaginary_unit: Option<&'src str>,
+    variable: &Identifier<'_>,
+) -> Option<&'src str> {
+    imaginary_unit.filter(|unit| *unit != variable.text)
+}
+
+/// Names of the rules used while simplifying, in order
+#[derive(Debug)]
+pub(crate) struct Rules {
+    pub(crate) used: Vec<&'static str>,
+    limit: usize,
+}
+
+impl Rules {
+    /// Allowing up to `limit` rules to be used
+    pub(crate) fn new(limit: usize) -> Rules {
+        Rules {
+            used: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Notes `rule` as used, unless the limit's been reached and it can't be
+    fn apply(&mut self, rule: &'static str) -> bool {
+        let allowed = self.used.len() < self.limit;
+        if allowed {
+            self.used.push(rule);
+        }
+        allowed
+    }
+}
+
+/// `coefficient * base^exponent * ...`
+#[derive(Debug, Clone)]
+struct Term<'src> {
+    coefficient: Number,
+    factors: Vec<Factor<'src>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Factor<'src> {
+    base: Expression<'src>,
+    exponent: Number,
+}
+
+impl<'src> Term<'src> {
+    fn constant(coefficient: Number) -> Term<'src> {
+        Term {
+            coefficient,
+            factors: Vec::new(),
+        }
+    }
+
+    fn factor(base: Expression<'src>) -> Term<'src> {
+        Term {
+            coefficient: Number::from(1),
+            factors: vec![Factor {
+                base,
+                exponent: Number::from(1),
+            }],
+        }
+    }
+
+    fn negate(mut self) -> Term<'src> {
+        self.coefficient = -&self.coefficient;
+        self
+    }
+
+    /// Adds the exponents of factors with the same base, and drops any that end up as `x^0`
+    fn multiply(mut self, other: Term<'src>) -> Term<'src> {
+        self.coefficient = &self.coefficient * &other.coefficient;
+        for factor in other.factors {
+            match self.factors.iter_mut().find(|f| f.base == factor.base) {
+                Some(existing) => existing.exponent = &existing.exponent + &factor.exponent,
+                None => self.factors.push(factor),
+            }
+        }
+        self.factors.retain(|factor| !factor.exponent.is_zero());
+        self
+    }
+
+    /// Whether a factor is a quotient left as written because it divides by zero
+    fn divides_by_zero(&self) -> bool {
+        self.factors.iter().any(|factor| {
+            matches!(
+                &factor.base,
+                Expression::Binary(Binary {
+                    operator: BinaryOperator::Divide,
+                    right,
+                    ..
+                }) if matches!(right.as_ref(), Expression::Number(zero) if zero.is_zero())
+            )
+        })
+    }
+
+    /// [`None`] when that would divide by zero
+    fn reciprocal(self) -> Option<Term<'src>> {
+        self.pow(&Number::from(-1))
+    }
+
+    /// Raises each factor, so `(2x)^2` is `4x^2`. Only for whole exponents, since
+    /// `(x^2)^(1/2)` is `|x|` and not `x`. [`None`] when the coefficient can't be raised exactly.
+    fn pow(self, exponent: &Number) -> Option<Term<'src>> {
+        exponent.to_integer()?;
+        let coefficient = self.coefficient.pow(exponent)?;
+        if self.coefficient.is_exact() && !coefficient.is_exact() {
+            return None;
+        }
+        let factors = self
+            .factors
+            .into_iter()
+            .map(|factor| Factor {
+                exponent: &factor.exponent * exponent,
+                ..factor
+            })
+            .filter(|factor| !factor.exponent.is_zero())
+            .collect();
+        Some(Term {
+            coefficient,
+            factors,
+        })
+    }
+
+    /// Same factors, whatever order they're in
+    fn like(&self, other: &Term<'src>) -> bool {
+        self.factors.len() == other.factors.len()
+            && self.factors.iter().all(|f| other.factors.contains(f))
+    }
+}
+
+/// `expression` as a sum
+fn terms(expression: Expression<'_>) -> Vec<Term<'_>> {
+    match expression {
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Add,
+            left,
+            right,
+        }) => {
+            let mut terms = terms(*left);
+            terms.extend(self::terms(*right));
+            terms
+        }
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Subtract,
+            left,
+            right,
+        }) => {
+            let mut terms = terms(*left);
+            terms.extend(self::terms(*right).into_iter().map(Term::negate));
+            terms
+        }
+        Expression::Unary(Unary {
+            operator: UnaryOperator::Negate,
+            operand,
+        }) => terms(*operand).into_iter().map(Term::negate).collect(),
+        Expression::Group(group) => terms(*group.inner),
+        expression => vec![term(expression)],
+    }
+}
+
+fn is_odd(number: &Number) -> bool {
+    number
+        .to_integer()
+        .is_some_and(|integer| integer % 2 != BigInt::from(0))
+}
+
+/// `expression` as a product
+fn term(expression: Expression<'_>) -> Term<'_> {
+    match expression {
+        Expression::Number(number) => Term::constant(number),
+        Expression::Literal(Literal::Whole(whole)) => {
+            Term::constant(BigInt::from(whole.value).into())
+        }
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply,
+            left,
+            right,
+        }) => term(*left).multiply(term(*right)),
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Divide,
+            left,
+            right,
+        }) => {
+            let denominator = term(*right.clone());
+            match denominator.reciprocal() {
+                Some(reciprocal) => term(*left).multiply(reciprocal),
+                // Dividing by zero, which is left as written so `0/0` isn't taken as `0`
+                None => Term::factor(Expression::Binary(Binary {
+                    operator: BinaryOperator::Divide,
+                    left,
+                    right,
+                })),
+            }
+        }
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Power,
+            left,
+            right,
+        }) => {
+            let Expression::Number(exponent) = *right else {
+                return Term::factor(Expression::Binary(Binary {
+                    operator: BinaryOperator::Power,
+                    left,
+                    right,
+                }));
+            };
+            let base = term(*left.clone());
+            if let Some(power) = base.clone().pow(&exponent) {
+                return power;
+            }
+            match base {
+                // A single factor to an odd power takes any exponent, so `(x^3)^(1/3)` is `x`,
+                // but `(x^2)^(1/2)` is `|x|` and stays as it is
+                Term {
+                    coefficient,
+                    mut factors,
+                } if coefficient.is_one() && factors.len() == 1 && is_odd(&factors[0].exponent) => {
+                    factors[0].exponent = &factors[0].exponent * &exponent;
+                    Term {
+                        coefficient,
+                        factors,
+                    }
+                }
+                _ => Term {
+                    coefficient: Number::from(1),
+                    factors: vec![Factor {
+                        base: *left,
+                        exponent,
+                    }],
+                },
+            }
+        }
+        Expression::Unary(Unary {
+            operator: UnaryOperator::Negate,
+            operand,
+        }) => term(*operand).negate(),
+        Expression::Group(group) => term(*group.inner),
+        expression => Term::factor(expression),
+    }
+}
+
+/// [`sum`] of the [`collect`]ed terms of `expression`, noting what that did. Like terms are
+/// combined first, then the numbers are added up, each as a rule of its own.
+fn combine<'src>(expression: Expression<'src>, rules: &mut Rules) -> Expression<'src> {
+    let terms = terms(expression.clone());
+    let count = terms.len();
+    let like = collect(false, terms);
+    if like.len() < count && !rules.apply("combine like terms") {
+        return expression;
+    }
+    let collected = collect(true, like.clone());
+    let evaluated = collected.len() < like.len()
+        || (count > 0 && collected.iter().all(|term| term.factors.is_empty()));
+    if evaluated && !rules.apply("evaluate") {
+        return sum(like);
+    }
+    sum(collected)
+}
+
+/// Adds up like terms, in the order they first appear. Terms that are just a number are only
+/// added up with `constants`.
+fn collect(constants: bool, terms: Vec<Term<'_>>) -> Vec<Term<'_>> {
+    let addable = |term: &Term| constants || !term.factors.is_empty();
+    let mut collected: Vec<Term> = Vec::new();
+    for term in terms {
+        let like = collected
+            .iter_mut()
+            .find(|c| addable(&term) && c.like(&term));
+        match like {
+            Some(like) => like.coefficient = &like.coefficient + &term.coefficient,
+            None => collected.push(term),
+        }
+    }
+    // Zero times a quotient that divides by zero is still undefined, so it's kept
+    collected
+        .retain(|term| !addable(term) || !term.coefficient.is_zero() || term.divides_by_zero());
+    collected
+}
+
+/// Back to an expression, subtracting the negative terms
+fn sum(terms: Vec<Term<'_>>) -> Expression<'_> {
+    let mut terms = terms.into_iter();
+    let Some(first) = terms.next() else {
+        return Expression::Number(Number::from(0));
+    };
+    let mut sum = product(first);
+    for term in terms {
+        let (operator, term) = if term.coefficient.is_negative() {
+            (BinaryOperator::Subtract, term.negate())
+        } else {
+            (BinaryOperator::Add, term)
+        };
+        sum = Expression::Binary(Binary {
+            operator,
+            left: Box::new(sum),
+            right: Box::new(product(term)),
+        });
+    }
+    sum
+}
+
+/// Back to an expression, with negative exponents below a fraction line
+fn product(term: Term<'_>) -> Expression<'_> {
+    if term.coefficient.is_negative() && !term.factors.is_empty() {
+        return negate_leading(product(term.negate()));
+    }
+    if term.factors.is_empty() {
+        return Expression::Number(term.coefficient);
+    }
+    let (coefficient, divisor) = match &term.coefficient {
+        Number::Exact(exact) => (
+            Number::from(exact.numer().clone()),
+            Number::from(exact.denom().clone()),
+        ),
+        float => (float.clone(), Number::from(1)),
+    };
+    let mut numerator = Vec::new();
+    let mut denominator = Vec::new();
+    for factor in term.factors {
+        if factor.exponent.is_negative() {
+            denominator.push(power(factor.base, -&factor.exponent));
+        } else {
+            numerator.push(power(factor.base, factor.exponent));
+        }
+    }
+    let numerator = multiply(coefficient, numerator);
+    if denominator.is_empty() && divisor.is_one() {
+        return numerator;
+    }
+    Expression::Binary(Binary {
+        operator: BinaryOperator::Divide,
+        left: Box::new(numerator),
+        right: Box::new(multiply(divisor, denominator)),
+    })
+}
+
+/// Negates the leftmost factor, since `-2x` reads as `(-2)x`
+fn negate_leading(expression: Expression<'_>) -> Expression<'_> {
+    match expression {
+        Expression::Number(number) => Expression::Number(-&number),
+        Expression::Binary(Binary {
+            operator:
+                operator @ (BinaryOperator::Multiply
+                | BinaryOperator::ImplicitMultiply
+                | BinaryOperator::Divide),
+            left,
+            right,
+        }) => Expression::Binary(Binary {
+            operator,
+            left: Box::new(negate_leading(*left)),
+            right,
+        }),
+        expression => Expression::Unary(Unary {
+            operator: UnaryOperator::Negate,
+            operand: Box::new(expression),
+        }),
+    }
+}
+
+/// `coefficient * factors[0] * ...`, leaving out a coefficient of 1
+fn multiply<'src>(coefficient: Number, factors: Vec<Expression<'src>>) -> Expression<'src> {
+    let mut factors = factors.into_iter();
+    let mut product = match coefficient.is_one() {
+        true => match factors.next() {
+            Some(factor) => factor,
+            None => return Expression::Number(coefficient),
+        },
+        false => Expression::Number(coefficient),
+    };
+    for factor in factors {
+        // Numbers side by side would read as one number
+        let operator = match factor {
+            Expression::Number(_)
+            | Expression::Binary(Binary {
+                operator: BinaryOperator::Power,
+                ..
+            }) if starts_with_number(&factor) => BinaryOperator::Multiply,
+            _ => BinaryOperator::ImplicitMultiply,
+        };
+        product = Expression::Binary(Binary {
+            operator,
+            left: Box::new(product),
+            right: Box::new(factor),
+        });
+    }
+    product
+}
+
+pub(crate) fn starts_with_number(expression: &Expression<'_>) -> bool {
+    match expression {
+        Expression::Number(_) | Expression::Literal(_) => true,
+        Expression::Binary(binary) => starts_with_number(&binary.left),
+        _ => false,
+    }
+}
+
+/// `base^exponent`, or just `base` to the first power
+fn power<'src>(base: Expression<'src>, exponent: Number) -> Expression<'src> {
+    if exponent.is_one() {
+        return base;
+    }
+    Expression::Binary(Binary {
+        operator: BinaryOperator::Power,
+        left: Box::new(base),
+        right: Box::new(Expression::Number(exponent)),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::unicode::Unicode;
+
+    fn simplify(str: &str) -> String {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        Unicode(&ast.simplify()).to_string()
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(simplify("1 + 2*3"), "7");
+        assert_eq!(simplify("1/3 + 1/6"), "1/2");
+        assert_eq!(simplify("2^10 - 24"), "1000");
+        assert_eq!(simplify("sqrt(16) + |-3|"), "7");
+        assert_eq!(simplify("sum(k, 1, 4, k^2)"), "30");
+        assert_eq!(simplify("sin(2) + 0"), "sin(2)");
+    }
+
+    #[test]
+    fn like_terms() {
+        assert_eq!(simplify("2x + 3x"), "5x");
+        assert_eq!(simplify("x + 1 - x"), "1");
+        assert_eq!(simplify("3x*y - y*x + 2"), "2x·y + 2");
+        assert_eq!(simplify("x - 3x"), "−2x");
+        assert_eq!(simplify("a + b - 2b"), "a − b");
+        assert_eq!(simplify("x/2 + x/2"), "x");
+        assert_eq!(simplify("x/2 + x"), "3x/2");
+    }
+
+    #[test]
+    fn factors() {
+        assert_eq!(simplify("x*y/x"), "y");
+        assert_eq!(simplify("x^2*x^3"), "x⁵");
+        assert_eq!(simplify("(2x)^3"), "8x³");
+        assert_eq!(simplify("6*x/(4*y)"), "3x/(2y)");
+        assert_eq!(simplify("(x + 1)/(x + 1)"), "1");
+        assert_eq!(simplify("2(x + 1) - (x + 1)*2"), "0");
+        assert_eq!(simplify("x^(1/2)*x^(1/2)"), "x");
+        assert_eq!(simplify("(x^3)^(1/3)"), "x");
+        assert_eq!(simplify("(x^2)^(1/2)"), "(x²)^(1/2)");
+    }
+
+    #[test]
+    fn identities() {
+        assert_eq!(simplify("x^0"), "1");
+        assert_eq!(simplify("x^1"), "x");
+        assert_eq!(simplify("x*1 + 0"), "x");
+        assert_eq!(simplify("0*x + y"), "y");
+        assert_eq!(simplify("-(-x)"), "x");
+        assert_eq!(simplify("1/0"), "1/0");
+        assert_eq!(simplify("0/0"), "0/0");
+        assert_eq!(simplify("i^2"), "−1");
+        assert_eq!(simplify("conj(2i)"), "−2i");
+        assert_eq!(simplify("(1 + i)(1 - i)"), "2");
+        assert_eq!(simplify("sum(i, 1, n, i^2)"), "∑(i=1..n) i²");
+        assert_eq!(simplify("0/0 + x - x"), "0/0");
+        assert_eq!(simplify("0*(1/0)"), "0·1/0");
+    }
+
+    #[test]
+    fn renders() {
+        let latex = |str| {
+            let tokens = crate::lexer::parse(str).unwrap();
+            let ast = crate::ast::parse(&tokens).unwrap();
+            crate::latex::Latex::new(&ast.simplify()).to_string()
+        };
+        assert_eq!(latex("1/3 + 1/6"), r"\frac{1}{2}");
+        assert_eq!(latex("x/2 + x"), r"\frac{3x}{2}");
+        assert_eq!(latex("x - 2x^2 - x^2"), r"x - 3x^{2}");
+        assert_eq!(latex("2^(1/2)*3"), r"3 \cdot 2^{\frac{1}{2}}");
+        assert_eq!(latex("3 m * 2"), r"6\,\mathrm{m}");
+    }
+
+    #[test]
+    fn statements() {
+        assert_eq!(simplify("let y = 2x + x"), "y ≔ 3x");
+        assert_eq!(simplify("f(x) = x*x"), "f(x) = x²");
+        assert_eq!(simplify("x + x = 2 + 2"), "2x = 4");
+    }
+
+    #[test]
+    fn imaginary_unit() {
+        assert_eq!(simplify("f(i) = i^2"), "f(i) = i²");
+        assert_eq!(simplify("i^2\nlet i = 2\ni^2"), "−1\ni ≔ 2\ni²");
+        assert_eq!(simplify("f(x) = x + i*i"), "f(x) = x − 1");
+        let tokens = crate::lexer::parse("j^2 + i^2").unwrap();
+        let ast = crate::ast::parse(&tokens)
+            .unwrap()
+            .simplify_with_unit(Some("j"));
+        assert_eq!(Unicode(&ast).to_string(), "−1 + i²");
+    }
+}
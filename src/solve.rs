@@ -0,0 +1,853 @@
+//! Solving equations. Linear and quadratic equations are solved exactly, with complex roots
+//! written using `i` or another imaginary unit. Systems of linear equations are solved by
+//! Gaussian elimination over the rationals, and anything else is solved numerically. Numeric
+//! solutions are only known to be all of them when they're every root of a polynomial.
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+use crate::{
+    ast::*,
+    differentiate::{self, add, binary, multiply, number, power, subtract},
+    eval::{Complex, Environment, Number, Value},
+};
+
+/// Highest power of the variable that's expanded when looking for a polynomial
+const MAX_DEGREE: usize = 32;
+
+/// Where numeric solutions are looked for, from `-RANGE` to `RANGE`, unless it's a polynomial
+/// whose roots are known to be nearer
+const RANGE: f64 = 100.0;
+
+/// Most numeric solutions found
+const MAX_ROOTS: usize = 8;
+
+/// Largest factor tried when taking squares out of a square root
+const MAX_FACTOR: u32 = 10_000;
+
+/// Pieces the range is cut into when looking for the sides crossing over
+const SAMPLES: usize = 4000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    #[error("can only solve equations, with one `=`")]
+    NotAnEquation,
+    #[error("can only solve for variables, not `{0}`")]
+    NotAVariable(String),
+    #[error("the equations aren't linear in the variables")]
+    NotLinear,
+    #[error("the equations don't pin down one solution")]
+    NoUniqueSolution,
+    #[error("couldn't find any solutions for {0}")]
+    NotFound(String),
+}
+
+/// What an equation or system of equations is solved by
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solutions<'src> {
+    /// Each solution of an equation, smallest first when they're real. For a system, the value
+    /// of each variable in order.
+    Values(Vec<Expression<'src>>),
+    /// Some of the solutions, found numerically, when there may be others that weren't, like
+    /// those of `sin(x) = 0` far from 0, or complex roots
+    Found(Vec<Expression<'src>>),
+    /// Every value is a solution, like for `x = x`
+    Everything,
+    /// No value is, like for `x = x + 1`
+    Nothing,
+}
+
+impl<'src> Solutions<'src> {
+    /// `x = 2, 3` for an equation, or `x = 1, y = 2` for a system. What was
+    /// [`Found`](Solutions::Found) is written `x ≈ 0, 3.14`.
+    pub fn to_expression(&self, variables: &[Identifier<'src>]) -> Expression<'src> {
+        let relation = |variable: &Identifier<'src>, operator, value| {
+            Expression::Relation(Relation {
+                first: Box::new(Expression::Variable(Variable {
+                    identifier: variable.clone(),
+                })),
+                rest: vec![(operator, value)],
+            })
+        };
+        let each = |value: &dyn Fn(usize) -> (RelationOperator, Expression<'src>)| {
+            let relations: Vec<_> = variables
+                .iter()
+                .enumerate()
+                .map(|(i, variable)| {
+                    let (operator, value) = value(i);
+                    relation(variable, operator, value)
+                })
+                .collect();
+            match <[_; 1]>::try_from(relations) {
+                Ok([relation]) => relation,
+                Err(relations) => Expression::List(List {
+                    elements: relations,
+                }),
+            }
+        };
+        match self {
+            Solutions::Values(values) | Solutions::Found(values) if variables.len() == 1 => {
+                let value = match values.as_slice() {
+                    [value] => value.clone(),
+                    values => Expression::List(List {
+                        elements: values.to_vec(),
+                    }),
+                };
+                let operator = match self {
+                    Solutions::Found(_) => RelationOperator::Approximately,
+                    _ => RelationOperator::Equal,
+                };
+                relation(&variables[0], operator, value)
+            }
+            Solutions::Values(values) => each(&|i| (RelationOperator::Equal, values[i].clone())),
+            Solutions::Found(values) => {
+                each(&|i| (RelationOperator::Approximately, values[i].clone()))
+            }
+            Solutions::Everything => {
+                each(&|_| (RelationOperator::In, Expression::NumberSet(NumberSet::Real)))
+            }
+            Solutions::Nothing => each(&|_| {
+                (
+                    RelationOperator::In,
+                    Expression::Set(Set {
+                        elements: Vec::new(),
+                    }),
+                )
+            }),
+        }
+    }
+}
+
+impl<'src> Relation<'src> {
+    /// Values of `variable` that make both sides equal. Other variables are constants.
+    pub fn solve(&self, variable: &Identifier<'src>) -> Result<Solutions<'src>, Error> {
+        self.solve_with_unit(variable, Some("i"))
+    }
+
+    /// Like [`solve`](Relation::solve), with complex roots written using `imaginary_unit`, or
+    /// left out when it's [`None`], to match
+    /// [`Environment::set_imaginary_unit`](crate::eval::Environment::set_imaginary_unit)
+    pub fn solve_with_unit(
+        &self,
+        variable: &Identifier<'src>,
+        imaginary_unit: Option<&'src str>,
+    ) -> Result<Solutions<'src>, Error> {
+        let difference = self.difference()?;
+        let Some(coefficients) = polynomial(&difference, variable) else {
+            return numeric(&difference, variable);
+        };
+        let mut coefficients: Vec<_> = coefficients.iter().map(Expression::simplify).collect();
+        while coefficients.last().is_some_and(is_zero) {
+            coefficients.pop();
+        }
+        match coefficients.as_slice() {
+            [] => Ok(Solutions::Everything),
+            [Expression::Number(_)] => Ok(Solutions::Nothing),
+            [_] => Err(Error::NotFound(variable.text.to_string())),
+            [b, a] => Ok(Solutions::Values(vec![divide(
+                negate(b.clone()),
+                a.clone(),
+            )
+            .simplify()])),
+            [c, b, a] => Ok(quadratic(a, b, c, imaginary_unit)),
+            coefficients => match numbers(coefficients) {
+                Some(coefficients) => polynomial_roots(&coefficients, &difference, variable),
+                None => numeric(&difference, variable),
+            },
+        }
+    }
+
+    /// `left - right`, for an equation `left = right`
+    fn difference(&self) -> Result<Expression<'src>, Error> {
+        match self.rest.as_slice() {
+            [(RelationOperator::Equal, right)] => {
+                Ok(subtract(self.first.as_ref().clone(), right.clone()))
+            }
+            _ => Err(Error::NotAnEquation),
+        }
+    }
+}
+
+/// Values of `variables` that make every equation hold. The equations must be linear, like
+/// `2x + y = 3`, with numbers as coefficients.
+pub fn solve_system<'src>(
+    equations: &[Relation<'src>],
+    variables: &[Identifier<'src>],
+) -> Result<Solutions<'src>, Error> {
+    // Each row is the coefficients of the variables, then the constant on the right
+    let mut rows = equations
+        .iter()
+        .map(|equation| {
+            let mut row = linear(&equation.difference()?.simplify(), variables)?;
+            let constant = row.remove(0);
+            row.push(-constant);
+            Ok(row)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut pivots = Vec::new();
+    for column in 0..variables.len() {
+        let row = pivots.len();
+        let Some(pivot) = (row..rows.len()).find(|&i| !rows[i][column].is_zero()) else {
+            continue;
+        };
+        rows.swap(row, pivot);
+        let divisor = rows[row][column].clone();
+        for value in &mut rows[row] {
+            *value = &*value / &divisor;
+        }
+        let pivot_row = rows[row].clone();
+        for (other, values) in rows.iter_mut().enumerate() {
+            let factor = values[column].clone();
+            if other == row || factor.is_zero() {
+                continue;
+            }
+            for (value, pivot) in values.iter_mut().zip(&pivot_row) {
+                *value = &*value - &factor * pivot;
+            }
+        }
+        pivots.push(column);
+    }
+
+    // A row left as `0 = c` can't hold
+    if rows[pivots.len()..]
+        .iter()
+        .any(|row| !row[variables.len()].is_zero())
+    {
+        return Ok(Solutions::Nothing);
+    }
+    if pivots.len() < variables.len() {
+        return Err(Error::NoUniqueSolution);
+    }
+    Ok(Solutions::Values(
+        rows.iter()
+            .take(variables.len())
+            .map(|row| Expression::Number(Number::Exact(row[variables.len()].clone())))
+            .collect(),
+    ))
+}
+
+/// `solve(x^2 = 4, x)` or `solve({x + y = 3, x - y = 1}, x, y)` worked out, when it can be
+pub(crate) fn call<'src>(inputs: &[Expression<'src>]) -> Option<Expression<'src>> {
+    let (equations, variables) = inputs.split_first()?;
+    let variables = match variables {
+        [Expression::Set(Set { elements }) | Expression::Vector(Vector { elements })] => {
+            elements.as_slice()
+        }
+        variables => variables,
+    };
+    let variables = variables
+        .iter()
+        .map(|variable| match variable {
+            Expression::Variable(variable) => Ok(variable.identifier.clone()),
+            _ => Err(Error::NotAVariable(format!("{variable:?}"))),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    let solutions = match (equations, variables.as_slice()) {
+        (Expression::Relation(equation), [variable]) => equation.solve(variable),
+        (
+            Expression::Set(Set {
+                elements: equations,
+            })
+            | Expression::Vector(Vector {
+                elements: equations,
+            }),
+            variables,
+        ) => {
+            let equations = equations
+                .iter()
+                .map(|equation| match equation {
+                    Expression::Relation(relation) => Some(relation.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            solve_system(&equations, variables)
+        }
+        _ => return None,
+    };
+    match solutions.ok()? {
+        // Written out, some of the solutions would read as if they were all of them
+        Solutions::Found(_) => None,
+        solutions => Some(solutions.to_expression(&variables)),
+    }
+}
+
+/// Coefficients of the powers of `variable`, lowest first. [`None`] when it isn't a polynomial
+/// in `variable`.
+fn polynomial<'src>(
+    expression: &Expression<'src>,
+    variable: &Identifier<'_>,
+) -> Option<Vec<Expression<'src>>> {
+    if !expression.mentions(variable) {
+        return Some(vec![expression.clone()]);
+    }
+    let polynomial = |expression| polynomial(expression, variable);
+    match expression {
+        Expression::Variable(_) => Some(vec![number(0), number(1)]),
+        Expression::Group(group) => polynomial(&group.inner),
+        Expression::Unary(Unary {
+            operator: UnaryOperator::Negate,
+            operand,
+        }) => Some(polynomial(operand)?.into_iter().map(negate).collect()),
+        Expression::Binary(Binary {
+            operator,
+            left,
+            right,
+        }) => match operator {
+            BinaryOperator::Add => Some(zip(polynomial(left)?, polynomial(right)?, add)),
+            BinaryOperator::Subtract => Some(zip(polynomial(left)?, polynomial(right)?, subtract)),
+            BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => {
+                times(&polynomial(left)?, &polynomial(right)?)
+            }
+            BinaryOperator::Divide if !right.mentions(variable) => Some(
+                polynomial(left)?
+                    .into_iter()
+                    .map(|coefficient| divide(coefficient, right.as_ref().clone()))
+                    .collect(),
+            ),
+            BinaryOperator::Power => {
+                let Expression::Number(exponent) = right.simplify() else {
+                    return None;
+                };
+                let exponent = exponent.to_integer()?.try_into().ok()?;
+                let base = polynomial(left)?;
+                (0..exponent).try_fold(vec![number(1)], |power: Vec<_>, _| times(&power, &base))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Adds or subtracts polynomials term by term
+fn zip<'src>(
+    left: Vec<Expression<'src>>,
+    right: Vec<Expression<'src>>,
+    combine: fn(Expression<'src>, Expression<'src>) -> Expression<'src>,
+) -> Vec<Expression<'src>> {
+    let length = left.len().max(right.len());
+    let mut left = left.into_iter();
+    let mut right = right.into_iter();
+    (0..length)
+        .map(|_| {
+            combine(
+                left.next().unwrap_or(number(0)),
+                right.next().unwrap_or(number(0)),
+            )
+        })
+        .collect()
+}
+
+/// Multiplies polynomials. [`None`] past [`MAX_DEGREE`].
+fn times<'src>(
+    left: &[Expression<'src>],
+    right: &[Expression<'src>],
+) -> Option<Vec<Expression<'src>>> {
+    let degree = left.len() + right.len() - 2;
+    if degree > MAX_DEGREE {
+        return None;
+    }
+    let mut product = vec![number(0); degree + 1];
+    for (i, left) in left.iter().enumerate() {
+        for (j, right) in right.iter().enumerate() {
+            let term = multiply(left.clone(), right.clone());
+            product[i + j] = add(product[i + j].clone(), term);
+        }
+    }
+    Some(product)
+}
+
+/// `ax^2 + bx + c = 0` by the quadratic formula
+fn quadratic<'src>(
+    a: &Expression<'src>,
+    b: &Expression<'src>,
+    c: &Expression<'src>,
+    imaginary_unit: Option<&'src str>,
+) -> Solutions<'src> {
+    let discriminant = subtract(
+        power(b.clone(), number(2)),
+        multiply(number(4), multiply(a.clone(), c.clone())),
+    )
+    .simplify();
+    let twice_a = multiply(number(2), a.clone());
+    let vertex = divide(negate(b.clone()), twice_a.clone());
+    // Floats are left as written by the simplifier, so they're worked out here
+    let floats = [a, b, c].into_iter().any(has_float);
+    let evaluated = |root: Expression<'src>| match floats {
+        true => evaluated(root, imaginary_unit),
+        false => root,
+    };
+    let value = match &discriminant {
+        Expression::Number(value) => Some(value.clone()),
+        _ if floats => Environment::new()
+            .evaluate(&discriminant)
+            .and_then(Value::number)
+            .ok(),
+        _ => None,
+    };
+    let Some(value) = value else {
+        // Not known to be positive or negative, so written with ±
+        let root = differentiate::call("sqrt", discriminant);
+        return Solutions::Values(vec![divide(
+            binary(BinaryOperator::PlusMinus, negate(b.clone()), root),
+            twice_a,
+        )
+        .simplify()]);
+    };
+    if value.is_zero() {
+        return Solutions::Values(vec![evaluated(vertex.simplify())]);
+    }
+    // Complex roots are `vertex ± offset i`
+    let negative = value.is_negative();
+    let offset = divide(square_root(&value.abs()), twice_a);
+    let offset = match (negative, imaginary_unit) {
+        (true, Some(text)) => multiply(
+            offset,
+            Expression::Variable(Variable {
+                identifier: Identifier { text },
+            }),
+        ),
+        // No real roots, and nothing to write the complex ones with
+        (true, None) => return Solutions::Nothing,
+        (false, _) => offset,
+    };
+    let mut roots = vec![
+        evaluated(subtract(vertex.clone(), offset.clone()).simplify()),
+        evaluated(add(vertex, offset).simplify()),
+    ];
+    if !negative {
+        roots.sort_by(|left, right| {
+            let value = |root| Environment::new().evaluate(root).and_then(Value::number);
+            match (value(left), value(right)) {
+                (Ok(left), Ok(right)) => left
+                    .partial_cmp(&right)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+                _ => std::cmp::Ordering::Equal,
+            }
+        });
+    }
+    Solutions::Values(roots)
+}
+
+/// Whether there's a float anywhere in `expression`, like `0.5x`
+fn has_float(expression: &Expression<'_>) -> bool {
+    let mut found = false;
+    expression.rewrite(&mut |expression| {
+        found |= matches!(
+            expression,
+            Expression::Number(Number::Float(_))
+                | Expression::Literal(Literal::Float(_) | Literal::Scientific(_))
+        );
+        None
+    });
+    found
+}
+
+/// The value of a root with no variables in it, written with `imaginary_unit` when it's complex
+fn evaluated<'src>(root: Expression<'src>, imaginary_unit: Option<&'src str>) -> Expression<'src> {
+    let mut environment = Environment::new();
+    environment.set_imaginary_unit(imaginary_unit);
+    match (environment.evaluate(&root), imaginary_unit) {
+        (Ok(Value::Number(value)), _) => Expression::Number(value),
+        (Ok(Value::Complex(Complex { re, im })), Some(text)) => {
+            let unit = Expression::Variable(Variable {
+                identifier: Identifier { text },
+            });
+            if re.is_zero() {
+                return multiply(Expression::Number(im), unit);
+            }
+            let imaginary = multiply(Expression::Number(im.abs()), unit);
+            match im.is_negative() {
+                true => subtract(Expression::Number(re), imaginary),
+                false => add(Expression::Number(re), imaginary),
+            }
+        }
+        _ => root,
+    }
+}
+
+/// Roots of `difference` from `-RANGE` to `RANGE`. Only the [`MAX_ROOTS`] nearest 0 are kept,
+/// and there may be others outside, so they're only what was [`Found`](Solutions::Found).
+fn numeric<'src>(
+    difference: &Expression<'src>,
+    variable: &Identifier<'src>,
+) -> Result<Solutions<'src>, Error> {
+    let mut roots = crossings(difference, variable, RANGE);
+    // Something periodic like `sin(x) = 1/2` has too many to list, so just the ones near 0
+    if roots.len() > MAX_ROOTS {
+        roots.sort_by(|left, right| left.to_f64().abs().total_cmp(&right.to_f64().abs()));
+        roots.truncate(MAX_ROOTS);
+        roots.sort_by(|left, right| left.to_f64().total_cmp(&right.to_f64()));
+    }
+    if roots.is_empty() {
+        return Err(Error::NotFound(variable.text.to_string()));
+    }
+    Ok(Solutions::Found(
+        roots.into_iter().map(Expression::Number).collect(),
+    ))
+}
+
+/// Real roots of the polynomial `difference`, with numbers as `coefficients`, lowest power
+/// first. Every real root is within Cauchy's bound, `1 + max |c_k / c_n|`, of 0, so they're
+/// all found. They're every solution when, counted as often as they divide the polynomial,
+/// there are as many as its degree, and what was [`Found`](Solutions::Found) otherwise.
+fn polynomial_roots<'src>(
+    coefficients: &[Number],
+    difference: &Expression<'src>,
+    variable: &Identifier<'src>,
+) -> Result<Solutions<'src>, Error> {
+    let (leading, rest) = coefficients
+        .split_last()
+        .expect("a polynomial past quadratic");
+    let bound = rest
+        .iter()
+        .map(|coefficient| (coefficient.to_f64() / leading.to_f64()).abs())
+        .fold(0.0, f64::max)
+        + 1.0;
+    // A whole power of two apart, so the samples land exactly on 0 and on whole numbers, and
+    // roots that only touch 0 there are found too
+    let step = (2.0 * bound / SAMPLES as f64).log2().ceil().exp2();
+    let roots = crossings(difference, variable, step * SAMPLES as f64 / 2.0);
+    if roots.is_empty() {
+        return Err(Error::NotFound(variable.text.to_string()));
+    }
+    let counted: usize = roots
+        .iter()
+        .map(|root| multiplicity(coefficients.to_vec(), root))
+        .sum();
+    let roots = roots.into_iter().map(Expression::Number).collect();
+    Ok(match counted == coefficients.len() - 1 {
+        true => Solutions::Values(roots),
+        false => Solutions::Found(roots),
+    })
+}
+
+/// Numbers, when every coefficient is one
+fn numbers(coefficients: &[Expression<'_>]) -> Option<Vec<Number>> {
+    coefficients
+        .iter()
+        .map(|coefficient| match coefficient {
+            Expression::Number(number) => Some(number.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// How many times `x - root` divides the polynomial with `coefficients`, lowest power first.
+/// Only an exact root is known to divide it more than once.
+fn multiplicity(mut coefficients: Vec<Number>, root: &Number) -> usize {
+    let mut count = 0;
+    loop {
+        // Synthetic division, from the highest power down
+        let mut carry = Number::from(0);
+        let mut quotient: Vec<Number> = coefficients
+            .iter()
+            .rev()
+            .map(|coefficient| {
+                carry = &(&carry * root) + coefficient;
+                carry.clone()
+            })
+            .collect();
+        let remainder = quotient.pop().expect("a polynomial has coefficients");
+        if !(remainder.is_exact() && remainder.is_zero()) || quotient.is_empty() {
+            return count.max(1);
+        }
+        count += 1;
+        quotient.reverse();
+        coefficients = quotient;
+    }
+}
+
+/// Finds where `difference` crosses zero from `-range` to `range`, then narrows each crossing
+/// down by bisection. Roots that turn out to be whole numbers, or exactly what a float says,
+/// are made exact.
+fn crossings<'src>(
+    difference: &Expression<'src>,
+    variable: &Identifier<'src>,
+    range: f64,
+) -> Vec<Number> {
+    let at = |x: Number| {
+        let mut environment = Environment::new();
+        environment.bind(variable.text, x);
+        environment
+            .evaluate(difference)
+            .and_then(Value::number)
+            .ok()
+    };
+    let float = |x: f64| {
+        at(Number::Float(x))
+            .map(|y| y.to_f64())
+            .filter(|y| y.is_finite())
+    };
+
+    let mut roots: Vec<f64> = Vec::new();
+    let step = 2.0 * range / SAMPLES as f64;
+    let mut previous: Option<(f64, f64)> = None;
+    for i in 0..=SAMPLES {
+        let x = -range + i as f64 * step;
+        let Some(y) = float(x) else {
+            previous = None;
+            continue;
+        };
+        if y == 0.0 {
+            roots.push(x);
+        } else if let Some((mut low, low_y)) = previous.filter(|(_, low_y)| low_y * y < 0.0) {
+            let mut high = x;
+            for _ in 0..200 {
+                let middle = (low + high) / 2.0;
+                match float(middle) {
+                    Some(middle_y) if (middle_y < 0.0) == (low_y < 0.0) => low = middle,
+                    Some(_) => high = middle,
+                    None => break,
+                }
+            }
+            let root = (low + high) / 2.0;
+            // A jump across zero, like `tan(x)` has, isn't a root
+            if float(root).is_some_and(|y| y.abs() < 1e-6) {
+                roots.push(root);
+            }
+        }
+        previous = Some((x, y));
+    }
+    roots.dedup_by(|right, left| (*right - *left).abs() < 1e-9);
+    roots
+        .into_iter()
+        .map(|root| {
+            let whole = Number::from(root.round() as i64);
+            let exact = BigRational::from_float(root).map(Number::Exact);
+            [Some(whole), exact]
+                .into_iter()
+                .flatten()
+                .find(|exact| at(exact.clone()).is_some_and(|y| y.is_exact() && y.is_zero()))
+                .unwrap_or(Number::Float(root))
+        })
+        .collect()
+}
+
+/// Coefficients of `variables`, after the constant. [`Error::NotLinear`] unless they're all
+/// exact numbers.
+fn linear(
+    expression: &Expression<'_>,
+    variables: &[Identifier<'_>],
+) -> Result<Vec<BigRational>, Error> {
+    let constant = |expression: &Expression<'_>| -> Result<BigRational, Error> {
+        match Environment::new()
+            .evaluate(expression)
+            .and_then(Value::number)
+        {
+            Ok(Number::Exact(exact)) => Ok(exact),
+            _ => Err(Error::NotLinear),
+        }
+    };
+    if !variables
+        .iter()
+        .any(|variable| expression.mentions(variable))
+    {
+        let mut row = vec![BigRational::zero(); variables.len() + 1];
+        row[0] = constant(expression)?;
+        return Ok(row);
+    }
+    let linear = |expression| linear(expression, variables);
+    let scale =
+        |row: Vec<BigRational>, by: &BigRational| row.iter().map(|value| value * by).collect();
+    match expression {
+        Expression::Variable(variable) => {
+            let mut row = vec![BigRational::zero(); variables.len() + 1];
+            let i = variables
+                .iter()
+                .position(|other| other.text == variable.identifier.text)
+                .ok_or(Error::NotLinear)?;
+            row[i + 1] = BigRational::one();
+            Ok(row)
+        }
+        Expression::Group(group) => linear(&group.inner),
+        Expression::Unary(Unary {
+            operator: UnaryOperator::Negate,
+            operand,
+        }) => Ok(scale(linear(operand)?, &-BigRational::one())),
+        Expression::Binary(Binary {
+            operator,
+            left,
+            right,
+        }) => match operator {
+            BinaryOperator::Add | BinaryOperator::Subtract => {
+                let sign = match operator {
+                    BinaryOperator::Add => BigRational::one(),
+                    _ => -BigRational::one(),
+                };
+                let right = linear(right)?;
+                Ok(linear(left)?
+                    .into_iter()
+                    .zip(right)
+                    .map(|(left, right)| left + &sign * right)
+                    .collect())
+            }
+            BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => {
+                let (left, right) = (linear(left)?, linear(right)?);
+                // One side has to be a number
+                let is_constant = |row: &[BigRational]| row[1..].iter().all(Zero::is_zero);
+                match (is_constant(&left), is_constant(&right)) {
+                    (true, _) => Ok(scale(right, &left[0])),
+                    (_, true) => Ok(scale(left, &right[0])),
+                    _ => Err(Error::NotLinear),
+                }
+            }
+            BinaryOperator::Divide => {
+                let divisor = constant(right)?;
+                if divisor.is_zero() {
+                    return Err(Error::NotLinear);
+                }
+                Ok(scale(linear(left)?, &divisor.recip()))
+            }
+            _ => Err(Error::NotLinear),
+        },
+        _ => Err(Error::NotLinear),
+    }
+}
+
+/// `sqrt(value)`, with square factors taken out so `sqrt(8)` is `2sqrt(2)`
+fn square_root<'src>(value: &Number) -> Expression<'src> {
+    let Number::Exact(exact) = value else {
+        return Expression::Number(Number::Float(value.to_f64().sqrt()));
+    };
+    // sqrt(p/q) is sqrt(pq)/q
+    let mut inside = exact.numer() * exact.denom();
+    let mut outside = BigRational::from_integer(exact.denom().clone()).recip();
+    let mut factor = BigInt::from(2);
+    while &factor * &factor <= inside && factor < BigInt::from(MAX_FACTOR) {
+        let square = &factor * &factor;
+        while (&inside % &square).is_zero() {
+            inside /= &square;
+            outside *= BigRational::from_integer(factor.clone());
+        }
+        factor += 1;
+    }
+    let root = inside.sqrt();
+    if &root * &root == inside {
+        outside *= BigRational::from_integer(root);
+        return Expression::Number(Number::Exact(outside));
+    }
+    let surd = differentiate::call("sqrt", Expression::Number(Number::from(inside)));
+    multiply(Expression::Number(Number::Exact(outside)), surd)
+}
+
+fn negate(expression: Expression<'_>) -> Expression<'_> {
+    Expression::Unary(Unary {
+        operator: UnaryOperator::Negate,
+        operand: Box::new(expression),
+    })
+}
+
+fn divide<'src>(numerator: Expression<'src>, denominator: Expression<'src>) -> Expression<'src> {
+    binary(BinaryOperator::Divide, numerator, denominator)
+}
+
+fn is_zero(expression: &Expression<'_>) -> bool {
+    matches!(expression, Expression::Number(number) if number.is_zero())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::unicode::Unicode;
+
+    fn solve(str: &str) -> String {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        Unicode(&ast.simplify()).to_string()
+    }
+
+    #[test]
+    fn linear() {
+        assert_eq!(solve("solve(2x + 3 = 7, x)"), "x = 2");
+        assert_eq!(solve("solve(x/3 = 1 - x, x)"), "x = 3/4");
+        assert_eq!(solve("solve(a*x + b = 0, x)"), "x = −b/a");
+        assert_eq!(solve("solve(x + 1 = x, x)"), "x ∈ {}");
+        assert_eq!(solve("solve(2(x + 1) = 2x + 2, x)"), "x ∈ ℝ");
+    }
+
+    #[test]
+    fn quadratic() {
+        assert_eq!(solve("solve(x^2 - 5x + 6 = 0, x)"), "x = 2, 3");
+        assert_eq!(solve("solve((x - 1)^2 = 0, x)"), "x = 1");
+        assert_eq!(solve("solve(x^2 = 2, x)"), "x = −sqrt(2), sqrt(2)");
+        assert_eq!(solve("solve(x^2 + 1 = 0, x)"), "x = −i, i");
+        assert_eq!(solve("solve(x^2 + 2x + 5 = 0, x)"), "x = −1 − 2i, −1 + 2i");
+        assert_eq!(solve("solve(0.5x^2 + x = 0, x)"), "x = −2, 0");
+        assert_eq!(solve("solve(x^2 - 2.25 = 0, x)"), "x = −1.5, 1.5");
+        assert_eq!(
+            solve("solve(x^2 + 2x + 1.25 = 0, x)"),
+            "x = −1 − 0.5i, −1 + 0.5i"
+        );
+        assert_eq!(
+            solve("solve(x^2 = -0.5, x)"),
+            "x = −0.7071067811865476i, 0.7071067811865476i"
+        );
+    }
+
+    #[test]
+    fn imaginary_unit() {
+        let tokens = crate::lexer::parse("x^2 + 1 = 0").unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let crate::ast::Statement::Equation(equation) = &ast.statements[0] else {
+            panic!("not an equation");
+        };
+        let x = crate::ast::Identifier { text: "x" };
+        let roots = |unit| {
+            let solutions = equation.solve_with_unit(&x, unit).unwrap();
+            Unicode(&solutions.to_expression(std::slice::from_ref(&x))).to_string()
+        };
+        assert_eq!(roots(Some("j")), "x = −j, j");
+        assert_eq!(roots(None), "x ∈ {}");
+    }
+
+    #[test]
+    fn systems() {
+        assert_eq!(solve("solve({x + y = 3, x - y = 1}, x, y)"), "x = 2, y = 1");
+        assert_eq!(
+            solve("solve([2a + b = 1, a - b = 1/2], [a, b])"),
+            "a = 1/2, b = 0"
+        );
+        assert_eq!(
+            solve("solve({x + y = 1, x + y = 2}, x, y)"),
+            "x ∈ {}, y ∈ {}"
+        );
+        assert_eq!(
+            solve("solve({x + y = 1, 2x + 2y = 2}, x, y)"),
+            "solve({x + y = 1, 2x + 2y = 2}, x, y)"
+        );
+    }
+
+    #[test]
+    fn numeric() {
+        assert_eq!(solve("solve(x^3 - 6x^2 + 11x - 6 = 0, x)"), "x = 1, 2, 3");
+        assert_eq!(solve("solve(x^3 - 200x^2 = 0, x)"), "x = 0, 200");
+        assert_eq!(solve("solve((x - 1/2)^2 (x - 3) = 0, x)"), "x = 1/2, 3");
+        // Only some of the solutions are found, so they're not written as if they were all
+        assert_eq!(solve("solve(x^4 = 1, x)"), "solve(x⁴ = 1, x)");
+        assert_eq!(solve("solve(sin(x) = 0, x)"), "solve(sin(x) = 0, x)");
+        assert_eq!(solve("solve(e^x = -1, x)"), "solve(e^x = −1, x)");
+    }
+
+    #[test]
+    fn found() {
+        let found = |str| {
+            let tokens = crate::lexer::parse(str).unwrap();
+            let ast = crate::ast::parse(&tokens).unwrap();
+            let crate::ast::Statement::Equation(equation) = &ast.statements[0] else {
+                panic!("not an equation");
+            };
+            let x = crate::ast::Identifier { text: "x" };
+            let solutions = equation.solve(&x).unwrap();
+            assert!(matches!(solutions, super::Solutions::Found(_)), "{str}");
+            Unicode(&solutions.to_expression(std::slice::from_ref(&x))).to_string()
+        };
+        assert_eq!(found("x^4 = 1"), "x ≈ −1, 1");
+        assert_eq!(found("x = cos(x)"), "x ≈ 0.7390851332151607");
+        assert_eq!(
+            found("0 = sin(x)").matches(", ").count() + 1,
+            super::MAX_ROOTS
+        );
+    }
+}
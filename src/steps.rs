@@ -0,0 +1,455 @@
+//! Working out shown one rule at a time, as a [`Derivation`] with each rule as its justification.
+//! Derivatives are taken apart from the outside in, then what's left is simplified
+//! and an equation in one unknown is solved, like
+//! ```text
+//! d/dx(x sin(x))
+//!   = d/dx(x) sin(x) + x d/dx(sin(x))  "product rule"
+//!   = sin(x) + x cos(x)                "standard derivative"
+//! ```
+
+use crate::{
+    ast::*,
+    differentiate::{self, rule},
+    eval::Environment,
+    simplify::Rules,
+    unicode::Unicode,
+};
+
+/// Most derivative steps before giving up and simplifying what's left
+const MAX_STEPS: usize = 100;
+
+impl<'src> Ast<'src> {
+    /// [`Expression::steps`] for each expression and equation in turn, with what's defined before
+    /// it substituted in as the first step. Definitions and derivations that are already written
+    /// out have nothing to work out.
+    pub fn steps(&self) -> Vec<Derivation<'src>> {
+        let mut definitions = Vec::new();
+        let mut derivations = Vec::new();
+        for statement in &self.statements {
+            let expression = match statement {
+                Statement::Expression(expression) => expression.clone(),
+                Statement::Equation(relation) => Expression::Relation(relation.clone()),
+                Statement::Definition(definition) => {
+                    let value = substitute(&definition.value, &definitions, &[]);
+                    definitions.push(Statement::Definition(Definition {
+                        value,
+                        ..definition.clone()
+                    }));
+                    continue;
+                }
+                Statement::FunctionDefinition(function) => {
+                    let body = substitute(&function.body, &definitions, &function.parameters);
+                    definitions.push(Statement::FunctionDefinition(FunctionDefinition {
+                        body,
+                        ..function.clone()
+                    }));
+                    continue;
+                }
+                Statement::Derivation(_) => continue,
+            };
+            let substituted = substitute(&expression, &definitions, &[]);
+            if substituted == expression {
+                derivations.push(expression.steps());
+                continue;
+            }
+            let mut derivation = substituted.steps();
+            let first = &mut derivation.steps[0];
+            first.kind = continuing(&expression);
+            first.justification = Some("substitute");
+            derivation.steps.insert(
+                0,
+                Step {
+                    kind: StepKind::Start,
+                    expression,
+                    justification: None,
+                    text: None,
+                },
+            );
+            derivations.push(derivation);
+        }
+        derivations
+    }
+}
+
+impl<'src> Expression<'src> {
+    /// How [`simplify`](Expression::simplify) gets to its answer. Each step continues from the
+    /// last with `=`, or follows from it with `⟹` for a relation.
+    pub fn steps(&self) -> Derivation<'src> {
+        let kind = continuing(self);
+        let mut steps = vec![Step {
+            kind: StepKind::Start,
+            expression: self.clone(),
+            justification: None,
+            text: None,
+        }];
+        let mut expression = self.clone();
+        while steps.len() <= MAX_STEPS {
+            let Some((next, rule)) = differentiate_step(&expression) else {
+                break;
+            };
+            steps.push(Step {
+                kind,
+                expression: next.clone(),
+                justification: Some(rule),
+                text: None,
+            });
+            expression = next;
+        }
+
+        // Simplified with one more rule allowed each time, so each rule gets its own step
+        let differentiated = steps.len();
+        for limit in 1.. {
+            let mut rules = Rules::new(limit);
+            let mut simplified = expression.simplify_noting(&mut rules);
+            let rule = (steps.len() <= MAX_STEPS)
+                .then(|| rules.used.get(limit - 1).copied())
+                .flatten();
+            if rule.is_none() {
+                simplified = expression.simplify();
+            }
+            let justification = rule.unwrap_or("simplify");
+            let simplifying = steps.len() > differentiated;
+            let last = steps.last_mut().expect("there's a first step");
+            // Only steps that change how it reads are worth showing, and a rule used again
+            // right away adds to the step it was last used in
+            let changed = Unicode(&simplified).to_string() != Unicode(&last.expression).to_string();
+            match changed && simplifying && last.justification == Some(justification) {
+                true => last.expression = simplified,
+                false if changed => steps.push(Step {
+                    kind,
+                    expression: simplified,
+                    justification: Some(justification),
+                    text: None,
+                }),
+                false => {}
+            }
+            if rule.is_none() {
+                break;
+            }
+        }
+
+        // An equation in one unknown is then solved for it
+        let last = &steps.last().expect("there's a first step").expression;
+        if let Some(solved) = solved(last) {
+            steps.push(Step {
+                kind,
+                expression: solved,
+                justification: Some("solve"),
+                text: None,
+            });
+        }
+        Derivation { steps }
+    }
+}
+
+/// How each step after the first of `expression`'s working out follows on
+fn continuing(expression: &Expression<'_>) -> StepKind {
+    match expression {
+        Expression::Relation(_) => StepKind::Implies,
+        _ => StepKind::Continues(RelationOperator::Equal),
+    }
+}
+
+/// `expression` with each name in `definitions` replaced by what it's defined as, the latest
+/// definition of a name winning. Names in `hidden`, and names a derivative or sum binds, are
+/// left alone where they're bound.
+fn substitute<'src>(
+    expression: &Expression<'src>,
+    definitions: &[Statement<'src>],
+    hidden: &[Identifier<'src>],
+) -> Expression<'src> {
+    let defined = |identifier: &Identifier<'src>| {
+        let name = Some(identifier.text);
+        let hidden = hidden.contains(identifier);
+        (!hidden)
+            .then(|| {
+                definitions
+                    .iter()
+                    .rev()
+                    .find(|statement| statement.defined_name() == name)
+            })
+            .flatten()
+    };
+    let hiding = |bound: &[Identifier<'src>]| [hidden, bound].concat();
+    expression.rewrite(&mut |part| match part {
+        Expression::Variable(variable) => match defined(&variable.identifier)? {
+            Statement::Definition(definition) => Some(definition.value.clone()),
+            _ => None,
+        },
+        // `v_1` for `let v_1 = 3`
+        Expression::Subscript(_) => {
+            definitions
+                .iter()
+                .rev()
+                .find_map(|statement| match statement {
+                    Statement::Definition(definition)
+                        if definition.index.is_some()
+                            && !hidden.contains(&definition.identifier)
+                            && definition.name() == *part =>
+                    {
+                        Some(definition.value.clone())
+                    }
+                    _ => None,
+                })
+        }
+        Expression::FunctionCall(call) => match defined(&call.identifier)? {
+            Statement::FunctionDefinition(function)
+                if function.parameters.len() == call.inputs.len() =>
+            {
+                let inputs: Vec<_> = call
+                    .inputs
+                    .iter()
+                    .map(|input| substitute(input, definitions, hidden))
+                    .collect();
+                Some(function.body.rewrite(&mut |part| {
+                    match part {
+                        Expression::Variable(variable) => function
+                            .parameters
+                            .iter()
+                            .position(|parameter| *parameter == variable.identifier)
+                            .map(|position| inputs[position].clone()),
+                        _ => None,
+                    }
+                }))
+            }
+            _ => None,
+        },
+        Expression::Derivative(Derivative::Leibniz { variable, body }) => {
+            let hidden = hiding(std::slice::from_ref(variable));
+            Some(Expression::Derivative(Derivative::Leibniz {
+                variable: variable.clone(),
+                body: Box::new(substitute(body, definitions, &hidden)),
+            }))
+        }
+        Expression::Derivative(Derivative::Partial { body, variables }) => {
+            Some(Expression::Derivative(Derivative::Partial {
+                body: Box::new(substitute(body, definitions, &hiding(variables))),
+                variables: variables.clone(),
+            }))
+        }
+        Expression::BigOperator(big_operator) => {
+            let hidden_in_body = hiding(std::slice::from_ref(&big_operator.index));
+            let substitute =
+                |expression, hidden| Box::new(substitute(expression, definitions, hidden));
+            Some(Expression::BigOperator(BigOperator {
+                from: substitute(&big_operator.from, hidden),
+                to: substitute(&big_operator.to, hidden),
+                body: substitute(&big_operator.body, &hidden_in_body),
+                ..big_operator.clone()
+            }))
+        }
+        _ => None,
+    })
+}
+
+/// `x = 2, 3` for an equation like `x^2 - 5x + 6 = 0` with only `x` to solve for
+fn solved<'src>(expression: &Expression<'src>) -> Option<Expression<'src>> {
+    let Expression::Relation(relation) = expression else {
+        return None;
+    };
+    let mut unknowns: Vec<Identifier<'src>> = Vec::new();
+    expression.rewrite(&mut |expression| {
+        if let Expression::Variable(variable) = expression {
+            // Constants like `pi` are already known
+            let known = Environment::new().evaluate(expression).is_ok();
+            if !known && !unknowns.contains(&variable.identifier) {
+                unknowns.push(variable.identifier.clone());
+            }
+        }
+        None
+    });
+    let [unknown] = unknowns.as_slice() else {
+        return None;
+    };
+    // Already solved when it's `x = ...` with no `x` on the right
+    if matches!(relation.first.as_ref(), Expression::Variable(variable) if variable.identifier == *unknown)
+        && relation
+            .rest
+            .iter()
+            .all(|(_, right)| !right.mentions(unknown))
+    {
+        return None;
+    }
+    let solutions = relation.solve(unknown).ok()?;
+    Some(solutions.to_expression(std::slice::from_ref(unknown)))
+}
+
+/// Takes apart every derivative the first one that can be taken apart does it with, using the
+/// same rule. [`None`] when there's nothing left to take apart.
+fn differentiate_step<'src>(
+    expression: &Expression<'src>,
+) -> Option<(Expression<'src>, &'static str)> {
+    let mut first_rule = None;
+    let rewritten = expression.rewrite(&mut |expression| {
+        let Expression::Derivative(derivative) = expression else {
+            return None;
+        };
+        let (taken_apart, rule) = take_apart(derivative).ok()?;
+        (*first_rule.get_or_insert(rule) == rule).then_some(taken_apart)
+    });
+    first_rule.map(|rule| (rewritten, rule))
+}
+
+/// One rule applied to the outside of the derivative, leaving the derivatives of the parts
+/// unworked
+fn take_apart<'src>(
+    derivative: &Derivative<'src>,
+) -> Result<(Expression<'src>, &'static str), differentiate::Error> {
+    match derivative {
+        Derivative::Leibniz { variable, body } => rule(body, variable, &|part| {
+            Ok(unworked(part, variable, |body| Derivative::Leibniz {
+                variable: variable.clone(),
+                body,
+            }))
+        }),
+        Derivative::Partial { body, variables } => {
+            let (variable, rest) =
+                variables
+                    .split_first()
+                    .ok_or(differentiate::Error::Unsupported(
+                        "a partial derivative without variables",
+                    ))?;
+            let (taken_apart, rule) = rule(body, variable, &|part| {
+                Ok(unworked(part, variable, |body| Derivative::Partial {
+                    body,
+                    variables: vec![variable.clone()],
+                }))
+            })?;
+            if rest.is_empty() {
+                return Ok((taken_apart, rule));
+            }
+            let rest = Derivative::Partial {
+                body: Box::new(taken_apart),
+                variables: rest.to_vec(),
+            };
+            Ok((Expression::Derivative(rest), rule))
+        }
+        Derivative::Prime { .. } => Ok((derivative.compute()?, "standard derivative")),
+    }
+}
+
+/// The derivative of `part` left as it is, unless it's just 0 or 1
+fn unworked<'src>(
+    part: &Expression<'src>,
+    variable: &Identifier<'_>,
+    derivative: impl Fn(Box<Expression<'src>>) -> Derivative<'src>,
+) -> Expression<'src> {
+    match part {
+        _ if !part.mentions(variable) => Expression::Number(0.into()),
+        Expression::Variable(_) => Expression::Number(1.into()),
+        _ => Expression::Derivative(derivative(Box::new(part.clone()))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ast::*, latex::Latex, unicode::Unicode};
+
+    fn steps(str: &str) -> Vec<String> {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let Some(derivation) = ast.steps().pop() else {
+            panic!("{ast:?}");
+        };
+        derivation
+            .steps
+            .iter()
+            .map(|step| match step.justification {
+                Some(justification) => {
+                    format!("{}  ({justification})", Unicode(&step.expression))
+                }
+                None => Unicode(&step.expression).to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn derivatives() {
+        assert_eq!(
+            steps("d/dx(x*sin(x))"),
+            [
+                "d/dx(x·sin(x))",
+                "sin(x) + x·d/dx(sin(x))  (product rule)",
+                "sin(x) + x·cos(x)  (standard derivative)",
+            ]
+        );
+        assert_eq!(
+            steps("diff(sin(x^2), x)"),
+            [
+                "d/dx(sin(x²))",
+                "d/dx(x²)cos(x²)  (chain rule)",
+                "2x^(2 − 1)·cos(x²)  (power rule)",
+                "2x·cos(x²)  (evaluate)",
+            ]
+        );
+    }
+
+    #[test]
+    fn simplifying() {
+        assert_eq!(steps("2x + 3x"), ["2x + 3x", "5x  (combine like terms)"]);
+        assert_eq!(steps("1 + 2*3"), ["1 + 2·3", "7  (evaluate)"]);
+        assert_eq!(steps("x"), ["x"]);
+        assert_eq!(
+            steps("2x + 3x + 1/3 + 1/6"),
+            [
+                "2x + 3x + 1/3 + 1/6",
+                "5x + 1/3 + 1/6  (combine like terms)",
+                "5x + 1/2  (evaluate)",
+            ]
+        );
+        assert_eq!(steps("x^2 - 4 = 0"), ["x² − 4 = 0", "x = −2, 2  (solve)"]);
+        assert_eq!(
+            steps("2(x + 1) = 3x - (1 + 1)"),
+            [
+                "2(x + 1) = 3x − (1 + 1)",
+                "2(x + 1) = 3x − 2  (evaluate)",
+                "x = 4  (solve)",
+            ]
+        );
+    }
+
+    #[test]
+    fn definitions() {
+        assert_eq!(
+            steps("let a = x + 1\nf(t) = t^2\nd/dx(f(a))"),
+            [
+                "d/dx(f(a))",
+                "d/dx((x + 1)²)  (substitute)",
+                "d/dx(x + 1)·2(x + 1)^(2 − 1)  (chain rule)",
+                "(1 + 0)·2(x + 1)^(2 − 1)  (sum rule)",
+                "2(x + 1)  (evaluate)",
+            ]
+        );
+        // What a derivative or sum binds isn't the definition
+        assert_eq!(
+            steps("let x = 3\nd/dx(x^2) + x"),
+            [
+                "d/dx(x²) + x",
+                "d/dx(x²) + 3  (substitute)",
+                "2x^(2 − 1) + 3  (power rule)",
+                "2x + 3  (evaluate)",
+            ]
+        );
+        assert_eq!(steps("let a = 2\na = 2; 2x"), ["2x"]);
+    }
+
+    #[test]
+    fn renders() {
+        let tokens = crate::lexer::parse("diff(x^2 + x, x)").unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let [Statement::Expression(expression)] = ast.statements.as_slice() else {
+            panic!("{ast:?}");
+        };
+        assert_eq!(
+            Latex::new(&expression.steps()).to_string(),
+            [
+                r"\begin{align*}",
+                r"\frac{d}{dx}(x^{2} + x) &= \frac{d}{dx}(x^{2}) + 1 && \text{sum rule} \\",
+                r"&= 2x^{2 - 1} + 1 && \text{power rule} \\",
+                r"&= 2x + 1 && \text{evaluate}",
+                r"\end{align*}",
+            ]
+            .join("\n")
+        );
+    }
+}
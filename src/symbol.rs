@@ -0,0 +1,105 @@
+//! Named mathematical symbols such as greek letters.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol {
+    /// What to type in hometex
+    pub name: &'static str,
+    pub latex: &'static str,
+    pub unicode: char,
+}
+
+macro_rules! symbols {
+    ($($name:literal => $latex:literal, $unicode:literal,)+) => {
+        pub const SYMBOLS: &[Symbol] = &[
+            $(Symbol { name: $name, latex: $latex, unicode: $unicode },)+
+        ];
+    };
+}
+
+symbols! {
+    "alpha" => r"\alpha", 'α',
+    "beta" => r"\beta", 'β',
+    "gamma" => r"\gamma", 'γ',
+    "delta" => r"\delta", 'δ',
+    "epsilon" => r"\epsilon", 'ϵ',
+    "varepsilon" => r"\varepsilon", 'ε',
+    "zeta" => r"\zeta", 'ζ',
+    "eta" => r"\eta", 'η',
+    "theta" => r"\theta", 'θ',
+    "vartheta" => r"\vartheta", 'ϑ',
+    "iota" => r"\iota", 'ι',
+    "kappa" => r"\kappa", 'κ',
+    "lambda" => r"\lambda", 'λ',
+    "mu" => r"\mu", 'μ',
+    "nu" => r"\nu", 'ν',
+    "xi" => r"\xi", 'ξ',
+    "omicron" => "o", 'ο',
+    "pi" => r"\pi", 'π',
+    "rho" => r"\rho", 'ρ',
+    "sigma" => r"\sigma", 'σ',
+    "tau" => r"\tau", 'τ',
+    "upsilon" => r"\upsilon", 'υ',
+    "phi" => r"\phi", 'ϕ',
+    "varphi" => r"\varphi", 'φ',
+    "chi" => r"\chi", 'χ',
+    "psi" => r"\psi", 'ψ',
+    "omega" => r"\omega", 'ω',
+    "Gamma" => r"\Gamma", 'Γ',
+    "Delta" => r"\Delta", 'Δ',
+    "Theta" => r"\Theta", 'Θ',
+    "Lambda" => r"\Lambda", 'Λ',
+    "Xi" => r"\Xi", 'Ξ',
+    "Pi" => r"\Pi", 'Π',
+    "Sigma" => r"\Sigma", 'Σ',
+    "Upsilon" => r"\Upsilon", 'Υ',
+    "Phi" => r"\Phi", 'Φ',
+    "Psi" => r"\Psi", 'Ψ',
+    "Omega" => r"\Omega", 'Ω',
+    "infinity" => r"\infty", '∞',
+    "nabla" => r"\nabla", '∇',
+    "partial" => r"\partial", '∂',
+    "hbar" => r"\hbar", 'ℏ',
+    "ell" => r"\ell", 'ℓ',
+    "aleph" => r"\aleph", 'ℵ',
+    "emptyset" => r"\emptyset", '∅',
+}
+
+impl Symbol {
+    /// Symbols by both their name and their unicode character
+    pub fn map() -> &'static HashMap<String, Symbol> {
+        static MAP: OnceLock<HashMap<String, Symbol>> = OnceLock::new();
+        MAP.get_or_init(|| {
+            SYMBOLS
+                .iter()
+                .flat_map(|s| [(s.name.to_string(), *s), (s.unicode.to_string(), *s)])
+                .collect()
+        })
+    }
+
+    /// Symbol named `str`, or written as its unicode character
+    pub fn recognize(str: &str) -> Option<Symbol> {
+        Symbol::map().get(str).copied()
+    }
+
+    /// Is `c` a symbol that isn't a letter, like `∞`
+    pub fn is_symbol_char(c: char) -> bool {
+        !c.is_alphanumeric() && SYMBOLS.iter().any(|s| s.unicode == c)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recognize() {
+        assert_eq!(Symbol::recognize("theta").unwrap().latex, r"\theta");
+        assert_eq!(Symbol::recognize("θ").unwrap().name, "theta");
+        assert_eq!(Symbol::recognize("infinity").unwrap().unicode, '∞');
+        assert_eq!(Symbol::recognize("thetas"), None);
+        assert!(Symbol::is_symbol_char('∞'));
+        assert!(!Symbol::is_symbol_char('θ'));
+    }
+}
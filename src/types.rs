@@ -0,0 +1,570 @@
+//! Types of expressions. Works out whether each part is a number, a vector, a matrix, a set or
+//! true or false, so meaningless things like adding a set to a number, or multiplying matrices
+//! whose shapes don't fit, are caught before they're evaluated or written out.
+
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt,
+    rc::Rc,
+};
+
+use crate::{ast::*, diagnostic::Diagnostic, eval, resolve, units::fragment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Type {
+    /// A number, real or complex, with or without a unit
+    Scalar,
+    /// `[1, 2, 3]`, with its length
+    Vector(usize),
+    /// `[[1, 2], [3, 4]]`, with its rows and columns
+    Matrix(usize, usize),
+    Set,
+    /// What a relation gives
+    Bool,
+}
+
+impl Type {
+    /// Numbers, vectors and matrices, which can be added and scaled
+    fn is_numeric(&self) -> bool {
+        !matches!(self, Type::Set | Type::Bool)
+    }
+}
+
+/// `a 2×3 matrix`
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Scalar => write!(f, "a number"),
+            Type::Vector(length) => write!(f, "a vector of length {length}"),
+            Type::Matrix(rows, columns) => write!(f, "a {rows}×{columns} matrix"),
+            Type::Set => write!(f, "a set"),
+            Type::Bool => write!(f, "true or false"),
+        }
+    }
+}
+
+/// What each name defined so far is, like the `A` of `let A = [[1, 2], [3, 4]]`
+#[derive(Debug, Clone, Default)]
+pub struct Types<'src> {
+    definitions: HashMap<&'src str, Type>,
+    /// Source of the statement being checked
+    source: &'src str,
+    /// Where to note the address of each product of two matrices, see
+    /// [`Binary::matrix_products`]
+    matrix_products: Option<Rc<RefCell<HashSet<usize>>>>,
+}
+
+impl<'src> Types<'src> {
+    /// Without `name`, for inside something that binds it again, like the `A` of `sum(A, 1, 3, A)`
+    fn without(&self, name: &str) -> Cow<'_, Self> {
+        if !self.definitions.contains_key(name) {
+            return Cow::Borrowed(self);
+        }
+        let mut types = self.clone();
+        types.definitions.remove(name);
+        Cow::Owned(types)
+    }
+
+    /// Source to point a diagnostic about `parts` at: that of the first one with a variable,
+    /// number or unit in it, or else the whole statement, like for the `{}` of `-{}`
+    fn fragment(&self, parts: &[&Expression<'src>]) -> &'src str {
+        parts
+            .iter()
+            .find_map(|part| fragment(part))
+            .unwrap_or(self.source)
+    }
+}
+
+impl<'src> Statement<'src> {
+    /// Finds the first place something is used where it can't be, like `{1, 2} + 3`, knowing the
+    /// definitions before it in `types`. A definition adds what it defines. `source` is the
+    /// statement's, from [`Ast::sources`].
+    pub fn check_types(
+        &self,
+        source: &'src str,
+        types: &mut Types<'src>,
+    ) -> Result<(), Diagnostic<'src>> {
+        types.source = source;
+        match self {
+            Statement::Definition(definition) => {
+                let value = definition.value.infer_type_in(types)?;
+                let name = definition.identifier.text;
+                match value {
+                    Some(value) if definition.index.is_none() => {
+                        types.definitions.insert(name, value);
+                    }
+                    _ => {
+                        types.definitions.remove(name);
+                    }
+                }
+                Ok(())
+            }
+            Statement::FunctionDefinition(function_definition) => {
+                types
+                    .definitions
+                    .remove(function_definition.identifier.text);
+                let mut body_types = Cow::Borrowed(&*types);
+                for parameter in &function_definition.parameters {
+                    body_types = Cow::Owned(body_types.without(parameter.text).into_owned());
+                }
+                function_definition
+                    .body
+                    .infer_type_in(&body_types)
+                    .map(drop)
+            }
+            Statement::Equation(relation) => relation.infer_type_in(types).map(drop),
+            Statement::Expression(expression) => expression.infer_type_in(types).map(drop),
+            Statement::Derivation(derivation) => derivation
+                .steps
+                .iter()
+                .try_for_each(|step| step.expression.infer_type_in(types).map(drop)),
+        }
+    }
+}
+
+impl<'src> Expression<'src> {
+    /// What the expression is, or a diagnostic where a part is used where it can't be. [`None`]
+    /// when it isn't known, like for a variable or a user defined function. `source` is what the
+    /// expression was parsed from.
+    pub fn infer_type(&self, source: &'src str) -> Result<Option<Type>, Diagnostic<'src>> {
+        self.infer_type_in(&Types {
+            source,
+            ..Types::default()
+        })
+    }
+
+    /// Like [`Expression::infer_type`], where the variables defined in `types` are known
+    fn infer_type_in(&self, types: &Types<'src>) -> Result<Option<Type>, Diagnostic<'src>> {
+        match self {
+            Expression::Literal(_) | Expression::Number(_) => Ok(Some(Type::Scalar)),
+            Expression::Quantity(quantity) => {
+                expect(&quantity.value, Type::Scalar, types)?;
+                Ok(Some(Type::Scalar))
+            }
+            Expression::Variable(variable) => {
+                Ok(types.definitions.get(variable.identifier.text).copied())
+            }
+            Expression::FunctionCall(function_call) => function_call.infer_type_in(types),
+            Expression::Subscript(subscript) => {
+                subscript.base.infer_type_in(types)?;
+                subscript.index.infer_type_in(types)?;
+                Ok(None)
+            }
+            Expression::Unary(unary) => match unary.operand.infer_type_in(types)? {
+                Some(operand) if !operand.is_numeric() => Err(Diagnostic::error(
+                    types.fragment(&[&unary.operand]),
+                    format!("cannot negate {operand}"),
+                )),
+                operand => Ok(operand),
+            },
+            Expression::Binary(binary) => binary.infer_type_in(types),
+            Expression::Relation(relation) => relation.infer_type_in(types),
+            Expression::BigOperator(big_operator) => {
+                expect(&big_operator.from, Type::Scalar, types)?;
+                expect(&big_operator.to, Type::Scalar, types)?;
+                numeric(&big_operator.body, &types.without(big_operator.index.text))
+            }
+            Expression::Integral(integral) => {
+                if let Some((lower, upper)) = &integral.bounds {
+                    expect(lower, Type::Scalar, types)?;
+                    expect(upper, Type::Scalar, types)?;
+                }
+                numeric(&integral.integrand, &types.without(integral.variable.text))
+            }
+            Expression::Limit(limit) => {
+                expect(&limit.approaching, Type::Scalar, types)?;
+                numeric(&limit.body, &types.without(limit.variable.text))
+            }
+            Expression::Derivative(Derivative::Leibniz { body, .. })
+            | Expression::Derivative(Derivative::Partial { body, .. }) => numeric(body, types),
+            Expression::Derivative(Derivative::Prime { inputs, .. }) => {
+                for input in inputs.iter().flatten() {
+                    expect(input, Type::Scalar, types)?;
+                }
+                Ok(None)
+            }
+            Expression::Vector(vector) => {
+                for element in &vector.elements {
+                    expect(element, Type::Scalar, types)?;
+                }
+                Ok(Some(Type::Vector(vector.elements.len())))
+            }
+            Expression::Matrix(matrix) => {
+                for element in matrix.rows.iter().flatten() {
+                    expect(element, Type::Scalar, types)?;
+                }
+                let columns = matrix.rows.first().map_or(0, Vec::len);
+                Ok(Some(Type::Matrix(matrix.rows.len(), columns)))
+            }
+            Expression::Piecewise(piecewise) => {
+                let mut values = Vec::new();
+                for arm in &piecewise.arms {
+                    values.push(arm.value.infer_type_in(types)?);
+                    expect(&arm.condition, Type::Bool, types)?;
+                }
+                if let Some(otherwise) = &piecewise.otherwise {
+                    values.push(otherwise.infer_type_in(types)?);
+                }
+                // Known only when every piece is the same
+                let first = values.first().copied().flatten();
+                Ok(first.filter(|first| values.iter().all(|value| *value == Some(*first))))
+            }
+            Expression::Set(set) => {
+                for element in &set.elements {
+                    element.infer_type_in(types)?;
+                }
+                Ok(Some(Type::Set))
+            }
+            Expression::SetBuilder(set_builder) => {
+                if let Some(domain) = &set_builder.domain {
+                    expect(domain, Type::Set, types)?;
+                }
+                let types = types.without(set_builder.variable.text);
+                expect(&set_builder.condition, Type::Bool, &types)?;
+                Ok(Some(Type::Set))
+            }
+            Expression::Interval(interval) => {
+                expect(&interval.lower, Type::Scalar, types)?;
+                expect(&interval.upper, Type::Scalar, types)?;
+                Ok(Some(Type::Set))
+            }
+            Expression::NumberSet(_) => Ok(Some(Type::Set)),
+            // `|A|` is also the determinant of a matrix and the size of a set
+            Expression::Delimited(delimited) => match delimited.kind {
+                DelimiterKind::Abs | DelimiterKind::Norm => {
+                    match delimited.inner.infer_type_in(types)? {
+                        Some(Type::Bool) => expect(&delimited.inner, Type::Scalar, types),
+                        _ => Ok(Some(Type::Scalar)),
+                    }
+                }
+                DelimiterKind::Floor | DelimiterKind::Ceil => {
+                    expect(&delimited.inner, Type::Scalar, types)?;
+                    Ok(Some(Type::Scalar))
+                }
+            },
+            Expression::Group(group) => group.inner.infer_type_in(types),
+            Expression::List(list) => {
+                for element in &list.elements {
+                    element.infer_type_in(types)?;
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl<'src> Binary<'src> {
+    /// Addresses of the `*` products of two matrices in this and everything in it, found in one
+    /// pass, so a backend can write them with a cross without working out types at every product
+    pub(crate) fn matrix_products(&self) -> HashSet<usize> {
+        let products = Rc::default();
+        let types = Types {
+            matrix_products: Some(Rc::clone(&products)),
+            ..Types::default()
+        };
+        // What can be worked out before a type error is still noted
+        self.infer_type_in(&types).ok();
+        products.take()
+    }
+
+    fn infer_type_in(&self, types: &Types<'src>) -> Result<Option<Type>, Diagnostic<'src>> {
+        if let BinaryOperator::Union | BinaryOperator::Intersection | BinaryOperator::Difference =
+            self.operator
+        {
+            expect(&self.left, Type::Set, types)?;
+            expect(&self.right, Type::Set, types)?;
+            return Ok(Some(Type::Set));
+        }
+        let (Some(left), Some(right)) = (
+            self.left.infer_type_in(types)?,
+            self.right.infer_type_in(types)?,
+        ) else {
+            return Ok(None);
+        };
+        let result = match (self.operator, left, right) {
+            (BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::PlusMinus, _, _)
+                if left == right && left.is_numeric() =>
+            {
+                Some(left)
+            }
+            (BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply, scaled, Type::Scalar)
+            | (BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply, Type::Scalar, scaled)
+                if scaled.is_numeric() =>
+            {
+                Some(scaled)
+            }
+            // A dot product, and products of matrices whose shapes fit
+            (
+                BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply,
+                Type::Vector(left),
+                Type::Vector(right),
+            ) if left == right => Some(Type::Scalar),
+            (
+                BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply,
+                Type::Matrix(rows, columns),
+                Type::Matrix(right_rows, right_columns),
+            ) if columns == right_rows => Some(Type::Matrix(rows, right_columns)),
+            (
+                BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply,
+                Type::Matrix(rows, columns),
+                Type::Vector(length),
+            ) if columns == length => Some(Type::Vector(rows)),
+            (BinaryOperator::Divide, divided, Type::Scalar) if divided.is_numeric() => {
+                Some(divided)
+            }
+            (BinaryOperator::Power, Type::Scalar, Type::Scalar) => Some(Type::Scalar),
+            (BinaryOperator::Power, Type::Matrix(rows, columns), Type::Scalar)
+                if rows == columns =>
+            {
+                Some(left)
+            }
+            _ => None,
+        };
+        if let (Some(products), BinaryOperator::Multiply, Some(_)) =
+            (&types.matrix_products, self.operator, result)
+        {
+            if matches!((left, right), (Type::Matrix(..), Type::Matrix(..))) {
+                products.borrow_mut().insert(self as *const Self as usize);
+            }
+        }
+        if result.is_some() {
+            return Ok(result);
+        }
+        let message = match self.operator {
+            BinaryOperator::Add | BinaryOperator::PlusMinus => {
+                format!("cannot add {right} to {left}")
+            }
+            BinaryOperator::Subtract => format!("cannot subtract {right} from {left}"),
+            BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => {
+                format!("cannot multiply {left} by {right}")
+            }
+            BinaryOperator::Divide => format!("cannot divide {left} by {right}"),
+            BinaryOperator::Power => format!("cannot raise {left} to the power of {right}"),
+            BinaryOperator::Union | BinaryOperator::Intersection | BinaryOperator::Difference => {
+                unreachable!("set operations are handled above")
+            }
+        };
+        let fragment = types.fragment(&[&self.right, &self.left]);
+        Err(Diagnostic::error(fragment, message))
+    }
+}
+
+impl<'src> Relation<'src> {
+    /// Checks each neighbouring pair can be compared. Only numbers have an order, and only sets
+    /// can hold something or be a subset.
+    fn infer_type_in(&self, types: &Types<'src>) -> Result<Option<Type>, Diagnostic<'src>> {
+        let mut left = &*self.first;
+        let mut left_type = left.infer_type_in(types)?;
+        for (operator, right) in &self.rest {
+            let right_type = right.infer_type_in(types)?;
+            match operator {
+                RelationOperator::Equal
+                | RelationOperator::NotEqual
+                | RelationOperator::Approximately => {
+                    if let (Some(l), Some(r)) = (left_type, right_type) {
+                        if l != r {
+                            return Err(Diagnostic::error(
+                                types.fragment(&[right, left]),
+                                format!("cannot compare {r} with {l}"),
+                            ));
+                        }
+                    }
+                }
+                RelationOperator::Less
+                | RelationOperator::LessEqual
+                | RelationOperator::Greater
+                | RelationOperator::GreaterEqual => {
+                    expect(left, Type::Scalar, types)?;
+                    expect(right, Type::Scalar, types)?;
+                }
+                RelationOperator::In => {
+                    expect(right, Type::Set, types)?;
+                }
+                RelationOperator::Subset => {
+                    expect(left, Type::Set, types)?;
+                    expect(right, Type::Set, types)?;
+                }
+            }
+            left = right;
+            left_type = right_type;
+        }
+        Ok(Some(Type::Bool))
+    }
+}
+
+impl<'src> FunctionCall<'src> {
+    /// Builtins only take numbers, and so do the special forms left as calls, like `to`. Nothing
+    /// is known about what user defined functions take.
+    fn infer_type_in(&self, types: &Types<'src>) -> Result<Option<Type>, Diagnostic<'src>> {
+        let name = self.identifier.text;
+        let numeric = resolve::special_form(name).is_some_and(|form| form.numeric);
+        if eval::builtin_arity(name).is_none() && !numeric {
+            for input in &self.inputs {
+                input.infer_type_in(types)?;
+            }
+            return Ok(None);
+        }
+        for input in &self.inputs {
+            expect(input, Type::Scalar, types)?;
+        }
+        Ok(Some(Type::Scalar))
+    }
+}
+
+/// `expected`, or [`None`] when it isn't known. A diagnostic when it's something else.
+fn expect<'src>(
+    expression: &Expression<'src>,
+    expected: Type,
+    types: &Types<'src>,
+) -> Result<Option<Type>, Diagnostic<'src>> {
+    match expression.infer_type_in(types)? {
+        Some(found) if found != expected => Err(Diagnostic::error(
+            types.fragment(&[expression]),
+            format!("expected {expected}, found {found}"),
+        )),
+        found => Ok(found),
+    }
+}
+
+/// Like a sum's body, which can be a number, vector or matrix but not a set
+fn numeric<'src>(
+    expression: &Expression<'src>,
+    types: &Types<'src>,
+) -> Result<Option<Type>, Diagnostic<'src>> {
+    match expression.infer_type_in(types)? {
+        Some(found) if !found.is_numeric() => expect(expression, Type::Scalar, types),
+        found => Ok(found),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn infer(str: &str) -> Result<Option<Type>, String> {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let [Statement::Expression(expression)] = ast.statements.as_slice() else {
+            return match ast.statements.as_slice() {
+                [Statement::Equation(relation)] => relation.infer_type_in(&Types {
+                    source: str,
+                    ..Types::default()
+                }),
+                _ => panic!("{ast:?}"),
+            }
+            .map_err(|diagnostic| diagnostic.render(str));
+        };
+        expression
+            .infer_type(str)
+            .map_err(|diagnostic| diagnostic.render(str))
+    }
+
+    #[test]
+    fn types() {
+        assert_eq!(infer("2 + 3 m"), Ok(Some(Type::Scalar)));
+        assert_eq!(infer("2*[1, 2] - [x, 4]"), Ok(Some(Type::Vector(2))));
+        assert_eq!(
+            infer("[[1, 2, 3], [4, 5, 6]] * [[1, 0], [0, 1], [1, 1]]"),
+            Ok(Some(Type::Matrix(2, 2)))
+        );
+        assert_eq!(
+            infer("[[1, 2], [3, 4]] * [5, 6]"),
+            Ok(Some(Type::Vector(2)))
+        );
+        assert_eq!(infer("[1, 2] * [3, 4]"), Ok(Some(Type::Scalar)));
+        assert_eq!(infer("[[1, 2], [3, 4]]^2"), Ok(Some(Type::Matrix(2, 2))));
+        assert_eq!(infer("{1, 2} union [0, 1)"), Ok(Some(Type::Set)));
+        assert_eq!(infer("x in R"), Ok(Some(Type::Bool)));
+        assert_eq!(infer("x + y"), Ok(None));
+        assert_eq!(infer("f(x) * [1, 2]"), Ok(None));
+        assert_eq!(
+            infer("piecewise([1, x] if x < 0, [x, 1] otherwise)"),
+            Ok(Some(Type::Vector(2)))
+        );
+        assert_eq!(
+            infer("|[[1, 2], [3, 4]]| - |{1, 2}| + floor(x)"),
+            Ok(Some(Type::Scalar))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            infer("{1, 2} + 3"),
+            Err("1:10: error: cannot add a number to a set".to_string())
+        );
+        assert_eq!(
+            infer("[[1, 2, 3], [4, 5, 6]] * [[1, 2], [3, 4]]"),
+            Err("1:28: error: cannot multiply a 2×3 matrix by a 2×2 matrix".to_string())
+        );
+        assert_eq!(
+            infer("[1, 2] + [1, 2, 3]"),
+            Err("1:11: error: cannot add a vector of length 3 to a vector of length 2".to_string())
+        );
+        assert_eq!(
+            infer("sin([1, 2])"),
+            Err("1:6: error: expected a number, found a vector of length 2".to_string())
+        );
+        assert_eq!(
+            infer("[[1, 2, 3], [4, 5, 6]]^2"),
+            Err("1:24: error: cannot raise a 2×3 matrix to the power of a number".to_string())
+        );
+        assert_eq!(
+            infer("{1} < 2"),
+            Err("1:2: error: expected a number, found a set".to_string())
+        );
+        // Nothing in the set to point at, so the whole statement
+        assert_eq!(
+            infer("{} < 1"),
+            Err("1:1: error: expected a number, found a set".to_string())
+        );
+        assert_eq!(
+            infer("-{}"),
+            Err("1:1: error: cannot negate a set".to_string())
+        );
+        assert_eq!(
+            infer("x in 3"),
+            Err("1:6: error: expected a set, found a number".to_string())
+        );
+        assert_eq!(
+            infer("piecewise(1 if 2, 0 otherwise)"),
+            Err("1:16: error: expected true or false, found a number".to_string())
+        );
+        assert_eq!(
+            infer("[1, {2}]"),
+            Err("1:6: error: expected a number, found a set".to_string())
+        );
+    }
+
+    /// The first mistake in each statement, knowing what the ones before define
+    fn check(str: &str) -> Vec<String> {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        let mut types = Types::default();
+        ast.statements
+            .iter()
+            .zip(&ast.sources)
+            .filter_map(|(statement, source)| statement.check_types(source, &mut types).err())
+            .map(|diagnostic| diagnostic.render(str))
+            .collect()
+    }
+
+    #[test]
+    fn definitions() {
+        assert_eq!(
+            check("let A = [[1, 2], [3, 4]]\nA * [[1, 2, 3]]"),
+            ["2:7: error: cannot multiply a 2×2 matrix by a 1×3 matrix"]
+        );
+        assert!(check("let A = [[1, 2], [3, 4]]\nA * [5, 6] + [1, 1]").is_empty());
+        // Bound again by the sum and the function, so not the matrix
+        assert!(check("let A = [[1, 2], [3, 4]]\nsum(A, 1, 3, A + 1)\nf(A) = A + 1").is_empty());
+        assert!(check("let A = [[1, 2], [3, 4]]\nlet A = 2\nA + 1").is_empty());
+        assert_eq!(
+            check("to([1, 2], m)"),
+            ["1:5: error: expected a number, found a vector of length 2"]
+        );
+        assert!(check("solve(x^2 = 4, x)").is_empty());
+    }
+}
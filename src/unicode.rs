@@ -0,0 +1,785 @@
+//! Plain text backend using unicode math characters. Wrap any [`ast`](crate::ast)
+//! node in [`Unicode`] and format it.
+
+use std::fmt;
+
+use crate::{
+    ast::*,
+    eval::{Number, Value},
+};
+
+/// Displays the wrapped node as unicode text
+pub struct Unicode<'a, T: ?Sized>(pub &'a T);
+
+impl fmt::Display for Unicode<'_, Ast<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, statement) in self.0.statements.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", Unicode(statement))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Unicode<'_, Statement<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Statement::Definition(definition) => write!(
+                f,
+                "{} ≔ {}",
+                Unicode(&definition.name()),
+                Unicode(&definition.value)
+            ),
+            Statement::FunctionDefinition(function_definition) => {
+                write!(f, "{}(", Unicode(&function_definition.identifier))?;
+                for (i, parameter) in function_definition.parameters.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", Unicode(parameter))?;
+                }
+                write!(f, ") = {}", Unicode(&function_definition.body))
+            }
+            Statement::Equation(relation) => write!(f, "{}", Unicode(relation)),
+            Statement::Expression(expression) => write!(f, "{}", Unicode(expression)),
+            Statement::Derivation(derivation) => write!(f, "{}", Unicode(derivation)),
+        }
+    }
+}
+
+impl fmt::Display for Unicode<'_, Derivation<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.0.steps.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            match step.kind {
+                StepKind::Start => write!(f, "{}", Unicode(&step.expression))?,
+                StepKind::Implies => write!(f, "⇒ {}", Unicode(&step.expression))?,
+                StepKind::Continues(operator) => {
+                    write!(f, "  {} {}", Unicode(&operator), Unicode(&step.expression))?
+                }
+            }
+            if let Some(justification) = step.justification {
+                write!(f, "  ({justification})")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Unicode<'_, Expression<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Expression::Literal(literal) => write!(f, "{}", Unicode(literal)),
+            Expression::Number(number) => write!(f, "{}", Unicode(number)),
+            Expression::Quantity(quantity) => write!(
+                f,
+                "{} {}",
+                operand(&quantity.value, Precedence::Power),
+                Unicode(&quantity.unit)
+            ),
+            Expression::Variable(variable) => write!(f, "{}", Unicode(&variable.identifier)),
+            Expression::FunctionCall(function_call) => write!(f, "{}", Unicode(function_call)),
+            Expression::Subscript(subscript) => write!(f, "{}", Unicode(subscript)),
+            Expression::Unary(unary) => write!(f, "{}", Unicode(unary)),
+            Expression::Binary(binary) => write!(f, "{}", Unicode(binary)),
+            Expression::Relation(relation) => write!(f, "{}", Unicode(relation)),
+            Expression::BigOperator(big_operator) => write!(f, "{}", Unicode(big_operator)),
+            Expression::Integral(integral) => write!(f, "{}", Unicode(integral)),
+            Expression::Limit(limit) => write!(f, "{}", Unicode(limit)),
+            Expression::Derivative(derivative) => write!(f, "{}", Unicode(derivative)),
+            Expression::Vector(vector) => write!(f, "{}", Unicode(vector)),
+            Expression::Matrix(matrix) => write!(f, "{}", Unicode(matrix)),
+            Expression::Piecewise(piecewise) => write!(f, "{}", Unicode(piecewise)),
+            Expression::Set(set) => {
+                let elements: Vec<String> = set
+                    .elements
+                    .iter()
+                    .map(|element| Unicode(element).to_string())
+                    .collect();
+                write!(f, "{{{}}}", elements.join(", "))
+            }
+            Expression::SetBuilder(set_builder) => write!(f, "{}", Unicode(set_builder)),
+            Expression::Interval(interval) => write!(
+                f,
+                "{}{}, {}{}",
+                if interval.lower_closed { "[" } else { "(" },
+                Unicode(interval.lower.as_ref()),
+                Unicode(interval.upper.as_ref()),
+                if interval.upper_closed { "]" } else { ")" }
+            ),
+            Expression::NumberSet(number_set) => write!(f, "{}", Unicode(number_set)),
+            Expression::Delimited(delimited) => {
+                let (left, right) = match delimited.kind {
+                    DelimiterKind::Abs => ('|', '|'),
+                    DelimiterKind::Norm => ('‖', '‖'),
+                    DelimiterKind::Floor => ('⌊', '⌋'),
+                    DelimiterKind::Ceil => ('⌈', '⌉'),
+                };
+                write!(f, "{left}{}{right}", Unicode(delimited.inner.as_ref()))
+            }
+            Expression::Group(group) => write!(f, "({})", Unicode(group.inner.as_ref())),
+            Expression::List(list) => {
+                let elements: Vec<String> = list
+                    .elements
+                    .iter()
+                    .map(|element| operand(element, Precedence::Relation))
+                    .collect();
+                write!(f, "{}", elements.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Unicode<'_, BigOperator<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let BigOperator {
+            kind,
+            index,
+            from,
+            to,
+            body,
+        } = self.0;
+        let symbol = match kind {
+            BigOperatorKind::Sum => '∑',
+            BigOperatorKind::Product => '∏',
+        };
+        write!(
+            f,
+            "{symbol}({}={}..{}) {}",
+            Unicode(index),
+            Unicode(from.as_ref()),
+            Unicode(to.as_ref()),
+            operand(body, Precedence::Product)
+        )
+    }
+}
+
+impl fmt::Display for Unicode<'_, Integral<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Integral {
+            integrand,
+            variable,
+            bounds,
+        } = self.0;
+        write!(f, "∫")?;
+        if let Some((lower, upper)) = bounds {
+            let lower = Unicode(lower.as_ref()).to_string();
+            let upper = Unicode(upper.as_ref()).to_string();
+            match (subscript(&lower), superscript(&upper)) {
+                (Some(lower), Some(upper)) => write!(f, "{lower}{upper}")?,
+                _ => write!(f, "[{lower}, {upper}]")?,
+            }
+        }
+        write!(
+            f,
+            " {} d{}",
+            operand(integrand, Precedence::Product),
+            Unicode(variable)
+        )
+    }
+}
+
+impl fmt::Display for Unicode<'_, Limit<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Limit {
+            variable,
+            approaching,
+            body,
+        } = self.0;
+        write!(
+            f,
+            "lim({}→{}) {}",
+            Unicode(variable),
+            Unicode(approaching.as_ref()),
+            operand(body, Precedence::Product)
+        )
+    }
+}
+
+/// `expression`, bracketed if it binds looser than `precedence`
+impl fmt::Display for Unicode<'_, SetBuilder<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let SetBuilder {
+            variable,
+            domain,
+            condition,
+        } = self.0;
+        write!(f, "{{{}", Unicode(variable))?;
+        if let Some(domain) = domain {
+            write!(f, " ∈ {}", Unicode(domain.as_ref()))?;
+        }
+        write!(f, " | {}}}", Unicode(condition.as_ref()))
+    }
+}
+
+impl fmt::Display for Unicode<'_, NumberSet> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unicode = match self.0 {
+            NumberSet::Natural => 'ℕ',
+            NumberSet::Integer => 'ℤ',
+            NumberSet::Rational => 'ℚ',
+            NumberSet::Real => 'ℝ',
+            NumberSet::Complex => 'ℂ',
+        };
+        write!(f, "{unicode}")
+    }
+}
+
+impl fmt::Display for Unicode<'_, Piecewise<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Piecewise { arms, otherwise } = self.0;
+        let mut arms: Vec<String> = arms
+            .iter()
+            .map(|arm| format!("{} if {}", Unicode(&arm.value), Unicode(&arm.condition)))
+            .collect();
+        if let Some(otherwise) = otherwise {
+            arms.push(format!("{} otherwise", Unicode(otherwise.as_ref())));
+        }
+        write!(f, "{{{}}}", arms.join(", "))
+    }
+}
+
+impl fmt::Display for Unicode<'_, Vector<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", list(&self.0.elements))
+    }
+}
+
+impl fmt::Display for Unicode<'_, Matrix<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = self.0.rows.iter().map(|row| list(row)).collect();
+        write!(f, "[{}]", rows.join(", "))
+    }
+}
+
+/// `[1, 2, 3]`
+fn list(elements: &[Expression<'_>]) -> String {
+    let elements: Vec<String> = elements
+        .iter()
+        .map(|element| Unicode(element).to_string())
+        .collect();
+    format!("[{}]", elements.join(", "))
+}
+
+impl fmt::Display for Unicode<'_, Derivative<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Derivative::Leibniz { variable, body } => {
+                write!(f, "d/d{}({})", Unicode(variable), Unicode(body.as_ref()))
+            }
+            Derivative::Prime {
+                function,
+                order,
+                inputs,
+            } => {
+                let primes = match order {
+                    1 => "′".to_string(),
+                    2 => "″".to_string(),
+                    3 => "‴".to_string(),
+                    4 => "⁗".to_string(),
+                    order => "′".repeat(*order),
+                };
+                write!(f, "{}{primes}", Unicode(function))?;
+                let Some(inputs) = inputs else {
+                    return Ok(());
+                };
+                write!(f, "(")?;
+                for (i, input) in inputs.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", Unicode(input))?;
+                }
+                write!(f, ")")
+            }
+            Derivative::Partial { body, .. } => {
+                let power = |count: usize| match count {
+                    1 => String::new(),
+                    count => superscript(&count.to_string()).unwrap_or_default(),
+                };
+                let denominator: String = self
+                    .0
+                    .grouped_variables()
+                    .into_iter()
+                    .map(|(variable, count)| format!("∂{}{}", Unicode(variable), power(count)))
+                    .collect();
+                let numerator = format!("∂{}", power(self.0.order()));
+                match body.as_ref() {
+                    Expression::Variable(variable) => write!(
+                        f,
+                        "{numerator}{}/{denominator}",
+                        Unicode(&variable.identifier)
+                    ),
+                    body => write!(f, "{numerator}/{denominator}({})", Unicode(body)),
+                }
+            }
+        }
+    }
+}
+
+/// `m/s²` or `kg·m²`
+impl fmt::Display for Unicode<'_, Unit<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let written = |powers: &[(&str, i32)]| {
+            let written: Vec<String> = powers
+                .iter()
+                .map(|(symbol, exponent)| match exponent {
+                    1 => symbol.to_string(),
+                    _ => format!(
+                        "{symbol}{}",
+                        superscript(&exponent.to_string()).unwrap_or_default()
+                    ),
+                })
+                .collect();
+            written.join("·")
+        };
+        let (numerator, denominator) = self.0.fraction();
+        write!(f, "{}", written(&numerator))?;
+        if !denominator.is_empty() {
+            write!(f, "/{}", written(&denominator))?;
+        }
+        Ok(())
+    }
+}
+
+fn operand(expression: &Expression<'_>, precedence: Precedence) -> String {
+    if expression.precedence() < precedence {
+        format!("({})", Unicode(expression))
+    } else {
+        Unicode(expression).to_string()
+    }
+}
+
+/// Right hand side of a binary operator. Also brackets negation so `a − (−b)` stays readable.
+fn right_operand(expression: &Expression<'_>, precedence: Precedence) -> String {
+    match expression {
+        Expression::Unary(_) => format!("({})", Unicode(expression)),
+        Expression::Number(number) if number.is_negative() => format!("({})", Unicode(expression)),
+        _ => operand(expression, precedence),
+    }
+}
+
+impl fmt::Display for Unicode<'_, Unary<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.operator {
+            UnaryOperator::Negate => write!(f, "−{}", operand(&self.0.operand, Precedence::Power)),
+            UnaryOperator::PlusMinus => {
+                write!(f, "±{}", operand(&self.0.operand, Precedence::Power))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Unicode<'_, Binary<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Binary {
+            operator,
+            left,
+            right,
+        } = self.0;
+        match operator {
+            BinaryOperator::Add => write!(
+                f,
+                "{} + {}",
+                operand(left, Precedence::Sum),
+                right_operand(right, Precedence::Sum)
+            ),
+            BinaryOperator::Subtract => write!(
+                f,
+                "{} − {}",
+                operand(left, Precedence::Sum),
+                right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::PlusMinus => write!(
+                f,
+                "{} ± {}",
+                operand(left, Precedence::Sum),
+                right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::Multiply => write!(
+                f,
+                "{}·{}",
+                operand(left, Precedence::Product),
+                right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::ImplicitMultiply => {
+                // `2^x ln(2)` would read as a power of `x ln(2)`, and so would `3·2^x ln(2)`
+                let caret_power = ends_with_power(left);
+                let left = operand(left, Precedence::Product);
+                let right = right_operand(right, Precedence::Product);
+                // `x cos(x)` written `xcos(x)` would read as a call of `xcos`
+                let merges =
+                    left.ends_with(char::is_alphabetic) && right.starts_with(char::is_alphabetic);
+                if right.starts_with(|c: char| c.is_ascii_digit())
+                    || merges
+                    || (caret_power && left.contains('^'))
+                {
+                    write!(f, "{left}·{right}")
+                } else {
+                    write!(f, "{left}{right}")
+                }
+            }
+            BinaryOperator::Divide => write!(
+                f,
+                "{}/{}",
+                operand(left, Precedence::Product),
+                right_operand(right, Precedence::Power)
+            ),
+            BinaryOperator::Power => {
+                let base = operand(left, Precedence::Atom);
+                match superscript(&Unicode(right.ungrouped()).to_string()) {
+                    Some(exponent) => write!(f, "{base}{exponent}"),
+                    None => write!(f, "{base}^{}", operand(right, Precedence::Atom)),
+                }
+            }
+            BinaryOperator::Union => write!(
+                f,
+                "{} ∪ {}",
+                operand(left, Precedence::Sum),
+                right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::Intersection => write!(
+                f,
+                "{} ∩ {}",
+                operand(left, Precedence::Product),
+                right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::Difference => write!(
+                f,
+                "{} ∖ {}",
+                operand(left, Precedence::Sum),
+                right_operand(right, Precedence::Product)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Unicode<'_, Relation<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", operand(&self.0.first, Precedence::Sum))?;
+        for (operator, right) in &self.0.rest {
+            // `x = 2, 3` reads fine without brackets, since the list is all that's left
+            let right = match right {
+                Expression::List(_) => Unicode(right).to_string(),
+                right => operand(right, Precedence::Sum),
+            };
+            write!(f, " {} {right}", Unicode(operator))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Unicode<'_, RelationOperator> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unicode = match self.0 {
+            RelationOperator::Equal => '=',
+            RelationOperator::NotEqual => '≠',
+            RelationOperator::Less => '<',
+            RelationOperator::LessEqual => '≤',
+            RelationOperator::Greater => '>',
+            RelationOperator::GreaterEqual => '≥',
+            RelationOperator::Approximately => '≈',
+            RelationOperator::In => '∈',
+            RelationOperator::Subset => '⊆',
+        };
+        write!(f, "{unicode}")
+    }
+}
+
+impl fmt::Display for Unicode<'_, Literal<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Literal::Whole(whole) => {
+                let digits = digits(whole.digits());
+                match whole.radix {
+                    Radix::Decimal => write!(f, "{digits}"),
+                    radix => {
+                        let base = subscript(&radix.base().to_string()).unwrap();
+                        write!(f, "{digits}{base}")
+                    }
+                }
+            }
+            Literal::Float(float) => write!(f, "{}", digits(float.text)),
+            Literal::Scientific(scientific) => {
+                let (significand, exponent) = scientific.parts();
+                let exponent = exponent.trim_start_matches('+').replace('_', "");
+                let exponent = superscript(&exponent).unwrap();
+                write!(f, "{}×10{exponent}", digits(significand))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Unicode<'_, Value> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Number(number) => write!(f, "{}", Unicode(number)),
+            Value::Complex(complex) => {
+                complex.write_with(f, |part| Unicode(part).to_string(), "−", "i")
+            }
+            Value::Bool(bool) => write!(f, "{bool}"),
+        }
+    }
+}
+
+/// Fractions as `1/3`
+impl fmt::Display for Unicode<'_, Number> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0.is_negative() { "−" } else { "" };
+        match self.0.abs() {
+            Number::Float(float) if float.is_infinite() => write!(f, "{sign}∞"),
+            number => write!(f, "{sign}{number}"),
+        }
+    }
+}
+
+impl fmt::Display for Unicode<'_, FunctionCall<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", Unicode(&self.0.identifier))?;
+        for (i, input) in self.0.inputs.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", Unicode(input))?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for Unicode<'_, Subscript<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Subscript { base, index } = self.0;
+        let index_text = Unicode(index.as_ref()).to_string();
+        match (subscript(&index_text), index.as_ref()) {
+            (Some(index), _) => write!(f, "{}{index}", Unicode(base.as_ref())),
+            (None, Expression::Literal(_) | Expression::Variable(_)) => {
+                write!(f, "{}_{index_text}", Unicode(base.as_ref()))
+            }
+            (None, _) => write!(f, "{}_({index_text})", Unicode(base.as_ref())),
+        }
+    }
+}
+
+impl fmt::Display for Unicode<'_, Identifier<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.symbol() {
+            Some(symbol) => write!(f, "{}", symbol.unicode),
+            None => write!(f, "{}", self.0.text),
+        }
+    }
+}
+
+/// Digits as written, with digit seperators turned into thin spaces
+fn digits(text: &str) -> String {
+    text.replace('_', "\u{2009}")
+}
+
+/// `text` in superscript characters, if every character has one
+pub fn superscript(text: &str) -> Option<String> {
+    text.chars()
+        .map(|c| match c {
+            '0' => Some('⁰'),
+            '1' => Some('¹'),
+            '2' => Some('²'),
+            '3' => Some('³'),
+            '4' => Some('⁴'),
+            '5' => Some('⁵'),
+            '6' => Some('⁶'),
+            '7' => Some('⁷'),
+            '8' => Some('⁸'),
+            '9' => Some('⁹'),
+            '+' => Some('⁺'),
+            '-' | '−' => Some('⁻'),
+            '=' => Some('⁼'),
+            '(' => Some('⁽'),
+            ')' => Some('⁾'),
+            'n' => Some('ⁿ'),
+            'i' => Some('ⁱ'),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `text` in subscript characters, if every character has one
+pub fn subscript(text: &str) -> Option<String> {
+    text.chars()
+        .map(|c| match c {
+            '0' => Some('₀'),
+            '1' => Some('₁'),
+            '2' => Some('₂'),
+            '3' => Some('₃'),
+            '4' => Some('₄'),
+            '5' => Some('₅'),
+            '6' => Some('₆'),
+            '7' => Some('₇'),
+            '8' => Some('₈'),
+            '9' => Some('₉'),
+            '+' => Some('₊'),
+            '-' | '−' => Some('₋'),
+            '=' => Some('₌'),
+            '(' => Some('₍'),
+            ')' => Some('₎'),
+            'a' => Some('ₐ'),
+            'e' => Some('ₑ'),
+            'h' => Some('ₕ'),
+            'i' => Some('ᵢ'),
+            'j' => Some('ⱼ'),
+            'k' => Some('ₖ'),
+            'l' => Some('ₗ'),
+            'm' => Some('ₘ'),
+            'n' => Some('ₙ'),
+            'o' => Some('ₒ'),
+            'p' => Some('ₚ'),
+            'r' => Some('ᵣ'),
+            's' => Some('ₛ'),
+            't' => Some('ₜ'),
+            'u' => Some('ᵤ'),
+            'v' => Some('ᵥ'),
+            'x' => Some('ₓ'),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the last factor of a product is a power, like the `2^x` of `3·2^x`
+fn ends_with_power(expression: &Expression<'_>) -> bool {
+    match expression {
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Power,
+            ..
+        }) => true,
+        Expression::Binary(Binary {
+            operator: BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply,
+            right,
+            ..
+        }) => ends_with_power(right),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unicode(str: &str) -> String {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        Unicode(&ast).to_string()
+    }
+
+    #[test]
+    fn symbols() {
+        assert_eq!(unicode("theta"), "θ");
+        assert_eq!(unicode("θ"), "θ");
+        assert_eq!(unicode("sin(alpha)"), "sin(α)");
+        assert_eq!(unicode("infinity"), "∞");
+    }
+
+    #[test]
+    fn subscript() {
+        assert_eq!(unicode("x_1"), "x₁");
+        assert_eq!(unicode("a_n"), "aₙ");
+        assert_eq!(unicode("v_max"), "vₘₐₓ");
+        assert_eq!(unicode("v_y"), "v_y");
+        assert_eq!(unicode("x_(beta)"), "x_β");
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(unicode("x^2 + 1"), "x² + 1");
+        assert_eq!(unicode("x^-1"), "x⁻¹");
+        assert_eq!(unicode("x^(a+b)"), "x^(a + b)");
+        assert_eq!(unicode("x^(2)"), "x²");
+        assert_eq!(unicode("(a - b) - c"), "(a − b) − c");
+        assert_eq!(unicode("a - b*c"), "a − b·c");
+        assert_eq!(unicode("1/(2x)"), "1/(2x)");
+        assert_eq!(unicode("-(x+1)"), "−(x + 1)");
+    }
+
+    #[test]
+    fn relation() {
+        assert_eq!(unicode("0 < x <= 1"), "0 < x ≤ 1");
+        assert_eq!(unicode("a >= b != c ~= d"), "a ≥ b ≠ c ≈ d");
+    }
+
+    #[test]
+    fn statements() {
+        assert_eq!(unicode("let g = 9.81"), "g ≔ 9.81");
+        assert_eq!(unicode("f(x) = x^2 + 1"), "f(x) = x² + 1");
+    }
+
+    #[test]
+    fn derivation() {
+        assert_eq!(
+            unicode("x^2 - 4 = 0 => (x-2)(x+2) = 0 \"factor\" => x = ±2"),
+            "x² − 4 = 0\n⇒ (x − 2)(x + 2) = 0  (factor)\n⇒ x = ±2"
+        );
+    }
+
+    #[test]
+    fn big_operators() {
+        assert_eq!(unicode("sum(i, 1, n, i^2)"), "∑(i=1..n) i²");
+        assert_eq!(unicode("int(f(x), x, 0, 1)"), "∫₀¹ f(x) dx");
+        assert_eq!(unicode("int(f(x), x, a, b)"), "∫[a, b] f(x) dx");
+        assert_eq!(unicode("lim(x, 0, sin(x)/x)"), "lim(x→0) sin(x)/x");
+    }
+
+    #[test]
+    fn derivatives() {
+        assert_eq!(unicode("d/dx(x^2 + 1)"), "d/dx(x² + 1)");
+        assert_eq!(unicode("f'(x) = 2f''(x)"), "f′(x) = 2f″(x)");
+        assert_eq!(unicode("pd(f, x, y)"), "∂²f/∂x∂y");
+        assert_eq!(unicode("pd(x*y^2, y, y)"), "∂²/∂y²(x·y²)");
+    }
+
+    #[test]
+    fn piecewise() {
+        assert_eq!(
+            unicode("piecewise(x^2 if x < 0, x otherwise)"),
+            "{x² if x < 0, x otherwise}"
+        );
+    }
+
+    #[test]
+    fn sets() {
+        assert_eq!(unicode("{x in R | x > 0}"), "{x ∈ ℝ | x > 0}");
+        assert_eq!(unicode("{1, 2} union [3, 4)"), "{1, 2} ∪ [3, 4)");
+        assert_eq!(unicode("A \\ B subset Z"), "A ∖ B ⊆ ℤ");
+    }
+
+    #[test]
+    fn delimited() {
+        assert_eq!(unicode("||x| - 1|"), "||x| − 1|");
+        assert_eq!(unicode("||v||"), "‖v‖");
+        assert_eq!(unicode("floor(x) + ceil(y)"), "⌊x⌋ + ⌈y⌉");
+    }
+
+    #[test]
+    fn matrices() {
+        assert_eq!(unicode("[1, -2, x^2]"), "[1, −2, x²]");
+        assert_eq!(unicode("[[1, 0], [0, 1]]"), "[[1, 0], [0, 1]]");
+        assert_eq!(
+            unicode("[[1, 0], [0, 1]]*[[2], [3]]"),
+            "[[1, 0], [0, 1]]·[[2], [3]]"
+        );
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(unicode("6.022e23"), "6.022×10²³");
+        assert_eq!(unicode("1e-9"), "1×10⁻⁹");
+        assert_eq!(unicode("0b1010"), "1010₂");
+    }
+
+    #[test]
+    fn quantities() {
+        assert_eq!(unicode("9.81 m/s^2"), "9.81 m/s²");
+        assert_eq!(unicode("3 kg * 2 m/s^2"), "3 kg·2 m/s²");
+        assert_eq!(unicode("4 Hz"), "4 Hz");
+        assert_eq!(unicode("1 s^-1"), "1 s⁻¹");
+    }
+}
@@ -0,0 +1,795 @@
+//! Physical units. Looks up symbols like `km` in a table of SI units, checks that quantities are
+//! only added or compared when they measure the same thing, and converts between units.
+
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{
+    ast::*,
+    diagnostic::Diagnostic,
+    eval::{Environment, Number, Value},
+};
+
+/// Powers of the SI base units, in the order of [`BASE_UNITS`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Dimension([i32; 7]);
+
+/// Symbols of the SI base units, in the order a [`Dimension`] is written
+const BASE_UNITS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+
+impl Dimension {
+    /// Of a plain number
+    pub const NONE: Dimension = Dimension([0; 7]);
+
+    const fn new(kg: i32, m: i32, s: i32, a: i32, k: i32, mol: i32, cd: i32) -> Dimension {
+        Dimension([kg, m, s, a, k, mol, cd])
+    }
+
+    /// [`None`] when some power is too large to count, like that of `(m^2)^2000000000 m`
+    pub fn times(self, other: Dimension) -> Option<Dimension> {
+        let mut powers = self.0;
+        for (power, other) in powers.iter_mut().zip(other.0) {
+            *power = power.checked_add(other)?;
+        }
+        Some(Dimension(powers))
+    }
+
+    /// [`None`] when some power is too large to count, like that of `(m^2)^2000000000`
+    pub fn pow(self, exponent: i32) -> Option<Dimension> {
+        let mut powers = self.0;
+        for power in &mut powers {
+            *power = power.checked_mul(exponent)?;
+        }
+        Some(Dimension(powers))
+    }
+
+    /// [`None`] when some power doesn't divide, like the square root of `m`
+    pub fn root(self, root: i32) -> Option<Dimension> {
+        let mut powers = self.0;
+        for power in &mut powers {
+            if power.checked_rem(root)? != 0 {
+                return None;
+            }
+            *power = power.checked_div(root)?;
+        }
+        Some(Dimension(powers))
+    }
+}
+
+/// `kg·m/s^2`, or `a plain number`
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == Dimension::NONE {
+            return write!(f, "a plain number");
+        }
+        let written = |powers: Vec<(&str, i32)>| {
+            let written: Vec<String> = powers
+                .iter()
+                .map(|(unit, power)| match power {
+                    1 => unit.to_string(),
+                    _ => format!("{unit}^{power}"),
+                })
+                .collect();
+            written.join("·")
+        };
+        let powers = BASE_UNITS.into_iter().zip(self.0);
+        let numerator: Vec<_> = powers.clone().filter(|(_, power)| *power > 0).collect();
+        let denominator: Vec<_> = powers
+            .filter(|(_, power)| *power < 0)
+            .map(|(unit, power)| (unit, -power))
+            .collect();
+        match (numerator.is_empty(), denominator.len()) {
+            (_, 0) => write!(f, "{}", written(numerator)),
+            (true, _) => write!(f, "1/{}", written(denominator)),
+            (false, 1) => write!(f, "{}/{}", written(numerator), written(denominator)),
+            (false, _) => write!(f, "{}/({})", written(numerator), written(denominator)),
+        }
+    }
+}
+
+/// A unit in the table, which prefixed symbols like `km` are built from
+struct Entry {
+    symbol: &'static str,
+    /// What Wolfram calls it, like `"Meters"`
+    name: &'static str,
+    /// `(mantissa, exponent)` of its size in SI base units, `mantissa * 10^exponent`
+    scale: (i64, i32),
+    dimension: Dimension,
+    /// Whether it takes a prefix like `k` in `km`. Minutes and hours don't.
+    prefixed: bool,
+}
+
+const fn entry(
+    symbol: &'static str,
+    name: &'static str,
+    scale: (i64, i32),
+    dimension: Dimension,
+    prefixed: bool,
+) -> Entry {
+    Entry {
+        symbol,
+        name,
+        scale,
+        dimension,
+        prefixed,
+    }
+}
+
+const LENGTH: Dimension = Dimension::new(0, 1, 0, 0, 0, 0, 0);
+const MASS: Dimension = Dimension::new(1, 0, 0, 0, 0, 0, 0);
+const TIME: Dimension = Dimension::new(0, 0, 1, 0, 0, 0, 0);
+const CURRENT: Dimension = Dimension::new(0, 0, 0, 1, 0, 0, 0);
+const TEMPERATURE: Dimension = Dimension::new(0, 0, 0, 0, 1, 0, 0);
+const AMOUNT: Dimension = Dimension::new(0, 0, 0, 0, 0, 1, 0);
+const LUMINOUS_INTENSITY: Dimension = Dimension::new(0, 0, 0, 0, 0, 0, 1);
+const FREQUENCY: Dimension = Dimension::new(0, 0, -1, 0, 0, 0, 0);
+const FORCE: Dimension = Dimension::new(1, 1, -2, 0, 0, 0, 0);
+const PRESSURE: Dimension = Dimension::new(1, -1, -2, 0, 0, 0, 0);
+const ENERGY: Dimension = Dimension::new(1, 2, -2, 0, 0, 0, 0);
+const POWER: Dimension = Dimension::new(1, 2, -3, 0, 0, 0, 0);
+const CHARGE: Dimension = Dimension::new(0, 0, 1, 1, 0, 0, 0);
+const VOLTAGE: Dimension = Dimension::new(1, 2, -3, -1, 0, 0, 0);
+const RESISTANCE: Dimension = Dimension::new(1, 2, -3, -2, 0, 0, 0);
+const CAPACITANCE: Dimension = Dimension::new(-1, -2, 4, 2, 0, 0, 0);
+const MAGNETIC_FIELD: Dimension = Dimension::new(1, 0, -2, -1, 0, 0, 0);
+const MAGNETIC_FLUX: Dimension = Dimension::new(1, 2, -2, -1, 0, 0, 0);
+const INDUCTANCE: Dimension = Dimension::new(1, 2, -2, -2, 0, 0, 0);
+const VOLUME: Dimension = Dimension::new(0, 3, 0, 0, 0, 0, 0);
+
+const UNITS: &[Entry] = &[
+    // Base units. The kilogram is the gram with a prefix.
+    entry("m", "Meters", (1, 0), LENGTH, true),
+    entry("g", "Grams", (1, -3), MASS, true),
+    entry("s", "Seconds", (1, 0), TIME, true),
+    entry("A", "Amperes", (1, 0), CURRENT, true),
+    entry("K", "Kelvins", (1, 0), TEMPERATURE, true),
+    entry("mol", "Moles", (1, 0), AMOUNT, true),
+    entry("cd", "Candelas", (1, 0), LUMINOUS_INTENSITY, true),
+    // Derived units
+    entry("Hz", "Hertz", (1, 0), FREQUENCY, true),
+    entry("N", "Newtons", (1, 0), FORCE, true),
+    entry("Pa", "Pascals", (1, 0), PRESSURE, true),
+    entry("J", "Joules", (1, 0), ENERGY, true),
+    entry("W", "Watts", (1, 0), POWER, true),
+    entry("C", "Coulombs", (1, 0), CHARGE, true),
+    entry("V", "Volts", (1, 0), VOLTAGE, true),
+    entry("Ω", "Ohms", (1, 0), RESISTANCE, true),
+    entry("ohm", "Ohms", (1, 0), RESISTANCE, true),
+    entry("F", "Farads", (1, 0), CAPACITANCE, true),
+    entry("T", "Teslas", (1, 0), MAGNETIC_FIELD, true),
+    entry("Wb", "Webers", (1, 0), MAGNETIC_FLUX, true),
+    entry("H", "Henries", (1, 0), INDUCTANCE, true),
+    // Used alongside SI
+    entry("min", "Minutes", (60, 0), TIME, false),
+    entry("h", "Hours", (3600, 0), TIME, false),
+    entry("L", "Liters", (1, -3), VOLUME, true),
+    entry("eV", "Electronvolts", (1_602_176_634, -28), ENERGY, true),
+    entry("bar", "Bars", (1, 5), PRESSURE, true),
+    entry("atm", "Atmospheres", (101_325, 0), PRESSURE, false),
+];
+
+/// Symbol, Wolfram name and power of ten of each SI prefix
+const PREFIXES: &[(&str, &str, i32)] = &[
+    ("T", "Tera", 12),
+    ("G", "Giga", 9),
+    ("M", "Mega", 6),
+    ("k", "Kilo", 3),
+    ("h", "Hecto", 2),
+    ("d", "Deci", -1),
+    ("c", "Centi", -2),
+    ("m", "Milli", -3),
+    ("µ", "Micro", -6),
+    ("μ", "Micro", -6),
+    ("u", "Micro", -6),
+    ("n", "Nano", -9),
+    ("p", "Pico", -12),
+    ("f", "Femto", -15),
+];
+
+/// What a symbol like `km` means
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownUnit {
+    /// Size in SI base units, so 1000 for `km`
+    pub scale: Number,
+    pub dimension: Dimension,
+    /// What Wolfram calls it, like `"Kilometers"`
+    pub name: String,
+}
+
+/// The unit `symbol` stands for, either straight from the table or a prefixed one like `km`. A
+/// symbol in the table wins over reading its first letter as a prefix, so `T` is the tesla.
+pub fn lookup(symbol: &str) -> Option<KnownUnit> {
+    let known = |entry: &Entry, prefix: Option<&(&str, &str, i32)>| {
+        let (mantissa, exponent) = entry.scale;
+        let (name, exponent) = match prefix {
+            Some((_, prefix, power)) => (
+                format!("{prefix}{}", entry.name.to_lowercase()),
+                exponent + power,
+            ),
+            None => (entry.name.to_string(), exponent),
+        };
+        KnownUnit {
+            scale: Number::Exact(
+                BigRational::from_integer(mantissa.into()) * power_of_ten(exponent),
+            ),
+            dimension: entry.dimension,
+            name,
+        }
+    };
+    if let Some(entry) = UNITS.iter().find(|entry| entry.symbol == symbol) {
+        return Some(known(entry, None));
+    }
+    PREFIXES.iter().find_map(|prefix| {
+        let rest = symbol.strip_prefix(prefix.0)?;
+        let entry = UNITS
+            .iter()
+            .find(|entry| entry.symbol == rest && entry.prefixed)?;
+        Some(known(entry, Some(prefix)))
+    })
+}
+
+/// `10^exponent`, exactly
+fn power_of_ten(exponent: i32) -> BigRational {
+    let power = BigRational::from_integer(BigInt::from(10).pow(exponent.unsigned_abs()));
+    if exponent < 0 {
+        power.recip()
+    } else {
+        power
+    }
+}
+
+/// Unit symbols with their exponents, like `[("m", 1), ("s", 2)]`
+pub type Powers<'src> = Vec<(&'src str, i32)>;
+
+impl<'src> Unit<'src> {
+    /// `m/s^2` from the expression `m/s^2`, like the unit `to` converts to. [`None`] when some
+    /// part isn't a unit.
+    pub fn from_expression(expression: &Expression<'src>) -> Option<Unit<'src>> {
+        let factors = match expression {
+            Expression::Variable(variable) => {
+                lookup(variable.identifier.text)?;
+                vec![UnitFactor {
+                    symbol: variable.identifier.text,
+                    exponent: 1,
+                }]
+            }
+            Expression::Group(group) => return Unit::from_expression(&group.inner),
+            Expression::Binary(binary) => {
+                let left = Unit::from_expression(&binary.left);
+                match binary.operator {
+                    BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => {
+                        let mut factors = left?.factors;
+                        factors.extend(Unit::from_expression(&binary.right)?.factors);
+                        factors
+                    }
+                    BinaryOperator::Divide => {
+                        let mut factors = left?.factors;
+                        factors.extend(Unit::from_expression(&binary.right)?.pow(-1)?.factors);
+                        factors
+                    }
+                    BinaryOperator::Power => {
+                        let exponent = Environment::new().evaluate(&binary.right).ok()?;
+                        let Value::Number(exponent) = exponent else {
+                            return None;
+                        };
+                        left?.pow(exponent.to_integer()?.to_i32()?)?.factors
+                    }
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        Some(Unit { factors })
+    }
+
+    /// [`None`] when some exponent is too large to count
+    fn pow(self, exponent: i32) -> Option<Unit<'src>> {
+        let factors = self
+            .factors
+            .into_iter()
+            .map(|factor| {
+                Some(UnitFactor {
+                    exponent: factor.exponent.checked_mul(exponent)?,
+                    ..factor
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(Unit { factors })
+    }
+
+    /// What it measures, like length over time squared for `m/s^2`. [`None`] when some power
+    /// is too large to count.
+    pub fn dimension(&self) -> Option<Dimension> {
+        self.factors
+            .iter()
+            .try_fold(Dimension::NONE, |dimension, factor| {
+                let unit = lookup(factor.symbol).expect("units are only parsed from the table");
+                dimension.times(unit.dimension.pow(factor.exponent)?)
+            })
+    }
+
+    /// Size in SI base units, so `km/h` is `5/18`
+    pub fn scale(&self) -> Number {
+        self.factors.iter().fold(Number::from(1), |scale, factor| {
+            let unit = lookup(factor.symbol).expect("units are only parsed from the table");
+            let power = unit
+                .scale
+                .pow(&Number::from(i64::from(factor.exponent)))
+                .expect("scales are positive");
+            &scale * &power
+        })
+    }
+
+    /// Numerator and denominator, each with positive exponents, for writing it as `m/s^2`. Only
+    /// a single unit goes below the line, otherwise every factor stays above it with its own
+    /// exponent, like `J kg^-1 K^-1`.
+    pub fn fraction(&self) -> (Powers<'src>, Powers<'src>) {
+        let factors = self
+            .factors
+            .iter()
+            .map(|factor| (factor.symbol, factor.exponent));
+        let below = factors
+            .clone()
+            .filter(|(_, exponent)| *exponent < 0)
+            .count();
+        let above = factors.len() - below;
+        if below != 1 || above == 0 {
+            return (factors.collect(), vec![]);
+        }
+        let (numerator, denominator): (Vec<_>, Vec<_>) =
+            factors.partition(|(_, exponent)| *exponent > 0);
+        let denominator = denominator
+            .into_iter()
+            .map(|(symbol, exponent)| (symbol, -exponent))
+            .collect();
+        (numerator, denominator)
+    }
+}
+
+/// Functions of angles, powers and logarithms, which only make sense of a plain number
+const PLAIN_FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "csc", "sec", "cot", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "ln", "log", "lg", "exp",
+];
+
+impl<'src> Statement<'src> {
+    /// Finds the first place quantities that measure different things are added or compared,
+    /// like `2 m + 3 s`. `source` is the statement's, from [`Ast::sources`].
+    pub fn check_units(&self, source: &'src str) -> Result<(), Diagnostic<'src>> {
+        match self {
+            Statement::Definition(definition) => definition.value.dimension(source).map(drop),
+            Statement::FunctionDefinition(function_definition) => {
+                function_definition.body.dimension(source).map(drop)
+            }
+            Statement::Equation(relation) => relation.dimension(source).map(drop),
+            Statement::Expression(expression) => expression.dimension(source).map(drop),
+            Statement::Derivation(derivation) => derivation
+                .steps
+                .iter()
+                .try_for_each(|step| step.expression.dimension(source).map(drop)),
+        }
+    }
+}
+
+impl<'src> Relation<'src> {
+    /// Checks each neighbouring pair measures the same thing. Relations don't have a dimension.
+    fn dimension(&self, source: &'src str) -> Result<Option<Dimension>, Diagnostic<'src>> {
+        let mut left = &*self.first;
+        let mut left_dimension = left.dimension(source)?;
+        for (_, right) in &self.rest {
+            let right_dimension = right.dimension(source)?;
+            same(
+                source,
+                left,
+                right,
+                left_dimension,
+                right_dimension,
+                |left, right| format!("cannot compare {right} with {left}"),
+            )?;
+            left = right;
+            left_dimension = right_dimension;
+        }
+        Ok(None)
+    }
+}
+
+impl<'src> Expression<'src> {
+    /// What the expression measures, or a diagnostic where quantities that measure different
+    /// things are added or compared. [`None`] when it isn't known, like for a variable. `source`
+    /// is what the expression was parsed from.
+    pub fn dimension(&self, source: &'src str) -> Result<Option<Dimension>, Diagnostic<'src>> {
+        match self {
+            Expression::Literal(_) | Expression::Number(_) => Ok(Some(Dimension::NONE)),
+            Expression::Quantity(quantity) => Ok(quantity
+                .value
+                .dimension(source)?
+                .and_then(|value| value.times(quantity.unit.dimension()?))),
+            Expression::Unary(unary) => unary.operand.dimension(source),
+            Expression::Group(group) => group.inner.dimension(source),
+            Expression::Delimited(delimited) => delimited.inner.dimension(source),
+            Expression::Binary(binary) => binary.dimension(source),
+            Expression::Relation(relation) => relation.dimension(source),
+            Expression::FunctionCall(function_call) => function_call.dimension(source),
+            _ => {
+                // Nothing's known about the whole, but what's inside can still be wrong
+                let mut error = None;
+                let mut outside = true;
+                self.rewrite(&mut |part| {
+                    if std::mem::take(&mut outside) {
+                        return None;
+                    }
+                    if let (Err(diagnostic), None) = (part.dimension(source), &error) {
+                        error = Some(diagnostic);
+                    }
+                    Some(part.clone())
+                });
+                error.map_or(Ok(None), Err)
+            }
+        }
+    }
+
+    /// Whether there's a quantity anywhere in it
+    pub(crate) fn has_units(&self) -> bool {
+        let mut found = false;
+        self.rewrite(&mut |part| {
+            found |= matches!(part, Expression::Quantity(_));
+            found.then(|| part.clone())
+        });
+        found
+    }
+}
+
+impl<'src> Binary<'src> {
+    fn dimension(&self, source: &'src str) -> Result<Option<Dimension>, Diagnostic<'src>> {
+        let left = self.left.dimension(source)?;
+        let right = self.right.dimension(source)?;
+        match self.operator {
+            BinaryOperator::Add | BinaryOperator::PlusMinus => same(
+                source,
+                &self.left,
+                &self.right,
+                left,
+                right,
+                |left, right| format!("cannot add {right} to {left}"),
+            ),
+            BinaryOperator::Subtract => same(
+                source,
+                &self.left,
+                &self.right,
+                left,
+                right,
+                |left, right| format!("cannot subtract {right} from {left}"),
+            ),
+            BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => {
+                Ok(left.zip(right).and_then(|(left, right)| left.times(right)))
+            }
+            BinaryOperator::Divide => Ok(left
+                .zip(right)
+                .and_then(|(left, right)| left.times(right.pow(-1)?))),
+            BinaryOperator::Power => {
+                if let Some(right) = right.filter(|right| *right != Dimension::NONE) {
+                    return Err(Diagnostic::error(
+                        fragment(&self.right).unwrap_or(source),
+                        format!("cannot raise to the power of {right}"),
+                    ));
+                }
+                let Some(left) = left else {
+                    return Ok(None);
+                };
+                if left == Dimension::NONE {
+                    return Ok(Some(left));
+                }
+                // `m^2` is an area, but `m^x` isn't anything
+                let Ok(Value::Number(Number::Exact(exponent))) =
+                    Environment::new().evaluate(&self.right)
+                else {
+                    return Ok(None);
+                };
+                let power = exponent.numer().to_i32().and_then(|power| left.pow(power));
+                let root = exponent.denom().to_i32();
+                Ok(power.zip(root).and_then(|(power, root)| power.root(root)))
+            }
+            BinaryOperator::Union | BinaryOperator::Intersection | BinaryOperator::Difference => {
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl<'src> FunctionCall<'src> {
+    fn dimension(&self, source: &'src str) -> Result<Option<Dimension>, Diagnostic<'src>> {
+        let dimensions = self
+            .inputs
+            .iter()
+            .map(|input| input.dimension(source))
+            .collect::<Result<Vec<_>, _>>()?;
+        let name = self.identifier.text;
+        match (name, self.inputs.as_slice(), dimensions.as_slice()) {
+            ("sqrt", _, [input]) => Ok(input.and_then(|input| input.root(2))),
+            ("abs" | "floor" | "ceil" | "round", _, [input]) => Ok(*input),
+            ("max" | "min", inputs, _) => {
+                let mut result = dimensions[0];
+                for (input, dimension) in inputs.iter().zip(&dimensions).skip(1) {
+                    result = same(
+                        source,
+                        &inputs[0],
+                        input,
+                        result,
+                        *dimension,
+                        |left, right| format!("cannot compare {right} with {left}"),
+                    )?;
+                }
+                Ok(result)
+            }
+            ("to", [quantity, unit], [from, _]) => {
+                let Some(unit) = Unit::from_expression(unit) else {
+                    return Ok(None);
+                };
+                let Some(to) = unit.dimension() else {
+                    return Ok(None);
+                };
+                if from.is_some_and(|from| from != to) {
+                    return Err(Diagnostic::error(
+                        fragment(quantity).unwrap_or(source),
+                        format!("cannot convert {} to {to}", from.unwrap()),
+                    ));
+                }
+                Ok(Some(to))
+            }
+            (name, inputs, _) if PLAIN_FUNCTIONS.contains(&name) => {
+                for (input, dimension) in inputs.iter().zip(&dimensions) {
+                    if let Some(dimension) = dimension.filter(|d| *d != Dimension::NONE) {
+                        return Err(Diagnostic::error(
+                            fragment(input).unwrap_or(source),
+                            format!("`{name}` needs a plain number, not {dimension}"),
+                        ));
+                    }
+                }
+                Ok(Some(Dimension::NONE))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The dimension `left` and `right` both have, or a diagnostic pointing at the right one when
+/// they're known to differ, or at `source` when neither has any source of its own
+fn same<'src>(
+    source: &'src str,
+    left: &Expression<'src>,
+    right: &Expression<'src>,
+    left_dimension: Option<Dimension>,
+    right_dimension: Option<Dimension>,
+    message: impl Fn(Dimension, Dimension) -> String,
+) -> Result<Option<Dimension>, Diagnostic<'src>> {
+    match (left_dimension, right_dimension) {
+        (Some(l), Some(r)) if l != r => {
+            let fragment = fragment(right).or_else(|| fragment(left));
+            Err(Diagnostic::error(fragment.unwrap_or(source), message(l, r)))
+        }
+        _ => Ok(left_dimension.or(right_dimension)),
+    }
+}
+
+/// Source of the first unit, variable or number written in the expression, to point a
+/// diagnostic at
+pub(crate) fn fragment<'src>(expression: &Expression<'src>) -> Option<&'src str> {
+    let mut fragment = None;
+    expression.rewrite(&mut |part| {
+        fragment = fragment.or(match part {
+            Expression::Quantity(quantity) => quantity.unit.factors.first().map(|f| f.symbol),
+            Expression::Variable(variable) => Some(variable.identifier.text),
+            Expression::Literal(literal) => Some(literal.text()),
+            _ => None,
+        });
+        fragment.map(|_| part.clone())
+    });
+    fragment
+}
+
+/// `to(5 km, m)` as `5000 m`, when the value is a number and the units measure the same thing
+pub(crate) fn convert<'src>(inputs: &[Expression<'src>]) -> Option<Expression<'src>> {
+    let [Expression::Quantity(quantity), unit] = inputs else {
+        return None;
+    };
+    let unit = Unit::from_expression(unit)?;
+    if quantity.unit.dimension()? != unit.dimension()? {
+        return None;
+    }
+    let value = exact(&quantity.value)?;
+    let ratio = &quantity.unit.scale() / &unit.scale();
+    let value = match &value * &ratio {
+        // Read as a decimal when it is one, so `1.5 km` rather than `3/2 km`
+        Number::Exact(exact) if !exact.is_integer() && terminates(&exact) => {
+            Number::Float(exact.to_f64()?)
+        }
+        value => value,
+    };
+    Some(Expression::Quantity(Quantity {
+        value: Box::new(Expression::Number(value)),
+        unit,
+    }))
+}
+
+/// `3 m * 2 s` as `6 m s`, or `6 m / 2 m` as `3`, when it's a product or quotient of
+/// quantities and numbers with at least one quantity
+pub(crate) fn multiply<'src>(binary: &Binary<'src>) -> Option<Expression<'src>> {
+    let divide = match binary.operator {
+        BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => false,
+        BinaryOperator::Divide => true,
+        _ => return None,
+    };
+    let parts = |expression: &Expression<'src>| match expression {
+        Expression::Quantity(quantity) => Some((*quantity.value.clone(), quantity.unit.clone())),
+        Expression::Number(_) | Expression::Literal(_) => {
+            Some((expression.clone(), Unit { factors: vec![] }))
+        }
+        _ => None,
+    };
+    let ((left, left_unit), (right, right_unit)) = (parts(&binary.left)?, parts(&binary.right)?);
+    if left_unit.factors.is_empty() && right_unit.factors.is_empty() {
+        return None;
+    }
+    let right_unit = if divide {
+        right_unit.pow(-1)?
+    } else {
+        right_unit
+    };
+    // Each symbol once, with the exponents added up
+    let mut factors: Vec<UnitFactor<'src>> = Vec::new();
+    for factor in left_unit.factors.into_iter().chain(right_unit.factors) {
+        match factors
+            .iter_mut()
+            .find(|other| other.symbol == factor.symbol)
+        {
+            Some(other) => other.exponent = other.exponent.checked_add(factor.exponent)?,
+            None => factors.push(factor),
+        }
+    }
+    factors.retain(|factor| factor.exponent != 0);
+    let value = Expression::Binary(Binary {
+        operator: binary.operator,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+    .simplify();
+    if factors.is_empty() {
+        return Some(value);
+    }
+    Some(Expression::Quantity(Quantity {
+        value: Box::new(value),
+        unit: Unit { factors },
+    }))
+}
+
+/// The value of a number as it was written or worked out, exactly if it was written exactly
+fn exact(expression: &Expression<'_>) -> Option<Number> {
+    match expression {
+        Expression::Number(number) => Some(number.clone()),
+        Expression::Literal(Literal::Whole(whole)) => {
+            Some(BigInt::from(whole.value.clone()).into())
+        }
+        Expression::Literal(Literal::Float(float)) => Some(decimal(&float.value).into()),
+        Expression::Literal(literal) => literal.to_f64().ok().map(Number::from),
+        Expression::Unary(Unary {
+            operator: UnaryOperator::Negate,
+            operand,
+        }) => exact(operand).map(|number| -&number),
+        Expression::Group(group) => exact(&group.inner),
+        _ => None,
+    }
+}
+
+fn decimal(decimal: &Decimal) -> BigRational {
+    let scale = i32::try_from(decimal.scale).unwrap_or(i32::MAX);
+    BigRational::from_integer(decimal.digits.clone().into()) * power_of_ten(-scale)
+}
+
+/// Whether it's written out in full as a decimal, so its denominator only has factors 2 and 5
+fn terminates(exact: &BigRational) -> bool {
+    let mut denominator = exact.denom().abs();
+    for factor in [2, 5] {
+        let factor = BigInt::from(factor);
+        while (&denominator % &factor).is_zero() {
+            denominator /= &factor;
+        }
+    }
+    denominator.is_one()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::unicode::Unicode;
+
+    fn check(str: &str) -> Result<(), String> {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        ast.statements
+            .iter()
+            .zip(&ast.sources)
+            .try_for_each(|(statement, source)| statement.check_units(source))
+            .map_err(|diagnostic| diagnostic.render(str))
+    }
+
+    fn simplify(str: &str) -> String {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        Unicode(&ast.simplify()).to_string()
+    }
+
+    #[test]
+    fn lookup() {
+        let km = super::lookup("km").unwrap();
+        assert_eq!(km.scale, 1000.into());
+        assert_eq!(km.name, "Kilometers");
+        assert_eq!(super::lookup("kg").unwrap().scale, 1.into());
+        assert_eq!(super::lookup("min").unwrap().scale, 60.into());
+        assert_eq!(super::lookup("Pa").unwrap().name, "Pascals");
+        assert_eq!(super::lookup("mPa").unwrap().name, "Millipascals");
+        assert_eq!(super::lookup("kmin"), None);
+        assert_eq!(super::lookup("x"), None);
+    }
+
+    #[test]
+    fn dimensions() {
+        assert_eq!(check("9.81 m/s^2"), Ok(()));
+        assert_eq!(check("3 kg * 2 m/s^2 = 6 N"), Ok(()));
+        assert_eq!(check("1 km + 300 m"), Ok(()));
+        assert_eq!(check("sqrt(4 m^2) + 1 m"), Ok(()));
+        assert_eq!(check("x + 2 s"), Ok(()));
+        // Too large to count, so not known
+        assert_eq!(check("(2 m^2)^2000000000 + 1 s"), Ok(()));
+        assert_eq!(check("m^2147483647 * 2 m"), Ok(()));
+        assert_eq!(
+            check("2 m + 3 s"),
+            Err("1:9: error: cannot add s to m".to_string())
+        );
+        assert_eq!(
+            check("1 J - 2 N"),
+            Err("1:9: error: cannot subtract kg·m/s^2 from kg·m^2/s^2".to_string())
+        );
+        assert_eq!(
+            check("5 m/s = 3 s"),
+            Err("1:11: error: cannot compare s with m/s".to_string())
+        );
+        assert_eq!(
+            check("2 m + 1"),
+            Err("1:7: error: cannot add a plain number to m".to_string())
+        );
+        assert_eq!(
+            check("sin(2 m)"),
+            Err("1:7: error: `sin` needs a plain number, not m".to_string())
+        );
+        assert_eq!(
+            check("[1 m + 1 kg]"),
+            Err("1:10: error: cannot add kg to m".to_string())
+        );
+        assert_eq!(
+            check("to(5 s, m)"),
+            Err("1:6: error: cannot convert s to m".to_string())
+        );
+    }
+
+    #[test]
+    fn convert() {
+        assert_eq!(simplify("to(5 km, m)"), "5000 m");
+        assert_eq!(simplify("to(1500 m, km)"), "1.5 km");
+        assert_eq!(simplify("to(36 km/h, m/s)"), "10 m/s");
+        assert_eq!(simplify("to(1 h, min)"), "60 min");
+        assert_eq!(simplify("to(2.5 L, m^3)"), "0.0025 m³");
+        assert_eq!(simplify("to(1 m, s)"), "to(1 m, s)");
+        assert_eq!(simplify("to(3 kg * 2 m/s^2, N)"), "6 N");
+    }
+
+    #[test]
+    fn multiply() {
+        assert_eq!(simplify("3 m * 2"), "6 m");
+        assert_eq!(simplify("2 * 3 m * 4 s"), "24 m·s");
+        assert_eq!(simplify("6 m / 2 s"), "3 m/s");
+        assert_eq!(simplify("6 m / 2 m"), "3");
+        assert_eq!(simplify("x * 3 m"), "x·3 m");
+    }
+}
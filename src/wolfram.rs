@@ -0,0 +1,715 @@
+//! Wolfram Language backend, for pasting into Mathematica or WolframAlpha. Wrap any
+//! [`ast`](crate::ast) node in [`Wolfram`] and format it.
+
+use std::fmt;
+
+use crate::{ast::*, eval::Number, units};
+
+/// Displays the wrapped node as Wolfram Language input
+pub struct Wolfram<'a, T: ?Sized>(pub &'a T);
+
+/// hometex function names and what Wolfram calls them
+const WOLFRAM_FUNCTIONS: &[(&str, &str)] = &[
+    ("sin", "Sin"),
+    ("cos", "Cos"),
+    ("tan", "Tan"),
+    ("csc", "Csc"),
+    ("sec", "Sec"),
+    ("cot", "Cot"),
+    ("arcsin", "ArcSin"),
+    ("arccos", "ArcCos"),
+    ("arctan", "ArcTan"),
+    ("sinh", "Sinh"),
+    ("cosh", "Cosh"),
+    ("tanh", "Tanh"),
+    ("coth", "Coth"),
+    ("ln", "Log"),
+    ("log", "Log10"),
+    ("lg", "Log2"),
+    ("exp", "Exp"),
+    ("sqrt", "Sqrt"),
+    ("abs", "Abs"),
+    ("floor", "Floor"),
+    ("ceil", "Ceiling"),
+    ("round", "Round"),
+    ("gcd", "GCD"),
+    ("lcm", "LCM"),
+    ("max", "Max"),
+    ("min", "Min"),
+    ("det", "Det"),
+    ("mod", "Mod"),
+];
+
+/// Symbols Wolfram has a built in name for
+const WOLFRAM_SYMBOLS: &[(&str, &str)] = &[("pi", "Pi"), ("infinity", "Infinity")];
+
+impl fmt::Display for Wolfram<'_, Ast<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, statement) in self.0.statements.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", Wolfram(statement))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Statement<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Statement::Definition(definition) => write!(
+                f,
+                "{} = {}",
+                Wolfram(&definition.name()),
+                Wolfram(&definition.value)
+            ),
+            Statement::FunctionDefinition(function_definition) => {
+                write!(f, "{}[", Wolfram(&function_definition.identifier))?;
+                for (i, parameter) in function_definition.parameters.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}_", Wolfram(parameter))?;
+                }
+                write!(f, "] := {}", Wolfram(&function_definition.body))
+            }
+            Statement::Equation(relation) => write!(f, "{}", Wolfram(relation)),
+            Statement::Expression(expression) => write!(f, "{}", Wolfram(expression)),
+            Statement::Derivation(derivation) => write!(f, "{}", Wolfram(derivation)),
+        }
+    }
+}
+
+/// Every step as its own claim. `= rhs` steps are written against the step before.
+impl fmt::Display for Wolfram<'_, Derivation<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut claims = Vec::new();
+        let mut previous: Option<&Expression> = None;
+        for step in &self.0.steps {
+            match (step.kind, previous) {
+                (StepKind::Continues(operator), Some(previous)) => claims.push(format!(
+                    "{} {} {}",
+                    operand(previous, Precedence::Sum),
+                    Wolfram(&operator),
+                    operand(&step.expression, Precedence::Sum)
+                )),
+                // A bare starting expression only makes sense together with the next step
+                (StepKind::Start, _) if !matches!(step.expression, Expression::Relation(_)) => {}
+                _ => claims.push(Wolfram(&step.expression).to_string()),
+            }
+            previous = Some(match &step.expression {
+                Expression::Relation(relation) => relation
+                    .rest
+                    .last()
+                    .map(|(_, right)| right)
+                    .unwrap_or(&relation.first),
+                expression => expression,
+            });
+        }
+        if claims.is_empty() {
+            if let Some(step) = self.0.steps.first() {
+                claims.push(Wolfram(&step.expression).to_string());
+            }
+        }
+        write!(f, "{}", claims.join("\n"))
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Expression<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Expression::Literal(literal) => write!(f, "{}", Wolfram(literal)),
+            Expression::Number(number) => write!(f, "{}", Wolfram(number)),
+            Expression::Quantity(quantity) => write!(
+                f,
+                "Quantity[{}, {}]",
+                Wolfram(quantity.value.as_ref()),
+                Wolfram(&quantity.unit)
+            ),
+            // Wolfram's imaginary unit is `I`, lower case `i` is just another symbol
+            Expression::Variable(variable) if variable.identifier.text == "i" => write!(f, "I"),
+            Expression::Variable(variable) => write!(f, "{}", Wolfram(&variable.identifier)),
+            Expression::FunctionCall(function_call) => write!(f, "{}", Wolfram(function_call)),
+            Expression::Subscript(subscript) => write!(
+                f,
+                "Subscript[{}, {}]",
+                Wolfram(subscript.base.as_ref()),
+                Wolfram(subscript.index.as_ref())
+            ),
+            Expression::Unary(unary) => write!(f, "{}", Wolfram(unary)),
+            Expression::Binary(binary) => write!(f, "{}", Wolfram(binary)),
+            Expression::Relation(relation) => write!(f, "{}", Wolfram(relation)),
+            Expression::BigOperator(big_operator) => write!(f, "{}", Wolfram(big_operator)),
+            Expression::Integral(integral) => write!(f, "{}", Wolfram(integral)),
+            Expression::Limit(limit) => write!(f, "{}", Wolfram(limit)),
+            Expression::Derivative(derivative) => write!(f, "{}", Wolfram(derivative)),
+            Expression::Vector(vector) => write!(f, "{}", list(&vector.elements)),
+            Expression::Piecewise(piecewise) => write!(f, "{}", Wolfram(piecewise)),
+            Expression::Set(set) => write!(f, "{}", list(&set.elements)),
+            Expression::SetBuilder(set_builder) => write!(f, "{}", Wolfram(set_builder)),
+            Expression::Interval(interval) => write!(f, "{}", Wolfram(interval)),
+            Expression::NumberSet(number_set) => write!(f, "{}", Wolfram(number_set)),
+            Expression::Delimited(delimited) => {
+                let head = match delimited.kind {
+                    DelimiterKind::Abs => "Abs",
+                    DelimiterKind::Norm => "Norm",
+                    DelimiterKind::Floor => "Floor",
+                    DelimiterKind::Ceil => "Ceiling",
+                };
+                write!(f, "{head}[{}]", Wolfram(delimited.inner.as_ref()))
+            }
+            // A point like `(1, 2)` is a list, already in braces
+            Expression::Group(group) if matches!(*group.inner, Expression::List(_)) => {
+                write!(f, "{}", Wolfram(group.inner.as_ref()))
+            }
+            Expression::Group(group) => write!(f, "({})", Wolfram(group.inner.as_ref())),
+            Expression::List(values) => write!(f, "{}", list(&values.elements)),
+            Expression::Matrix(matrix) => {
+                let rows: Vec<String> = matrix.rows.iter().map(|row| list(row)).collect();
+                write!(f, "{{{}}}", rows.join(", "))
+            }
+        }
+    }
+}
+
+/// `"Meters"/"Seconds"^2`, with Wolfram's names for the units
+impl fmt::Display for Wolfram<'_, Unit<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let written = |powers: &[(&str, i32)]| {
+            let written: Vec<String> = powers
+                .iter()
+                .map(|(symbol, exponent)| {
+                    let name = units::lookup(symbol).map_or(symbol.to_string(), |unit| unit.name);
+                    match exponent {
+                        1 => format!("\"{name}\""),
+                        _ => format!("\"{name}\"^{exponent}"),
+                    }
+                })
+                .collect();
+            written.join("*")
+        };
+        let (numerator, denominator) = self.0.fraction();
+        write!(f, "{}", written(&numerator))?;
+        if !denominator.is_empty() {
+            write!(f, "/{}", written(&denominator))?;
+        }
+        Ok(())
+    }
+}
+
+/// `{1, 2, 3}`
+fn list(elements: &[Expression<'_>]) -> String {
+    let elements: Vec<String> = elements
+        .iter()
+        .map(|element| Wolfram(element).to_string())
+        .collect();
+    format!("{{{}}}", elements.join(", "))
+}
+
+/// `expression`, bracketed if it binds looser than `precedence`
+fn operand(expression: &Expression<'_>, precedence: Precedence) -> String {
+    if expression.precedence() < precedence {
+        format!("({})", Wolfram(expression))
+    } else {
+        Wolfram(expression).to_string()
+    }
+}
+
+/// Right hand side of a binary operator. Also brackets negation so `a - (-b)` stays readable.
+fn right_operand(expression: &Expression<'_>, precedence: Precedence) -> String {
+    match expression {
+        Expression::Unary(_) => format!("({})", Wolfram(expression)),
+        Expression::Number(number) if number.is_negative() => format!("({})", Wolfram(expression)),
+        _ => operand(expression, precedence),
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Unary<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.operator {
+            UnaryOperator::Negate => write!(f, "-{}", operand(&self.0.operand, Precedence::Power)),
+            UnaryOperator::PlusMinus => {
+                write!(f, "PlusMinus[{}]", Wolfram(self.0.operand.as_ref()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Binary<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Binary {
+            operator,
+            left,
+            right,
+        } = self.0;
+        match operator {
+            BinaryOperator::Add => write!(
+                f,
+                "{} + {}",
+                operand(left, Precedence::Sum),
+                right_operand(right, Precedence::Sum)
+            ),
+            BinaryOperator::Subtract => write!(
+                f,
+                "{} - {}",
+                operand(left, Precedence::Sum),
+                right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::PlusMinus => write!(
+                f,
+                "PlusMinus[{}, {}]",
+                Wolfram(left.as_ref()),
+                Wolfram(right.as_ref())
+            ),
+            BinaryOperator::Multiply | BinaryOperator::ImplicitMultiply => write!(
+                f,
+                "{}*{}",
+                operand(left, Precedence::Product),
+                right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::Divide => write!(
+                f,
+                "{}/{}",
+                operand(left, Precedence::Product),
+                right_operand(right, Precedence::Power)
+            ),
+            BinaryOperator::Power => write!(
+                f,
+                "{}^{}",
+                operand(left, Precedence::Atom),
+                right_operand(right, Precedence::Atom)
+            ),
+            BinaryOperator::Union => write!(
+                f,
+                r"{} \[Union] {}",
+                operand(left, Precedence::Sum),
+                right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::Intersection => write!(
+                f,
+                r"{} \[Intersection] {}",
+                operand(left, Precedence::Product),
+                right_operand(right, Precedence::Product)
+            ),
+            BinaryOperator::Difference => write!(
+                f,
+                "Complement[{}, {}]",
+                Wolfram(left.as_ref()),
+                Wolfram(right.as_ref())
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Relation<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", operand(&self.0.first, Precedence::Sum))?;
+        for (operator, right) in &self.0.rest {
+            // Lists are already in braces
+            let right = match right {
+                Expression::List(_) => Wolfram(right).to_string(),
+                right => operand(right, Precedence::Sum),
+            };
+            write!(f, " {} {right}", Wolfram(operator))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Wolfram<'_, RelationOperator> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let wolfram = match self.0 {
+            RelationOperator::Equal => "==",
+            RelationOperator::NotEqual => "!=",
+            RelationOperator::Less => "<",
+            RelationOperator::LessEqual => "<=",
+            RelationOperator::Greater => ">",
+            RelationOperator::GreaterEqual => ">=",
+            RelationOperator::Approximately => r"\[TildeTilde]",
+            RelationOperator::In => r"\[Element]",
+            RelationOperator::Subset => r"\[SubsetEqual]",
+        };
+        write!(f, "{wolfram}")
+    }
+}
+
+impl fmt::Display for Wolfram<'_, BigOperator<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let BigOperator {
+            kind,
+            index,
+            from,
+            to,
+            body,
+        } = self.0;
+        let head = match kind {
+            BigOperatorKind::Sum => "Sum",
+            BigOperatorKind::Product => "Product",
+        };
+        let (index, body) = renamed(index, body, &[from, to]);
+        write!(
+            f,
+            "{head}[{}, {{{}, {}, {}}}]",
+            Wolfram(&body),
+            Wolfram(&index),
+            Wolfram(from.as_ref()),
+            Wolfram(to.as_ref())
+        )
+    }
+}
+
+/// An index called `i` renamed to a letter nothing else uses, since `I` can't be an index
+fn renamed<'src>(
+    index: &Identifier<'src>,
+    body: &Expression<'src>,
+    others: &[&Expression<'src>],
+) -> (Identifier<'src>, Expression<'src>) {
+    if index.text != "i" {
+        return (index.clone(), body.clone());
+    }
+    let free = |text| {
+        let identifier = Identifier { text };
+        !body.mentions(&identifier) && !others.iter().any(|other| other.mentions(&identifier))
+    };
+    let Some(text) = ["k", "j", "m", "n", "p", "q"]
+        .into_iter()
+        .find(|text| free(text))
+    else {
+        return (index.clone(), body.clone());
+    };
+    let replacement = Identifier { text };
+    let body = body.rewrite(&mut |expression| match expression {
+        Expression::Variable(variable) if variable.identifier.text == "i" => {
+            Some(Expression::Variable(Variable {
+                identifier: replacement.clone(),
+            }))
+        }
+        // An inner sum over `i` has its own index
+        Expression::BigOperator(inner) if inner.index.text == "i" => Some(expression.clone()),
+        _ => None,
+    });
+    (replacement, body)
+}
+
+impl fmt::Display for Wolfram<'_, Integral<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Integral {
+            integrand,
+            variable,
+            bounds,
+        } = self.0;
+        match bounds {
+            Some((lower, upper)) => write!(
+                f,
+                "Integrate[{}, {{{}, {}, {}}}]",
+                Wolfram(integrand.as_ref()),
+                Wolfram(variable),
+                Wolfram(lower.as_ref()),
+                Wolfram(upper.as_ref())
+            ),
+            None => write!(
+                f,
+                "Integrate[{}, {}]",
+                Wolfram(integrand.as_ref()),
+                Wolfram(variable)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Limit<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Limit {
+            variable,
+            approaching,
+            body,
+        } = self.0;
+        write!(
+            f,
+            "Limit[{}, {} -> {}]",
+            Wolfram(body.as_ref()),
+            Wolfram(variable),
+            Wolfram(approaching.as_ref())
+        )
+    }
+}
+
+impl fmt::Display for Wolfram<'_, SetBuilder<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let SetBuilder {
+            variable,
+            domain,
+            condition,
+        } = self.0;
+        write!(f, "ImplicitRegion[")?;
+        if let Some(domain) = domain {
+            write!(
+                f,
+                r"{} \[Element] {} && ",
+                Wolfram(variable),
+                Wolfram(domain.as_ref())
+            )?;
+        }
+        write!(
+            f,
+            "{}, {{{}}}]",
+            operand(condition, Precedence::Relation),
+            Wolfram(variable)
+        )
+    }
+}
+
+/// `Interval` is always closed, so other intervals are written as a region
+impl fmt::Display for Wolfram<'_, Interval<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Interval {
+            lower,
+            upper,
+            lower_closed,
+            upper_closed,
+        } = self.0;
+        if *lower_closed && *upper_closed {
+            return write!(
+                f,
+                "Interval[{{{}, {}}}]",
+                Wolfram(lower.as_ref()),
+                Wolfram(upper.as_ref())
+            );
+        }
+        let comparison = |closed: bool| if closed { "<=" } else { "<" };
+        write!(
+            f,
+            r"ImplicitRegion[{} {} \[FormalX] {} {}, {{\[FormalX]}}]",
+            operand(lower, Precedence::Sum),
+            comparison(*lower_closed),
+            comparison(*upper_closed),
+            operand(upper, Precedence::Sum)
+        )
+    }
+}
+
+impl fmt::Display for Wolfram<'_, NumberSet> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let wolfram = match self.0 {
+            NumberSet::Natural => "NonNegativeIntegers",
+            NumberSet::Integer => "Integers",
+            NumberSet::Rational => "Rationals",
+            NumberSet::Real => "Reals",
+            NumberSet::Complex => "Complexes",
+        };
+        write!(f, "{wolfram}")
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Piecewise<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Piecewise { arms, otherwise } = self.0;
+        let arms: Vec<String> = arms
+            .iter()
+            .map(|arm| format!("{{{}, {}}}", Wolfram(&arm.value), Wolfram(&arm.condition)))
+            .collect();
+        write!(f, "Piecewise[{{{}}}", arms.join(", "))?;
+        if let Some(otherwise) = otherwise {
+            write!(f, ", {}", Wolfram(otherwise.as_ref()))?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Derivative<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Derivative::Leibniz { variable, body } => {
+                write!(f, "D[{}, {}]", Wolfram(body.as_ref()), Wolfram(variable))
+            }
+            Derivative::Prime {
+                function,
+                order,
+                inputs,
+            } => {
+                write!(f, "{}{}", Wolfram(function), "'".repeat(*order))?;
+                let Some(inputs) = inputs else {
+                    return Ok(());
+                };
+                write!(f, "[")?;
+                for (i, input) in inputs.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", Wolfram(input))?;
+                }
+                write!(f, "]")
+            }
+            Derivative::Partial { body, .. } => {
+                write!(f, "D[{}", Wolfram(body.as_ref()))?;
+                for (variable, count) in self.0.grouped_variables() {
+                    match count {
+                        1 => write!(f, ", {}", Wolfram(variable))?,
+                        count => write!(f, ", {{{}, {count}}}", Wolfram(variable))?,
+                    }
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Literal<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Literal::Whole(whole) => match whole.radix {
+                Radix::Decimal => write!(f, "{}", digits_text(whole.text)),
+                radix => write!(f, "{}^^{}", radix.base(), digits_text(whole.digits())),
+            },
+            Literal::Float(float) => write!(f, "{}", digits_text(float.text)),
+            Literal::Scientific(scientific) => {
+                let (significand, exponent) = scientific.parts();
+                let exponent = exponent.trim_start_matches('+');
+                write!(f, "{}*^{}", digits_text(significand), digits_text(exponent))
+            }
+        }
+    }
+}
+
+/// Exact fractions stay exact as `1/3`
+impl fmt::Display for Wolfram<'_, Number> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Number::Float(float) if float.is_infinite() && *float < 0.0 => write!(f, "-Infinity"),
+            Number::Float(float) if float.is_infinite() => write!(f, "Infinity"),
+            number => write!(f, "{number}"),
+        }
+    }
+}
+
+impl fmt::Display for Wolfram<'_, FunctionCall<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.0.identifier.text;
+        match WOLFRAM_FUNCTIONS.iter().find(|(ours, _)| *ours == name) {
+            Some((_, wolfram)) => write!(f, "{wolfram}[")?,
+            None => write!(f, "{}[", Wolfram(&self.0.identifier))?,
+        }
+        for (i, input) in self.0.inputs.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", Wolfram(input))?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl fmt::Display for Wolfram<'_, Identifier<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(symbol) = self.0.symbol() else {
+            return write!(f, "{}", self.0.text);
+        };
+        match WOLFRAM_SYMBOLS
+            .iter()
+            .find(|(name, _)| *name == symbol.name)
+        {
+            Some((_, wolfram)) => write!(f, "{wolfram}"),
+            None => write!(f, "{}", symbol.unicode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn wolfram(str: &str) -> String {
+        let tokens = crate::lexer::parse(str).unwrap();
+        let ast = crate::ast::parse(&tokens).unwrap();
+        Wolfram(&ast).to_string()
+    }
+
+    #[test]
+    fn big_operators() {
+        assert_eq!(wolfram("sum(i, 1, n, i^2)"), "Sum[k^2, {k, 1, n}]");
+        assert_eq!(wolfram("sum(i, 1, k, i^2)"), "Sum[j^2, {j, 1, k}]");
+        assert_eq!(wolfram("prod(k, 1, 10, k)"), "Product[k, {k, 1, 10}]");
+        assert_eq!(wolfram("int(f(x), x, 0, 1)"), "Integrate[f[x], {x, 0, 1}]");
+        assert_eq!(wolfram("int(x^2, x)"), "Integrate[x^2, x]");
+        assert_eq!(wolfram("lim(x, 0, sin(x)/x)"), "Limit[Sin[x]/x, x -> 0]");
+        assert_eq!(
+            wolfram("lim(x, infinity, 1/x)"),
+            "Limit[1/x, x -> Infinity]"
+        );
+    }
+
+    #[test]
+    fn derivatives() {
+        assert_eq!(wolfram("d/dx(sin(x))"), "D[Sin[x], x]");
+        assert_eq!(wolfram("f''(x)"), "f''[x]");
+        assert_eq!(wolfram("pd(f, x, x, y)"), "D[f, {x, 2}, y]");
+    }
+
+    #[test]
+    fn piecewise() {
+        assert_eq!(
+            wolfram("piecewise(x^2 if x < 0, x otherwise)"),
+            "Piecewise[{{x^2, x < 0}}, x]"
+        );
+    }
+
+    #[test]
+    fn sets() {
+        assert_eq!(wolfram("(1, 2)"), "{1, 2}");
+        assert_eq!(
+            wolfram("{x in R | x > 0}"),
+            r"ImplicitRegion[x \[Element] Reals && x > 0, {x}]"
+        );
+        assert_eq!(
+            wolfram("A union B intersect D \\ {1, 2}"),
+            r"Complement[A \[Union] B \[Intersection] D, {1, 2}]"
+        );
+        assert_eq!(wolfram("x in [0, 1]"), r"x \[Element] Interval[{0, 1}]");
+        assert_eq!(
+            wolfram("x in [0, 1)"),
+            r"x \[Element] ImplicitRegion[0 <= \[FormalX] < 1, {\[FormalX]}]"
+        );
+    }
+
+    #[test]
+    fn delimited() {
+        assert_eq!(wolfram("|x| + ||v||"), "Abs[x] + Norm[v]");
+        assert_eq!(wolfram("ceil(x/2)"), "Ceiling[x/2]");
+    }
+
+    #[test]
+    fn matrices() {
+        assert_eq!(wolfram("[1, 2, 3]"), "{1, 2, 3}");
+        assert_eq!(wolfram("[[1,2],[3,4]]"), "{{1, 2}, {3, 4}}");
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(wolfram("2x^(n+1) - 1"), "2*x^(n + 1) - 1");
+        assert_eq!(wolfram("x_1 = 6.022e23"), "Subscript[x, 1] == 6.022*^23");
+        assert_eq!(wolfram("0xFF + 2pi"), "16^^FF + 2*Pi");
+        assert_eq!(wolfram("0 < theta <= 1"), "0 < θ <= 1");
+        assert_eq!(wolfram("3 + 4i"), "3 + 4*I");
+    }
+
+    #[test]
+    fn quantities() {
+        assert_eq!(
+            wolfram("9.81 m/s^2"),
+            r#"Quantity[9.81, "Meters"/"Seconds"^2]"#
+        );
+        assert_eq!(wolfram("5 km"), r#"Quantity[5, "Kilometers"]"#);
+        assert_eq!(wolfram("2 kg m"), r#"Quantity[2, "Kilograms"*"Meters"]"#);
+    }
+
+    #[test]
+    fn statements() {
+        assert_eq!(wolfram("f(x, y) = x*y^2"), "f[x_, y_] := x*y^2");
+        assert_eq!(wolfram("let g = 9.81"), "g = 9.81");
+        assert_eq!(
+            wolfram("(x+1)^2\n  = x^2 + 2x + 1"),
+            "(x + 1)^2 == x^2 + 2*x + 1"
+        );
+    }
+}